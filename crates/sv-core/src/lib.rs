@@ -1,5 +1,10 @@
 //! Core domain entities, rules, and traits for SetupVault.
 
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -58,14 +63,44 @@ impl Tag {
         Ok(Self { value })
     }
 
+    /// Create a tag, replacing a configured alias with its canonical tag. Aliases map one
+    /// level only: the canonical tag is not looked up again.
+    pub fn canonical(
+        value: impl Into<String>,
+        aliases: &BTreeMap<String, String>,
+    ) -> CoreResult<Self> {
+        let value = value.into();
+        match aliases.get(value.trim()) {
+            Some(canonical) => Self::new(canonical.clone()),
+            None => Self::new(value),
+        }
+    }
+
     /// Access the tag value.
     pub fn as_str(&self) -> &str {
         &self.value
     }
 }
 
+/// Build tags with [`Tag::canonical`], dropping repeats such as an alias given beside its
+/// canonical tag.
+pub fn canonical_tags<I, S>(values: I, aliases: &BTreeMap<String, String>) -> CoreResult<Vec<Tag>>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut tags: Vec<Tag> = Vec::new();
+    for value in values {
+        let tag = Tag::canonical(value, aliases)?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags)
+}
+
 /// Supported entry categories.
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "snake_case")]
 pub enum EntryType {
     /// Package manager installs.
@@ -80,6 +115,22 @@ pub enum EntryType {
     Other,
 }
 
+impl EntryType {
+    /// Every variant, in declaration order.
+    pub const ALL: [Self; 5] = [Self::Package, Self::Config, Self::Application, Self::Script, Self::Other];
+
+    /// Name of this type as written in entry files and config.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Package => "package",
+            Self::Config => "config",
+            Self::Application => "application",
+            Self::Script => "script",
+            Self::Other => "other",
+        }
+    }
+}
+
 /// The current lifecycle status of an entry.
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -92,6 +143,11 @@ pub enum EntryStatus {
     Ignored,
 }
 
+impl EntryStatus {
+    /// Every variant, in declaration order.
+    pub const ALL: [Self; 3] = [Self::Active, Self::Snoozed, Self::Ignored];
+}
+
 /// System metadata to help reproduce environments.
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct SystemInfo {
@@ -99,6 +155,122 @@ pub struct SystemInfo {
     pub os: String,
     /// Architecture identifier.
     pub arch: String,
+    /// Hostname of the machine that captured the change, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+}
+
+/// A user-defined detector declared under `custom_detectors` in `config.yaml`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(deny_unknown_fields)]
+pub struct CustomDetectorSpec {
+    /// Detector name, also used as the source label of its changes.
+    pub name: String,
+    /// Command whose stdout lists one item per line; run directly, not through a shell.
+    pub list_cmd: String,
+    /// Reproduction command for an item; `{name}` is replaced with the item.
+    pub install_cmd_template: String,
+}
+
+/// User-configurable inputs for building detectors, derived from `config.yaml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct DetectorConfig {
+    /// Domains watched by the macOS defaults detector; empty uses the curated list.
+    pub mac_defaults_domains: Vec<String>,
+    /// User-defined command detectors, added on every OS.
+    pub custom_detectors: Vec<CustomDetectorSpec>,
+    /// Detector names to build instead of the OS default set, from every known detector;
+    /// `None` keeps the defaults and an empty list disables detection.
+    pub enabled_detectors: Option<Vec<String>>,
+    /// Detector names to drop, applied after `enabled_detectors`.
+    pub disabled_detectors: Vec<String>,
+    /// Files watched by the dotfile detector; empty uses the detector's default list.
+    pub dotfile_paths: Vec<PathBuf>,
+    /// Executables used in place of a program name on `PATH`, keyed by that name (`brew`).
+    pub binaries: BTreeMap<String, String>,
+}
+
+impl DetectorConfig {
+    /// The executable to run for `program`: its configured override, or the name itself.
+    pub fn binary(&self, program: &str) -> String {
+        self.binaries
+            .get(program)
+            .cloned()
+            .unwrap_or_else(|| program.to_string())
+    }
+}
+
+/// Capture defaults declared under `entry_templates` in `config.yaml`, keyed by a source or
+/// an entry type.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct EntryTemplate {
+    /// Tags used when a capture gives none.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Verification guidance used when a capture gives none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<String>,
+    /// Rationale skeleton; `{title}` is replaced with the entry title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<String>,
+}
+
+impl EntryTemplate {
+    /// The rationale skeleton for an entry titled `title`, if the template has one.
+    pub fn rationale_for(&self, title: &str) -> Option<String> {
+        self.rationale.as_ref().map(|text| text.replace("{title}", title))
+    }
+}
+
+/// Default presentation order of the inbox, set with `inbox_order` in `config.yaml`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InboxOrder {
+    /// The order items were queued in.
+    #[default]
+    Insertion,
+    /// Items clustered by source, sources in alphabetical order.
+    SourceGrouped,
+    /// Items sorted by title, ignoring case.
+    Alphabetical,
+}
+
+impl InboxOrder {
+    /// Every variant, in declaration order.
+    pub const ALL: [Self; 3] = [Self::Insertion, Self::SourceGrouped, Self::Alphabetical];
+
+    /// Config value naming this order.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Insertion => "insertion",
+            Self::SourceGrouped => "source_grouped",
+            Self::Alphabetical => "alphabetical",
+        }
+    }
+
+    /// Sort inbox items in place; the sort is stable, so ties keep insertion order.
+    pub fn sort<T: Borrow<DetectedChange>>(self, items: &mut [T]) {
+        match self {
+            Self::Insertion => {}
+            Self::SourceGrouped => items.sort_by(|a, b| a.borrow().source.cmp(&b.borrow().source)),
+            Self::Alphabetical => {
+                items.sort_by_cached_key(|item| item.borrow().title.to_lowercase());
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for InboxOrder {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> CoreResult<Self> {
+        Self::ALL.into_iter().find(|order| order.as_str() == value).ok_or_else(|| {
+            CoreError::Validation(format!(
+                "unknown inbox order `{value}`; expected insertion, source_grouped, or alphabetical"
+            ))
+        })
+    }
 }
 
 /// A persisted record in the SetupVault.
@@ -126,6 +298,27 @@ pub struct Entry {
     pub rationale: Rationale,
     /// Optional verification guidance.
     pub verification: Option<String>,
+    /// Whether the entry is pinned to the top of the library.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Optional command that deliberately removes the change.
+    #[serde(default)]
+    pub uninstall_cmd: Option<String>,
+    /// Optional freeform maintenance notes, kept apart from the rationale.
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Optional multi-line script body for script entries.
+    #[serde(default)]
+    pub script_body: Option<String>,
+    /// Detector that reported the change; `None` for manual captures.
+    #[serde(default)]
+    pub detector: Option<String>,
+    /// When the entry's verification command last passed.
+    #[serde(default)]
+    pub last_verified: Option<DateTime<Utc>>,
+    /// Earlier rationales, oldest first, each with when it was replaced.
+    #[serde(default)]
+    pub rationale_history: Vec<(DateTime<Utc>, String)>,
 }
 
 impl Entry {
@@ -144,18 +337,8 @@ impl Entry {
         rationale: Rationale,
         verification: Option<String>,
     ) -> CoreResult<Self> {
-        let title = title.into();
-        if title.trim().is_empty() {
-            return Err(CoreError::Validation("title cannot be empty".into()));
-        }
-        let source = source.into();
-        if source.trim().is_empty() {
-            return Err(CoreError::Validation("source cannot be empty".into()));
-        }
-        let cmd = cmd.into();
-        if cmd.trim().is_empty() {
-            return Err(CoreError::Validation("cmd cannot be empty".into()));
-        }
+        let (title, source, cmd) = (title.into(), source.into(), cmd.into());
+        validate_identity(&title, &source, &cmd)?;
 
         Ok(Self {
             id,
@@ -169,8 +352,274 @@ impl Entry {
             tags,
             rationale,
             verification,
+            pinned: false,
+            uninstall_cmd: None,
+            notes: None,
+            script_body: None,
+            detector: None,
+            last_verified: None,
+            rationale_history: Vec::new(),
         })
     }
+
+    /// Start building an entry; see [`EntryBuilder`] for required and defaulted fields.
+    pub fn builder(id: Uuid, title: impl Into<String>) -> EntryBuilder {
+        EntryBuilder {
+            id,
+            title: title.into(),
+            entry_type: None,
+            source: None,
+            cmd: None,
+            system: None,
+            detected_at: None,
+            status: EntryStatus::Active,
+            tags: Vec::new(),
+            rationale: None,
+            verification: None,
+            pinned: false,
+            uninstall_cmd: None,
+            notes: None,
+            script_body: None,
+            detector: None,
+            last_verified: None,
+            rationale_history: Vec::new(),
+        }
+    }
+
+    /// Replace the rationale, appending the current one to the history stamped with `at`.
+    /// Returns `false` and leaves the entry alone when the text is unchanged.
+    pub fn revise_rationale(&mut self, rationale: Rationale, at: DateTime<Utc>) -> bool {
+        if rationale == self.rationale {
+            return false;
+        }
+        let previous = std::mem::replace(&mut self.rationale, rationale);
+        self.rationale_history.push((at, previous.as_str().to_string()));
+        true
+    }
+
+    /// Replace tag `from` with `to`, keeping a single `to` when the entry already has it.
+    /// Returns whether the entry had `from`.
+    pub fn replace_tag(&mut self, from: &Tag, to: &Tag) -> bool {
+        let Some(position) = self.tags.iter().position(|tag| tag == from) else {
+            return false;
+        };
+        if self.tags.contains(to) {
+            self.tags.remove(position);
+        } else {
+            self.tags[position] = to.clone();
+        }
+        true
+    }
+
+    /// Case-insensitive substring match used by `search` and the TUI filter: the title,
+    /// command, any tag, or the rationale contains `query`. An empty query matches everything.
+    pub fn matches(&self, query: &str) -> bool {
+        self.match_score(query).is_some()
+    }
+
+    /// Relevance of a [`Entry::matches`] hit, or `None` when the entry does not match.
+    ///
+    /// The best field wins: a title match outranks any tag match, which outranks a command
+    /// match, which outranks a rationale match. Within a field, a match nearer the start
+    /// scores higher, so a title starting with `query` ranks above one merely containing it.
+    pub fn match_score(&self, query: &str) -> Option<u32> {
+        let query = query.to_lowercase();
+        let field = |text: &str, weight: u32| {
+            let position = match_position(text, &query)?;
+            Some(weight + MATCH_POSITION_BONUS.saturating_sub(position as u32))
+        };
+        [
+            field(&self.title, 400),
+            self.tags.iter().filter_map(|tag| field(tag.as_str(), 300)).max(),
+            field(&self.cmd, 200),
+            field(self.rationale.as_str(), 100),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+}
+
+/// Score added for a match at the start of a field, shrinking by one per character of
+/// offset; kept below the gap between field weights so field order always dominates.
+const MATCH_POSITION_BONUS: u32 = 99;
+
+/// Whether `text` contains the already-lowercased `query`, ignoring case.
+fn matches_text(text: &str, query: &str) -> bool {
+    text.to_lowercase().contains(query)
+}
+
+/// Character offset of the already-lowercased `query` in `text`, ignoring case.
+fn match_position(text: &str, query: &str) -> Option<usize> {
+    let text = text.to_lowercase();
+    let index = text.find(query)?;
+    Some(text[..index].chars().count())
+}
+
+fn validate_identity(title: &str, source: &str, cmd: &str) -> CoreResult<()> {
+    if title.trim().is_empty() {
+        return Err(CoreError::Validation("title cannot be empty".into()));
+    }
+    if source.trim().is_empty() {
+        return Err(CoreError::Validation("source cannot be empty".into()));
+    }
+    if cmd.trim().is_empty() {
+        return Err(CoreError::Validation("cmd cannot be empty".into()));
+    }
+    Ok(())
+}
+
+fn required<T>(value: Option<T>, field: &str) -> CoreResult<T> {
+    value.ok_or_else(|| CoreError::Validation(format!("missing required field `{field}`")))
+}
+
+/// Named-field construction for [`Entry`].
+///
+/// `entry_type`, `source`, `cmd`, `system`, and `rationale` are required. `detected_at`
+/// defaults to now, `status` to active, and the remaining fields to empty or unset.
+#[derive(Clone, Debug)]
+pub struct EntryBuilder {
+    id: Uuid,
+    title: String,
+    entry_type: Option<EntryType>,
+    source: Option<String>,
+    cmd: Option<String>,
+    system: Option<SystemInfo>,
+    detected_at: Option<DateTime<Utc>>,
+    status: EntryStatus,
+    tags: Vec<Tag>,
+    rationale: Option<Rationale>,
+    verification: Option<String>,
+    pinned: bool,
+    uninstall_cmd: Option<String>,
+    notes: Option<String>,
+    script_body: Option<String>,
+    detector: Option<String>,
+    last_verified: Option<DateTime<Utc>>,
+    rationale_history: Vec<(DateTime<Utc>, String)>,
+}
+
+impl EntryBuilder {
+    /// Set the entry category.
+    pub fn entry_type(mut self, entry_type: EntryType) -> Self {
+        self.entry_type = Some(entry_type);
+        self
+    }
+
+    /// Set the source label.
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Set the command that reproduces the change.
+    pub fn cmd(mut self, cmd: impl Into<String>) -> Self {
+        self.cmd = Some(cmd.into());
+        self
+    }
+
+    /// Set the capturing system's metadata.
+    pub fn system(mut self, system: SystemInfo) -> Self {
+        self.system = Some(system);
+        self
+    }
+
+    /// Set when the change was detected.
+    pub fn detected_at(mut self, detected_at: DateTime<Utc>) -> Self {
+        self.detected_at = Some(detected_at);
+        self
+    }
+
+    /// Set the lifecycle status.
+    pub fn status(mut self, status: EntryStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Replace the tags.
+    pub fn tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Set the rationale.
+    pub fn rationale(mut self, rationale: Rationale) -> Self {
+        self.rationale = Some(rationale);
+        self
+    }
+
+    /// Set verification guidance.
+    pub fn verification(mut self, verification: impl Into<String>) -> Self {
+        self.verification = Some(verification.into());
+        self
+    }
+
+    /// Pin or unpin the entry.
+    pub fn pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Set the uninstall command.
+    pub fn uninstall_cmd(mut self, uninstall_cmd: impl Into<String>) -> Self {
+        self.uninstall_cmd = Some(uninstall_cmd.into());
+        self
+    }
+
+    /// Set the notes.
+    pub fn notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = Some(notes.into());
+        self
+    }
+
+    /// Set the script body.
+    pub fn script_body(mut self, script_body: impl Into<String>) -> Self {
+        self.script_body = Some(script_body.into());
+        self
+    }
+
+    /// Set the detector that reported the change.
+    pub fn detector(mut self, detector: impl Into<String>) -> Self {
+        self.detector = Some(detector.into());
+        self
+    }
+
+    /// Set when verification last passed.
+    pub fn last_verified(mut self, last_verified: DateTime<Utc>) -> Self {
+        self.last_verified = Some(last_verified);
+        self
+    }
+
+    /// Replace the earlier rationales, oldest first.
+    pub fn rationale_history(mut self, history: Vec<(DateTime<Utc>, String)>) -> Self {
+        self.rationale_history = history;
+        self
+    }
+
+    /// Validate required fields and the same invariants as [`Entry::new`].
+    pub fn build(self) -> CoreResult<Entry> {
+        let mut entry = Entry::new(
+            self.id,
+            self.title,
+            required(self.entry_type, "entry_type")?,
+            required(self.source, "source")?,
+            required(self.cmd, "cmd")?,
+            required(self.system, "system")?,
+            self.detected_at.unwrap_or_else(Utc::now),
+            self.status,
+            self.tags,
+            required(self.rationale, "rationale")?,
+            self.verification,
+        )?;
+        entry.pinned = self.pinned;
+        entry.uninstall_cmd = self.uninstall_cmd;
+        entry.notes = self.notes;
+        entry.script_body = self.script_body;
+        entry.detector = self.detector;
+        entry.last_verified = self.last_verified;
+        entry.rationale_history = self.rationale_history;
+        Ok(entry)
+    }
 }
 
 /// A change detected by a detector before user approval.
@@ -194,6 +643,134 @@ pub struct DetectedChange {
     pub detected_at: DateTime<Utc>,
     /// Suggested tags.
     pub tags: Vec<Tag>,
+    /// Name of the detector that reported the change, set when the detector builds it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detector: Option<String>,
+}
+
+impl DetectedChange {
+    /// Start building a detected change; see [`DetectedChangeBuilder`] for required and
+    /// defaulted fields.
+    pub fn builder(id: Uuid, title: impl Into<String>) -> DetectedChangeBuilder {
+        DetectedChangeBuilder {
+            id,
+            title: title.into(),
+            path: None,
+            entry_type: None,
+            source: None,
+            cmd: None,
+            system: None,
+            detected_at: None,
+            tags: Vec::new(),
+            detector: None,
+        }
+    }
+
+    /// Identity used to treat two detections as the same change: `(source, title)`.
+    pub fn dedupe_key(&self) -> (&str, &str) {
+        (&self.source, &self.title)
+    }
+
+    /// Case-insensitive substring match used by the TUI filter and bulk `--query` actions: the
+    /// title, source, command, or any tag contains `query`. Unlike [`Entry::matches`] the
+    /// source is searched, since a queued change is usually picked by the detector that found it.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        matches_text(&self.title, &query)
+            || matches_text(&self.source, &query)
+            || matches_text(&self.cmd, &query)
+            || self.tags.iter().any(|tag| matches_text(tag.as_str(), &query))
+    }
+}
+
+/// Named-field construction for [`DetectedChange`].
+///
+/// `entry_type`, `source`, `cmd`, and `system` are required and validated like
+/// [`Entry::new`]. `detected_at` defaults to now, `path` and `detector` to none, and `tags` to
+/// empty.
+#[derive(Clone, Debug)]
+pub struct DetectedChangeBuilder {
+    id: Uuid,
+    title: String,
+    path: Option<String>,
+    entry_type: Option<EntryType>,
+    source: Option<String>,
+    cmd: Option<String>,
+    system: Option<SystemInfo>,
+    detected_at: Option<DateTime<Utc>>,
+    tags: Vec<Tag>,
+    detector: Option<String>,
+}
+
+impl DetectedChangeBuilder {
+    /// Set the path associated with the change.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the entry category.
+    pub fn entry_type(mut self, entry_type: EntryType) -> Self {
+        self.entry_type = Some(entry_type);
+        self
+    }
+
+    /// Set the detector source.
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Set the command that reproduces the change.
+    pub fn cmd(mut self, cmd: impl Into<String>) -> Self {
+        self.cmd = Some(cmd.into());
+        self
+    }
+
+    /// Set the system metadata.
+    pub fn system(mut self, system: SystemInfo) -> Self {
+        self.system = Some(system);
+        self
+    }
+
+    /// Set when the change was detected.
+    pub fn detected_at(mut self, detected_at: DateTime<Utc>) -> Self {
+        self.detected_at = Some(detected_at);
+        self
+    }
+
+    /// Replace the suggested tags.
+    pub fn tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Set the name of the detector reporting the change.
+    pub fn detector(mut self, detector: impl Into<String>) -> Self {
+        self.detector = Some(detector.into());
+        self
+    }
+
+    /// Validate required fields and non-empty title, source, and command.
+    pub fn build(self) -> CoreResult<DetectedChange> {
+        let entry_type = required(self.entry_type, "entry_type")?;
+        let source = required(self.source, "source")?;
+        let cmd = required(self.cmd, "cmd")?;
+        let system = required(self.system, "system")?;
+        validate_identity(&self.title, &source, &cmd)?;
+        Ok(DetectedChange {
+            id: self.id,
+            path: self.path,
+            title: self.title,
+            entry_type,
+            source,
+            cmd,
+            system,
+            detected_at: self.detected_at.unwrap_or_else(Utc::now),
+            tags: self.tags,
+            detector: self.detector,
+        })
+    }
 }
 
 /// Repository abstraction for reading and writing entries.
@@ -202,7 +779,7 @@ pub trait VaultRepository {
     fn list(&self) -> CoreResult<Vec<Entry>>;
     /// Fetch a single entry by id.
     fn get(&self, id: Uuid) -> CoreResult<Option<Entry>>;
-    /// Create a new entry.
+    /// Create a new entry; errors if an entry with the same id already exists.
     fn create(&self, entry: &Entry) -> CoreResult<()>;
     /// Update an existing entry.
     fn update(&self, entry: &Entry) -> CoreResult<()>;
@@ -214,10 +791,66 @@ pub trait VaultRepository {
 pub trait Detector {
     /// Return the detector name.
     fn name(&self) -> &'static str;
-    /// Scan for changes and return detected changes.
+    /// Return the source label the detector's changes are stored under.
+    fn source(&self) -> &'static str {
+        self.name()
+    }
+    /// Report whether the detector's OS and backing tool are available.
+    fn is_available(&self) -> bool {
+        true
+    }
+    /// Scan for changes and return detected changes, each naming this detector.
     fn scan(&self) -> CoreResult<Vec<DetectedChange>>;
 }
 
+/// Vault repository that keeps entries in memory instead of on disk.
+///
+/// Mirrors `FsVault` semantics: `create` rejects an id that already exists, `update`
+/// overwrites any entry with the same id, `delete` is a no-op for unknown ids, and `list`
+/// orders entries by detection time, then id.
+#[derive(Debug, Default)]
+pub struct InMemoryVault {
+    entries: RefCell<HashMap<Uuid, Entry>>,
+}
+
+impl InMemoryVault {
+    /// Create an empty in-memory vault.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl VaultRepository for InMemoryVault {
+    fn list(&self) -> CoreResult<Vec<Entry>> {
+        let mut entries: Vec<Entry> = self.entries.borrow().values().cloned().collect();
+        entries.sort_by(|a, b| a.detected_at.cmp(&b.detected_at).then(a.id.cmp(&b.id)));
+        Ok(entries)
+    }
+
+    fn get(&self, id: Uuid) -> CoreResult<Option<Entry>> {
+        Ok(self.entries.borrow().get(&id).cloned())
+    }
+
+    fn create(&self, entry: &Entry) -> CoreResult<()> {
+        let mut entries = self.entries.borrow_mut();
+        if entries.contains_key(&entry.id) {
+            return Err(CoreError::Validation("entry id already exists".into()));
+        }
+        entries.insert(entry.id, entry.clone());
+        Ok(())
+    }
+
+    fn update(&self, entry: &Entry) -> CoreResult<()> {
+        self.entries.borrow_mut().insert(entry.id, entry.clone());
+        Ok(())
+    }
+
+    fn delete(&self, id: Uuid) -> CoreResult<()> {
+        self.entries.borrow_mut().remove(&id);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,6 +873,7 @@ mod tests {
         let system = SystemInfo {
             os: "macos".into(),
             arch: "arm64".into(),
+            hostname: None,
         };
 
         let entry = Entry::new(
@@ -258,4 +892,264 @@ mod tests {
 
         assert!(entry.is_ok());
     }
+
+    #[test]
+    fn in_memory_vault_round_trips_entries() {
+        let vault = InMemoryVault::new();
+        let system = SystemInfo {
+            os: "macos".into(),
+            arch: "arm64".into(),
+            hostname: None,
+        };
+        let mut entry = Entry::new(
+            Uuid::new_v4(),
+            "jq",
+            EntryType::Package,
+            "homebrew",
+            "brew install jq",
+            system,
+            Utc::now(),
+            EntryStatus::Active,
+            Vec::new(),
+            Rationale::new("needed for json parsing").unwrap(),
+            None,
+        )
+        .unwrap();
+
+        vault.create(&entry).unwrap();
+        assert_eq!(vault.get(entry.id).unwrap(), Some(entry.clone()));
+
+        entry.title = "jq (json)".into();
+        vault.update(&entry).unwrap();
+        assert_eq!(vault.list().unwrap(), vec![entry.clone()]);
+
+        vault.delete(entry.id).unwrap();
+        vault.delete(entry.id).unwrap();
+        assert!(vault.get(entry.id).unwrap().is_none());
+
+        let mut older = entry.clone();
+        older.detected_at = entry.detected_at - chrono::Duration::days(1);
+        let ids = [Uuid::from_u128(3), Uuid::from_u128(1), Uuid::from_u128(2)];
+        for (id, source) in ids.into_iter().zip([&entry, &entry, &older]) {
+            vault.create(&Entry { id, ..source.clone() }).unwrap();
+        }
+        let listed: Vec<Uuid> = vault.list().unwrap().iter().map(|entry| entry.id).collect();
+        assert_eq!(listed, [ids[2], ids[1], ids[0]]);
+    }
+
+    #[test]
+    fn builders_default_optional_fields_and_report_missing_ones() {
+        let system = SystemInfo {
+            os: "macos".into(),
+            arch: "arm64".into(),
+            hostname: None,
+        };
+        let id = Uuid::new_v4();
+        let entry = Entry::builder(id, "jq")
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd("brew install jq")
+            .system(system.clone())
+            .rationale(Rationale::new("needed for json parsing").unwrap())
+            .detector("homebrew")
+            .build()
+            .unwrap();
+        assert_eq!(entry.id, id);
+        assert_eq!(entry.status, EntryStatus::Active);
+        assert!(entry.tags.is_empty());
+        assert_eq!(entry.verification, None);
+        assert_eq!(entry.detector.as_deref(), Some("homebrew"));
+
+        let missing = Entry::builder(id, "jq")
+            .entry_type(EntryType::Package)
+            .cmd("brew install jq")
+            .system(system.clone())
+            .rationale(Rationale::new("needed for json parsing").unwrap())
+            .build();
+        assert_eq!(
+            missing.unwrap_err().to_string(),
+            "validation error: missing required field `source`"
+        );
+        let blank = Entry::builder(id, "  ")
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd("brew install jq")
+            .system(system.clone())
+            .rationale(Rationale::new("needed for json parsing").unwrap())
+            .build();
+        assert!(matches!(blank, Err(CoreError::Validation(_))));
+
+        let change = DetectedChange::builder(id, "jq")
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd("brew install jq")
+            .system(system.clone())
+            .build()
+            .unwrap();
+        assert_eq!(change.path, None);
+        assert_eq!(change.detector, None);
+        assert!(change.tags.is_empty());
+        let reported = DetectedChange::builder(id, "jq")
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd("brew install jq")
+            .system(system.clone())
+            .detector("homebrew")
+            .build()
+            .unwrap();
+        assert_eq!(reported.detector.as_deref(), Some("homebrew"));
+        let missing = DetectedChange::builder(id, "jq")
+            .source("homebrew")
+            .cmd("brew install jq")
+            .system(system)
+            .build();
+        assert_eq!(
+            missing.unwrap_err().to_string(),
+            "validation error: missing required field `entry_type`"
+        );
+    }
+
+    #[test]
+    fn matches_checks_title_cmd_tags_and_rationale() {
+        let system = SystemInfo {
+            os: "macos".into(),
+            arch: "arm64".into(),
+            hostname: None,
+        };
+        let entry = Entry::builder(Uuid::new_v4(), "jq")
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd("brew install jq")
+            .system(system.clone())
+            .tags(vec![Tag::new("JSON").unwrap()])
+            .rationale(Rationale::new("Parse API responses").unwrap())
+            .build()
+            .unwrap();
+        for query in ["JQ", "install", "json", "api resp", ""] {
+            assert!(entry.matches(query), "{query}");
+        }
+        assert!(!entry.matches("homebrew"), "source is not searched");
+
+        let change = DetectedChange::builder(Uuid::new_v4(), "jq")
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd("brew install jq")
+            .system(system)
+            .tags(vec![Tag::new("json").unwrap()])
+            .build()
+            .unwrap();
+        assert!(change.matches("Brew") && change.matches("JSON"));
+        assert!(change.matches("homebrew") && !change.matches("cask"));
+    }
+
+    #[test]
+    fn tag_aliases_canonicalize_and_merge() {
+        let aliases = BTreeMap::from([
+            ("js".to_string(), "javascript".to_string()),
+            ("node".to_string(), "javascript".to_string()),
+        ]);
+        let tags = canonical_tags(["js", "cli", " node ", "javascript"], &aliases).unwrap();
+        let values: Vec<_> = tags.iter().map(Tag::as_str).collect();
+        assert_eq!(values, ["javascript", "cli"]);
+        assert!(canonical_tags([" "], &aliases).is_err());
+
+        let mut entry = Entry::builder(Uuid::new_v4(), "eslint")
+            .entry_type(EntryType::Package)
+            .source("npm")
+            .cmd("npm install -g eslint")
+            .system(SystemInfo {
+                os: "macos".into(),
+                arch: "arm64".into(),
+                hostname: None,
+            })
+            .tags(vec![Tag::new("js").unwrap(), Tag::new("lint").unwrap()])
+            .rationale(Rationale::new("Lint scripts").unwrap())
+            .build()
+            .unwrap();
+        let javascript = Tag::new("javascript").unwrap();
+        let js = Tag::new("js").unwrap();
+        assert!(entry.replace_tag(&js, &javascript));
+        assert!(!entry.replace_tag(&js, &javascript));
+        entry.tags.push(Tag::new("node").unwrap());
+        assert!(entry.replace_tag(&Tag::new("node").unwrap(), &javascript));
+        assert_eq!(entry.tags, vec![javascript, Tag::new("lint").unwrap()]);
+    }
+
+    #[test]
+    fn match_score_ranks_title_over_tag_over_rationale() {
+        let entry = |title: &str, tag: &str, rationale: &str| {
+            Entry::builder(Uuid::new_v4(), title)
+                .entry_type(EntryType::Package)
+                .source("homebrew")
+                .cmd("brew install tool")
+                .system(SystemInfo {
+                    os: "macos".into(),
+                    arch: "arm64".into(),
+                    hostname: None,
+                })
+                .tags(vec![Tag::new(tag).unwrap()])
+                .rationale(Rationale::new(rationale).unwrap())
+                .build()
+                .unwrap()
+        };
+        let title = entry("jq", "cli", "daily tool");
+        let late_title = entry("gojq", "cli", "daily tool");
+        let tag = entry("yq", "jq-like", "daily tool");
+        let rationale = entry("fx", "cli", "a jq alternative");
+
+        let scores: Vec<_> = [&title, &late_title, &tag, &rationale]
+            .iter()
+            .map(|entry| entry.match_score("JQ").expect("matches"))
+            .collect();
+        assert!(scores.windows(2).all(|pair| pair[0] > pair[1]), "{scores:?}");
+        assert_eq!(title.match_score("missing"), None);
+        assert_eq!(title.match_score("tool"), late_title.match_score("tool"));
+    }
+
+    #[test]
+    fn inbox_order_sorts_a_mixed_inbox() {
+        let change = |title: &str, source: &str| {
+            DetectedChange::builder(Uuid::new_v4(), title)
+                .entry_type(EntryType::Package)
+                .source(source)
+                .cmd(format!("install {title}"))
+                .system(SystemInfo {
+                    os: "linux".into(),
+                    arch: "x86_64".into(),
+                    hostname: None,
+                })
+                .build()
+                .unwrap()
+        };
+        let inbox = vec![
+            change("ripgrep", "homebrew"),
+            change("eslint", "npm"),
+            change("Bat", "homebrew"),
+            change("zoxide", "cargo"),
+            change("prettier", "npm"),
+        ];
+        let titles = |order: InboxOrder| {
+            let mut items: Vec<&DetectedChange> = inbox.iter().collect();
+            order.sort(&mut items);
+            items.iter().map(|item| item.title.as_str()).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            titles(InboxOrder::Insertion),
+            ["ripgrep", "eslint", "Bat", "zoxide", "prettier"]
+        );
+        assert_eq!(
+            titles(InboxOrder::SourceGrouped),
+            ["zoxide", "ripgrep", "Bat", "eslint", "prettier"]
+        );
+        assert_eq!(
+            titles(InboxOrder::Alphabetical),
+            ["Bat", "eslint", "prettier", "ripgrep", "zoxide"]
+        );
+
+        let mut owned = inbox.clone();
+        InboxOrder::Alphabetical.sort(&mut owned);
+        assert_eq!(owned[0].title, "Bat");
+        assert_eq!("source_grouped".parse::<InboxOrder>().unwrap(), InboxOrder::SourceGrouped);
+        assert!("random".parse::<InboxOrder>().is_err());
+    }
 }
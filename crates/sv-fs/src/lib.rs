@@ -1,16 +1,20 @@
 //! Filesystem-backed persistence for the SetupVault.
 
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 use walkdir::WalkDir;
 
 use sv_core::{
-    CoreError, CoreResult, DetectedChange, Entry, EntryStatus, EntryType, Rationale, SystemInfo, Tag,
-    VaultRepository,
+    CoreError, CoreResult, CustomDetectorSpec, DetectedChange, DetectorConfig, Entry, EntryStatus,
+    EntryTemplate, EntryType, InboxOrder, Rationale, SystemInfo, Tag, VaultRepository,
 };
 
 /// Default directory name for the vault.
@@ -18,16 +22,157 @@ pub const VAULT_DIR_NAME: &str = "setupvault";
 
 const CONFIG_FILE_NAME: &str = "config.yaml";
 
+/// Name of a project-local vault directory; with a `.yaml` suffix, of a pointer file.
+pub const PROJECT_MARKER_NAME: &str = ".setupvault";
+
+/// How many directories the project marker search checks, starting with the current one.
+const PROJECT_MARKER_MAX_DEPTH: usize = 32;
+
+/// How entry files are arranged under `entries/`, stored as `layout` in the vault settings.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultLayout {
+    /// `entries/<type>/<source>/<file>.md`.
+    #[default]
+    Nested,
+    /// Every file directly under `entries/`.
+    Flat,
+}
+
+impl VaultLayout {
+    /// Every variant, in declaration order.
+    pub const ALL: [Self; 2] = [Self::Nested, Self::Flat];
+
+    /// Config value naming this layout.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Nested => "nested",
+            Self::Flat => "flat",
+        }
+    }
+}
+
+impl std::str::FromStr for VaultLayout {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> CoreResult<Self> {
+        Self::ALL.into_iter().find(|layout| layout.as_str() == value).ok_or_else(|| {
+            CoreError::Validation(format!("unknown layout `{value}`; expected nested or flat"))
+        })
+    }
+}
+
+/// What saving an inbox larger than `max_inbox_size` does, stored as `inbox_overflow` in the
+/// vault settings.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InboxOverflow {
+    /// Save every item and report how far over the limit the inbox is.
+    #[default]
+    Warn,
+    /// Drop the oldest items by detection time, as if they were ignored.
+    DropOldest,
+}
+
+impl InboxOverflow {
+    /// Every variant, in declaration order.
+    pub const ALL: [Self; 2] = [Self::Warn, Self::DropOldest];
+
+    /// Config value naming this policy.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Warn => "warn",
+            Self::DropOldest => "drop_oldest",
+        }
+    }
+}
+
+impl std::str::FromStr for InboxOverflow {
+    type Err = CoreError;
+
+    fn from_str(value: &str) -> CoreResult<Self> {
+        Self::ALL.into_iter().find(|policy| policy.as_str() == value).ok_or_else(|| {
+            CoreError::Validation(format!(
+                "unknown inbox_overflow `{value}`; expected warn or drop_oldest"
+            ))
+        })
+    }
+}
+
+/// The `keep` most recently detected changes, in their original order; ties keep the later
+/// queued item.
+fn newest_changes(changes: &[DetectedChange], keep: usize) -> Vec<DetectedChange> {
+    let mut by_age: Vec<_> = (0..changes.len()).collect();
+    by_age.sort_by_key(|&index| (changes[index].detected_at, index));
+    let mut kept = by_age.split_off(changes.len() - keep);
+    kept.sort_unstable();
+    kept.into_iter().map(|index| changes[index].clone()).collect()
+}
+
+/// Reported by [`FsVault::save_inbox`] when the inbox exceeds `max_inbox_size`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InboxOverflowNotice {
+    /// Every item was saved; the inbox holds `count` items.
+    Over { count: usize, max: usize },
+    /// The `dropped` oldest items were discarded to keep `max`.
+    Dropped { dropped: usize, max: usize },
+}
+
+impl std::fmt::Display for InboxOverflowNotice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Over { count, max } => write!(
+                f,
+                "inbox has {count} items, over max_inbox_size ({max}); consider triaging"
+            ),
+            Self::Dropped { dropped, max } => write!(
+                f,
+                "dropped the {dropped} oldest inbox item(s) to stay within max_inbox_size ({max})"
+            ),
+        }
+    }
+}
+
 /// Filesystem-backed vault repository.
 #[derive(Debug, Clone)]
 pub struct FsVault {
     root: PathBuf,
+    settings: VaultSettings,
 }
 
 impl FsVault {
-    /// Create a new filesystem vault rooted at the provided path.
+    /// Create a new filesystem vault rooted at the provided path, using default settings.
     pub fn new(root: PathBuf) -> Self {
-        Self { root }
+        Self {
+            root,
+            settings: VaultSettings::default(),
+        }
+    }
+
+    /// Open the vault rooted at `root` with the settings stored in it, if any.
+    pub fn open(root: PathBuf) -> CoreResult<Self> {
+        let mut vault = Self::new(root);
+        vault.settings = vault.load_settings()?;
+        Ok(vault)
+    }
+
+    /// Write new and updated entries in `layout`; reads find entries in either layout.
+    pub fn with_layout(mut self, layout: VaultLayout) -> Self {
+        self.settings.layout = layout;
+        self
+    }
+
+    /// Cap the inbox at `max` items when [`FsVault::save_inbox`] stores a refresh, handling
+    /// larger inboxes with `overflow`; `None` leaves it unlimited.
+    pub fn with_inbox_limit(mut self, max: Option<usize>, overflow: InboxOverflow) -> Self {
+        self.settings.max_inbox_size = max;
+        self.settings.inbox_overflow = overflow;
+        self
+    }
+
+    /// The same settings for a vault rooted at `root`.
+    pub fn with_root(&self, root: PathBuf) -> Self {
+        Self { root, ..self.clone() }
     }
 
     /// Get the root path of the vault.
@@ -35,6 +180,43 @@ impl FsVault {
         &self.root
     }
 
+    /// The layout new and updated entries are written in.
+    pub fn layout(&self) -> VaultLayout {
+        self.settings.layout
+    }
+
+    /// Settings stored in this vault.
+    pub fn settings(&self) -> &VaultSettings {
+        &self.settings
+    }
+
+    fn load_settings(&self) -> CoreResult<VaultSettings> {
+        let path = self.settings_path();
+        if !path.exists() {
+            return Ok(VaultSettings::default());
+        }
+        let contents =
+            fs::read_to_string(&path).map_err(|err| CoreError::Storage(err.to_string()))?;
+        serde_yaml::from_str(&contents).map_err(|err| {
+            CoreError::Storage(format!(
+                "invalid vault settings in {}: {err}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Store `settings` in the vault and use them from now on.
+    pub fn save_settings(&mut self, settings: VaultSettings) -> CoreResult<()> {
+        let path = self.settings_path();
+        fs::create_dir_all(self.state_root()).map_err(|err| CoreError::Storage(err.to_string()))?;
+        let contents =
+            serde_yaml::to_string(&settings).map_err(|err| CoreError::Storage(err.to_string()))?;
+        tracing::debug!(path = %path.display(), "writing vault settings");
+        fs::write(&path, contents).map_err(|err| CoreError::Storage(err.to_string()))?;
+        self.settings = settings;
+        Ok(())
+    }
+
     /// Resolve the default vault path (~/.setupvault).
     pub fn default_path() -> CoreResult<PathBuf> {
         if let Some(dir) = dirs::home_dir() {
@@ -45,9 +227,9 @@ impl FsVault {
         ))
     }
 
-    /// Check if the vault exists at the root path.
+    /// Check if a SetupVault (identified by its `entries` directory) exists at the root path.
     pub fn exists(&self) -> bool {
-        self.root.exists() && self.entries_root().exists()
+        self.entries_root().is_dir()
     }
 
     /// Initialize the vault structure.
@@ -55,6 +237,12 @@ impl FsVault {
         if self.exists() {
             return Ok(());
         }
+        if self.root.exists() && !self.root.is_dir() {
+            return Err(CoreError::Storage(format!(
+                "{} exists and is not a directory",
+                self.root.display()
+            )));
+        }
         fs::create_dir_all(self.entries_root())
             .map_err(|err| CoreError::Storage(err.to_string()))?;
         fs::create_dir_all(self.state_root())
@@ -74,10 +262,23 @@ impl FsVault {
         self.state_root().join("inbox.yaml")
     }
 
+    fn settings_path(&self) -> PathBuf {
+        self.state_root().join("settings.yaml")
+    }
+
     fn snoozed_path(&self) -> PathBuf {
         self.state_root().join("snoozed.yaml")
     }
 
+    /// Directory holding cached detector scan output between refreshes.
+    pub fn detector_cache_dir(&self) -> PathBuf {
+        self.state_root().join("detector-cache")
+    }
+
+    fn access_path(&self) -> PathBuf {
+        self.state_root().join("access.yaml")
+    }
+
     fn detector_snapshot_path(&self, source: &str) -> PathBuf {
         self.state_root().join("detectors").join(format!("{source}.yaml"))
     }
@@ -99,9 +300,35 @@ impl FsVault {
     }
 
     fn entry_path(&self, entry: &Entry) -> PathBuf {
-        self.entries_root()
-            .join(Self::entry_dir(&entry.entry_type, &entry.source))
-            .join(Self::entry_file_name(entry))
+        let dir = match self.settings.layout {
+            VaultLayout::Nested => {
+                self.entries_root().join(Self::entry_dir(&entry.entry_type, &entry.source))
+            }
+            VaultLayout::Flat => self.entries_root(),
+        };
+        // The file name carries the id, so it stays unique without the type/source nesting.
+        dir.join(Self::entry_file_name(entry))
+    }
+
+    /// Remove now-empty type/source directories left behind by a moved entry.
+    fn prune_empty_dirs(&self, removed: &std::path::Path) {
+        let entries_root = self.entries_root();
+        let mut dir = removed.parent();
+        while let Some(current) = dir {
+            if current == entries_root || !current.starts_with(&entries_root) {
+                break;
+            }
+            // `remove_dir` fails on non-empty directories, which ends the walk.
+            if fs::remove_dir(current).is_err() {
+                break;
+            }
+            dir = current.parent();
+        }
+    }
+
+    /// Path of the file that stores entry `id`, if it exists.
+    pub fn entry_file(&self, id: Uuid) -> CoreResult<Option<PathBuf>> {
+        self.find_entry_path(id)
     }
 
     fn find_entry_path(&self, id: Uuid) -> CoreResult<Option<PathBuf>> {
@@ -138,8 +365,37 @@ impl FsVault {
         serde_yaml::from_str(&contents).map_err(|err| CoreError::Storage(err.to_string()))
     }
 
-    /// Persist the inbox queue to disk.
-    pub fn save_inbox(&self, changes: &[DetectedChange]) -> CoreResult<()> {
+    /// Persist a refreshed inbox queue to disk, applying the inbox limit from
+    /// [`FsVault::with_inbox_limit`]; returns a notice when the inbox is over it.
+    ///
+    /// Single-item updates (adding, removing, snoozing, or restoring one change) skip the
+    /// limit, so the item being inserted is never evicted as the oldest; removals of several
+    /// items use [`FsVault::write_inbox`] for the same reason.
+    pub fn save_inbox(
+        &self,
+        changes: &[DetectedChange],
+    ) -> CoreResult<Option<InboxOverflowNotice>> {
+        let count = changes.len();
+        let (changes, notice) = match (self.settings.max_inbox_size, self.settings.inbox_overflow) {
+            (Some(max), InboxOverflow::Warn) if count > max => {
+                (Cow::Borrowed(changes), Some(InboxOverflowNotice::Over { count, max }))
+            }
+            (Some(max), InboxOverflow::DropOldest) if count > max => {
+                let notice = InboxOverflowNotice::Dropped { dropped: count - max, max };
+                (Cow::Owned(newest_changes(changes, max)), Some(notice))
+            }
+            _ => (Cow::Borrowed(changes), None),
+        };
+        self.write_inbox(&changes)?;
+        if let Some(notice) = notice {
+            tracing::warn!(%notice, "inbox over max_inbox_size");
+        }
+        Ok(notice)
+    }
+
+    /// Write the inbox queue as given, without the inbox limit, so removing items never
+    /// evicts others.
+    pub fn write_inbox(&self, changes: &[DetectedChange]) -> CoreResult<()> {
         let path = self.inbox_path();
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)
@@ -147,22 +403,50 @@ impl FsVault {
         }
         let contents = serde_yaml::to_string(changes)
             .map_err(|err| CoreError::Storage(err.to_string()))?;
+        tracing::debug!(path = %path.display(), count = changes.len(), "writing inbox");
         fs::write(path, contents).map_err(|err| CoreError::Storage(err.to_string()))?;
         Ok(())
     }
 
-    /// Add a new item to the inbox queue.
-    pub fn add_inbox_item(&self, item: DetectedChange) -> CoreResult<()> {
+    /// Add an item to the inbox queue unless a change with the same source and title is
+    /// already queued, returning whether it was added.
+    pub fn add_inbox_item(&self, item: DetectedChange) -> CoreResult<bool> {
+        let mut changes = self.load_inbox()?;
+        if changes.iter().any(|change| change.dedupe_key() == item.dedupe_key()) {
+            return Ok(false);
+        }
+        changes.push(item);
+        self.write_inbox(&changes)?;
+        Ok(true)
+    }
+
+    /// Add an item to the inbox queue even if an equivalent change is already queued.
+    pub fn force_add_inbox_item(&self, item: DetectedChange) -> CoreResult<()> {
         let mut changes = self.load_inbox()?;
         changes.push(item);
-        self.save_inbox(&changes)
+        self.write_inbox(&changes)?;
+        Ok(())
     }
 
-    /// Remove a single inbox item by id.
-    pub fn remove_inbox_item(&self, id: Uuid) -> CoreResult<()> {
+    /// Remove a single inbox item by id, returning whether it was present.
+    pub fn remove_inbox_item(&self, id: Uuid) -> CoreResult<bool> {
         let mut changes = self.load_inbox()?;
+        let before = changes.len();
         changes.retain(|change| change.id != id);
-        self.save_inbox(&changes)
+        if changes.len() == before {
+            return Ok(false);
+        }
+        self.write_inbox(&changes)?;
+        Ok(true)
+    }
+
+    /// Remove every inbox item in one write, returning how many were queued.
+    pub fn clear_inbox(&self) -> CoreResult<usize> {
+        let count = self.load_inbox()?.len();
+        if count > 0 {
+            self.write_inbox(&[])?;
+        }
+        Ok(count)
     }
 
     /// Load snoozed changes from disk.
@@ -185,31 +469,60 @@ impl FsVault {
         }
         let contents = serde_yaml::to_string(changes)
             .map_err(|err| CoreError::Storage(err.to_string()))?;
+        tracing::debug!(path = %path.display(), count = changes.len(), "writing snoozed list");
         fs::write(path, contents).map_err(|err| CoreError::Storage(err.to_string()))?;
         Ok(())
     }
 
-    /// Move an inbox item into the snoozed list.
-    pub fn snooze_inbox_item(&self, id: Uuid) -> CoreResult<()> {
+    /// Move an inbox item into the snoozed list, returning whether it was present.
+    pub fn snooze_inbox_item(&self, id: Uuid) -> CoreResult<bool> {
         let mut inbox = self.load_inbox()?;
         let mut snoozed = self.load_snoozed()?;
-        if let Some(position) = inbox.iter().position(|change| change.id == id) {
-            snoozed.push(inbox.remove(position));
-            self.save_snoozed(&snoozed)?;
-            self.save_inbox(&inbox)?;
-        }
-        Ok(())
+        let Some(position) = inbox.iter().position(|change| change.id == id) else {
+            return Ok(false);
+        };
+        snoozed.push(inbox.remove(position));
+        self.save_snoozed(&snoozed)?;
+        self.write_inbox(&inbox)?;
+        Ok(true)
     }
 
-    /// Move a snoozed item back into the inbox.
-    pub fn unsnooze_item(&self, id: Uuid) -> CoreResult<()> {
+    /// Move a snoozed item back into the inbox, returning whether it was present.
+    pub fn unsnooze_item(&self, id: Uuid) -> CoreResult<bool> {
         let mut inbox = self.load_inbox()?;
         let mut snoozed = self.load_snoozed()?;
-        if let Some(position) = snoozed.iter().position(|change| change.id == id) {
-            inbox.push(snoozed.remove(position));
-            self.save_snoozed(&snoozed)?;
-            self.save_inbox(&inbox)?;
+        let Some(position) = snoozed.iter().position(|change| change.id == id) else {
+            return Ok(false);
+        };
+        inbox.push(snoozed.remove(position));
+        self.save_snoozed(&snoozed)?;
+        self.write_inbox(&inbox)?;
+        Ok(true)
+    }
+
+    /// Load when each entry was last viewed or acted on, keyed by entry id.
+    pub fn load_access_times(&self) -> CoreResult<BTreeMap<Uuid, DateTime<Utc>>> {
+        let path = self.access_path();
+        if !path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| CoreError::Storage(err.to_string()))?;
+        serde_yaml::from_str(&contents).map_err(|err| CoreError::Storage(err.to_string()))
+    }
+
+    /// Record that an entry was just viewed or acted on.
+    pub fn record_access(&self, id: Uuid) -> CoreResult<()> {
+        let mut times = self.load_access_times()?;
+        times.insert(id, Utc::now());
+        let path = self.access_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| CoreError::Storage(err.to_string()))?;
         }
+        let contents = serde_yaml::to_string(&times)
+            .map_err(|err| CoreError::Storage(err.to_string()))?;
+        fs::write(path, contents).map_err(|err| CoreError::Storage(err.to_string()))?;
         Ok(())
     }
 
@@ -240,14 +553,567 @@ impl FsVault {
         }
         let contents = serde_yaml::to_string(changes)
             .map_err(|err| CoreError::Storage(err.to_string()))?;
+        tracing::debug!(path = %path.display(), count = changes.len(), "writing detector snapshot");
         fs::write(path, contents).map_err(|err| CoreError::Storage(err.to_string()))?;
         Ok(())
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
-struct VaultConfig {
-    path: Option<String>,
+/// Problem found while verifying a vault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityIssue {
+    /// An entry file could not be read or parsed.
+    UnparseableEntry {
+        /// Entry file path.
+        path: PathBuf,
+        /// Parse error message.
+        error: String,
+    },
+    /// More than one entry file carries the same id.
+    DuplicateEntryId {
+        /// Shared entry id.
+        id: Uuid,
+        /// Files that carry the id.
+        paths: Vec<PathBuf>,
+    },
+    /// An inbox or snoozed change reuses a library entry id.
+    QueueIdCollision {
+        /// Queue name (`inbox` or `snoozed`).
+        queue: &'static str,
+        /// Colliding id.
+        id: Uuid,
+    },
+    /// An entry's frontmatter carries keys outside [`FRONTMATTER_FIELDS`] (strict mode only).
+    UnknownFrontmatterFields {
+        /// Entry file path.
+        path: PathBuf,
+        /// Unrecognized keys, in file order.
+        fields: Vec<String>,
+    },
+    /// A state file (inbox, snoozed list, detector snapshot) is not valid YAML.
+    InvalidStateFile {
+        /// State file path.
+        path: PathBuf,
+        /// Parse error message.
+        error: String,
+    },
+}
+
+impl std::fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnparseableEntry { path, error } => {
+                write!(f, "unparseable entry {}: {error}", path.display())
+            }
+            Self::DuplicateEntryId { id, paths } => {
+                let paths: Vec<_> = paths.iter().map(|path| path.display().to_string()).collect();
+                write!(f, "duplicate entry id {id} in {}", paths.join(", "))
+            }
+            Self::QueueIdCollision { queue, id } => {
+                write!(f, "{queue} change {id} collides with a library entry id")
+            }
+            Self::UnknownFrontmatterFields { path, fields } => {
+                let fields: Vec<_> = fields.iter().map(|field| format!("`{field}`")).collect();
+                write!(f, "unknown frontmatter field(s) {} in {}", fields.join(", "), path.display())
+            }
+            Self::InvalidStateFile { path, error } => {
+                write!(f, "invalid state file {}: {error}", path.display())
+            }
+        }
+    }
+}
+
+/// Result of [`FsVault::verify_integrity`].
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityReport {
+    /// Number of entry files inspected.
+    pub entries_checked: usize,
+    /// Problems found.
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    /// Whether the vault passed every check.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Result of [`FsVault::migrate_entries`].
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    /// Number of entry files inspected.
+    pub entries_checked: usize,
+    /// Number of files rewritten.
+    pub migrated: usize,
+    /// Files that could not be parsed, with the parse error.
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+/// Aggregate counts over a vault, shared by the TUI dashboard and `setupvault stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VaultStats {
+    /// Number of library entries.
+    pub total_entries: usize,
+    /// Library entries per entry type; types without entries are omitted.
+    pub by_type: BTreeMap<EntryType, usize>,
+    /// Library entries per source.
+    pub by_source: BTreeMap<String, usize>,
+    /// Changes waiting in the inbox.
+    pub inbox: usize,
+    /// Changes in the snoozed queue.
+    pub snoozed: usize,
+    /// Earliest `detected_at` among library entries.
+    pub oldest_detected_at: Option<DateTime<Utc>>,
+    /// Latest `detected_at` among library entries.
+    pub newest_detected_at: Option<DateTime<Utc>>,
+}
+
+impl VaultStats {
+    /// Aggregate already-loaded entries and queues.
+    pub fn from_parts(
+        entries: &[Entry],
+        inbox: &[DetectedChange],
+        snoozed: &[DetectedChange],
+    ) -> Self {
+        let mut stats = Self {
+            total_entries: entries.len(),
+            inbox: inbox.len(),
+            snoozed: snoozed.len(),
+            oldest_detected_at: entries.iter().map(|entry| entry.detected_at).min(),
+            newest_detected_at: entries.iter().map(|entry| entry.detected_at).max(),
+            ..Self::default()
+        };
+        for entry in entries {
+            *stats.by_type.entry(entry.entry_type.clone()).or_default() += 1;
+            *stats.by_source.entry(entry.source.clone()).or_default() += 1;
+        }
+        stats
+    }
+
+    /// Sources ordered by entry count, largest first, ties by name.
+    pub fn top_sources(&self) -> Vec<(&str, usize)> {
+        let mut sources: Vec<_> =
+            self.by_source.iter().map(|(source, count)| (source.as_str(), *count)).collect();
+        sources.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        sources
+    }
+}
+
+/// Persisted user preferences stored in `config.yaml`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct VaultConfig {
+    /// Optional custom vault path.
+    pub path: Option<String>,
+    /// macOS defaults domains to snapshot; empty uses the detector's curated list.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mac_defaults_domains: Vec<String>,
+    /// Files watched by the dotfile detector; empty uses its default list.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dotfile_paths: Vec<String>,
+    /// Executables detectors run instead of a program on `PATH`, keyed by program (`brew`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub detector_binaries: BTreeMap<String, String>,
+    /// Seconds a detector's scan output is reused before re-running it; zero disables the cache.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub detector_cache_ttl_secs: u64,
+    /// Standing exclusions for detected changes (`pattern` or `source:pattern`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_patterns: Vec<String>,
+    /// Named rationale boilerplate; `{title}` is replaced with the entry title.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rationale_templates: BTreeMap<String, String>,
+    /// Capture defaults keyed by source or entry type; edited in `config.yaml` only.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub entry_templates: BTreeMap<String, EntryTemplate>,
+    /// Tag aliases mapped to their canonical tag, applied when tags are added.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tag_aliases: BTreeMap<String, String>,
+    /// Warn about short or bulk-reused rationales on approval and mark them in the TUI.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub rationale_quality_warnings: bool,
+    /// Source order used to collapse the same title reported by several sources; empty disables it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_priority: Vec<String>,
+    /// User-defined detectors that list items with a command; edited in `config.yaml` only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_detectors: Vec<CustomDetectorSpec>,
+    /// Default order the CLI and TUI present inbox items in.
+    #[serde(default, skip_serializing_if = "is_insertion")]
+    pub inbox_order: InboxOrder,
+    /// Detector names to run instead of the OS default set; `None` keeps the defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled_detectors: Option<Vec<String>>,
+    /// Detector names never to run, applied after `enabled_detectors`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_detectors: Vec<String>,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            mac_defaults_domains: Vec::new(),
+            dotfile_paths: Vec::new(),
+            detector_binaries: BTreeMap::new(),
+            detector_cache_ttl_secs: 0,
+            ignore_patterns: Vec::new(),
+            rationale_templates: BTreeMap::new(),
+            entry_templates: BTreeMap::new(),
+            tag_aliases: BTreeMap::new(),
+            rationale_quality_warnings: true,
+            source_priority: Vec::new(),
+            custom_detectors: Vec::new(),
+            inbox_order: InboxOrder::default(),
+            enabled_detectors: None,
+            disabled_detectors: Vec::new(),
+        }
+    }
+}
+
+impl VaultConfig {
+    /// Keys understood in `config.yaml`.
+    pub const FIELDS: &'static [&'static str] = &[
+        "path",
+        "mac_defaults_domains",
+        "dotfile_paths",
+        "detector_binaries",
+        "detector_cache_ttl_secs",
+        "ignore_patterns",
+        "rationale_templates",
+        "entry_templates",
+        "tag_aliases",
+        "rationale_quality_warnings",
+        "source_priority",
+        "custom_detectors",
+        "inbox_order",
+        "enabled_detectors",
+        "disabled_detectors",
+    ];
+
+    /// Expand the named rationale template for an entry titled `title`.
+    pub fn expand_rationale_template(&self, name: &str, title: &str) -> CoreResult<Rationale> {
+        let Some(template) = self.rationale_templates.get(name) else {
+            let known: Vec<_> = self.rationale_templates.keys().map(String::as_str).collect();
+            return Err(CoreError::Validation(if known.is_empty() {
+                format!("unknown rationale template `{name}`; none are configured")
+            } else {
+                format!(
+                    "unknown rationale template `{name}`; known templates: {}",
+                    known.join(", ")
+                )
+            }));
+        };
+        Rationale::new(template.replace("{title}", title))
+    }
+
+    /// Capture defaults for an entry from `source`, falling back to its entry type.
+    pub fn entry_template(&self, source: &str, entry_type: &EntryType) -> Option<&EntryTemplate> {
+        self.entry_templates
+            .get(source)
+            .or_else(|| self.entry_templates.get(entry_type.as_str()))
+    }
+
+    /// The detector settings carried by this config.
+    pub fn detector_config(&self) -> DetectorConfig {
+        DetectorConfig {
+            mac_defaults_domains: self.mac_defaults_domains.clone(),
+            custom_detectors: self.custom_detectors.clone(),
+            enabled_detectors: self.enabled_detectors.clone(),
+            disabled_detectors: self.disabled_detectors.clone(),
+            dotfile_paths: self.dotfile_paths.iter().map(PathBuf::from).collect(),
+            binaries: self.detector_binaries.clone(),
+        }
+    }
+
+    /// Render a field as text; lists are comma-separated, and templates, aliases, and binaries
+    /// are addressed as `rationale_templates.<name>`, `tag_aliases.<alias>`, and
+    /// `detector_binaries.<program>`.
+    pub fn get(&self, key: &str) -> CoreResult<String> {
+        if let Some(name) = key.strip_prefix("rationale_templates.") {
+            return self.rationale_templates.get(name).cloned().ok_or_else(|| {
+                CoreError::Validation(format!("no rationale template named `{name}`"))
+            });
+        }
+        if let Some(alias) = key.strip_prefix("tag_aliases.") {
+            return self.tag_aliases.get(alias).cloned().ok_or_else(|| {
+                CoreError::Validation(format!("no tag alias named `{alias}`"))
+            });
+        }
+        if let Some(program) = key.strip_prefix("detector_binaries.") {
+            return self.detector_binaries.get(program).cloned().ok_or_else(|| {
+                CoreError::Validation(format!("no detector binary set for `{program}`"))
+            });
+        }
+        Ok(match key {
+            "path" => self.path.clone().unwrap_or_default(),
+            "mac_defaults_domains" => self.mac_defaults_domains.join(","),
+            "dotfile_paths" => self.dotfile_paths.join(","),
+            "detector_binaries" => self
+                .detector_binaries
+                .iter()
+                .map(|(program, binary)| format!("{program}={binary}"))
+                .collect::<Vec<_>>()
+                .join(","),
+            "detector_cache_ttl_secs" => self.detector_cache_ttl_secs.to_string(),
+            "ignore_patterns" => self.ignore_patterns.join(","),
+            "rationale_templates" => self
+                .rationale_templates
+                .iter()
+                .map(|(name, text)| format!("{name}={text}"))
+                .collect::<Vec<_>>()
+                .join(","),
+            "entry_templates" => {
+                self.entry_templates.keys().cloned().collect::<Vec<_>>().join(",")
+            }
+            "tag_aliases" => self
+                .tag_aliases
+                .iter()
+                .map(|(alias, tag)| format!("{alias}={tag}"))
+                .collect::<Vec<_>>()
+                .join(","),
+            "rationale_quality_warnings" => self.rationale_quality_warnings.to_string(),
+            "source_priority" => self.source_priority.join(","),
+            "custom_detectors" => self
+                .custom_detectors
+                .iter()
+                .map(|spec| spec.name.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            "inbox_order" => self.inbox_order.as_str().to_string(),
+            "enabled_detectors" => self
+                .enabled_detectors
+                .as_deref()
+                .unwrap_or_default()
+                .join(","),
+            "disabled_detectors" => self.disabled_detectors.join(","),
+            _ => return Err(unknown_config_key(key)),
+        })
+    }
+
+    /// Parse `value` into a field; lists take comma-separated items and an empty value clears.
+    pub fn set(&mut self, key: &str, value: &str) -> CoreResult<()> {
+        let value = value.trim();
+        if let Some(name) = key.strip_prefix("rationale_templates.") {
+            if name.is_empty() {
+                return Err(CoreError::Validation("template name cannot be empty".into()));
+            }
+            if value.is_empty() {
+                self.rationale_templates.remove(name);
+            } else {
+                self.rationale_templates.insert(name.to_string(), value.to_string());
+            }
+            return Ok(());
+        }
+        if let Some(alias) = key.strip_prefix("tag_aliases.") {
+            if alias.trim().is_empty() {
+                return Err(CoreError::Validation("tag alias cannot be empty".into()));
+            }
+            if value.is_empty() {
+                self.tag_aliases.remove(alias);
+            } else {
+                self.tag_aliases.insert(alias.to_string(), value.to_string());
+            }
+            return Ok(());
+        }
+        if let Some(program) = key.strip_prefix("detector_binaries.") {
+            if program.trim().is_empty() {
+                return Err(CoreError::Validation("program name cannot be empty".into()));
+            }
+            if value.is_empty() {
+                self.detector_binaries.remove(program);
+            } else {
+                let binary = expand_path(value).display().to_string();
+                self.detector_binaries.insert(program.to_string(), binary);
+            }
+            return Ok(());
+        }
+        match key {
+            "path" => {
+                self.path = (!value.is_empty()).then(|| expand_path(value).display().to_string());
+            }
+            "mac_defaults_domains" => self.mac_defaults_domains = split_list(value),
+            "dotfile_paths" => {
+                self.dotfile_paths = split_list(value)
+                    .iter()
+                    .map(|path| expand_path(path).display().to_string())
+                    .collect();
+            }
+            "detector_binaries" => {
+                return Err(CoreError::Validation(
+                    "set binaries one at a time with `detector_binaries.<program>`".into(),
+                ))
+            }
+            "detector_cache_ttl_secs" => {
+                self.detector_cache_ttl_secs = if value.is_empty() {
+                    0
+                } else {
+                    value.parse().map_err(|_| {
+                        CoreError::Validation(format!(
+                            "detector_cache_ttl_secs must be a whole number of seconds, got `{value}`"
+                        ))
+                    })?
+                };
+            }
+            "ignore_patterns" => self.ignore_patterns = split_list(value),
+            "rationale_templates" => {
+                return Err(CoreError::Validation(
+                    "set templates one at a time with `rationale_templates.<name>`".into(),
+                ))
+            }
+            "entry_templates" => {
+                return Err(CoreError::Validation(
+                    "edit entry_templates in config.yaml; each is keyed by a source or entry type \
+                     and may set tags, verification, and rationale"
+                        .into(),
+                ))
+            }
+            "tag_aliases" => {
+                return Err(CoreError::Validation(
+                    "set aliases one at a time with `tag_aliases.<alias>`".into(),
+                ))
+            }
+            "rationale_quality_warnings" => {
+                self.rationale_quality_warnings = value.is_empty()
+                    || value.parse().map_err(|_| {
+                        CoreError::Validation(format!(
+                            "rationale_quality_warnings must be true or false, got `{value}`"
+                        ))
+                    })?;
+            }
+            "source_priority" => self.source_priority = split_list(value),
+            "custom_detectors" => {
+                return Err(CoreError::Validation(
+                    "edit custom_detectors in config.yaml; each item needs name, list_cmd, \
+                     and install_cmd_template"
+                        .into(),
+                ))
+            }
+            "inbox_order" => {
+                self.inbox_order =
+                    if value.is_empty() { InboxOrder::default() } else { value.parse()? };
+            }
+            "enabled_detectors" => {
+                self.enabled_detectors = (!value.is_empty()).then(|| split_list(value));
+            }
+            "disabled_detectors" => self.disabled_detectors = split_list(value),
+            _ => return Err(unknown_config_key(key)),
+        }
+        Ok(())
+    }
+}
+
+/// Settings that belong to one vault, stored in its `.state/settings.yaml` so they follow the
+/// vault when it is moved, switched to, or synced.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct VaultSettings {
+    /// How entry files are arranged under `entries/`; change it with `migrate-layout`.
+    #[serde(default, skip_serializing_if = "is_nested")]
+    pub layout: VaultLayout,
+    /// Most items the inbox should hold; `None` leaves it unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_inbox_size: Option<usize>,
+    /// What happens when a save would exceed `max_inbox_size`.
+    #[serde(default, skip_serializing_if = "is_warn")]
+    pub inbox_overflow: InboxOverflow,
+}
+
+impl VaultSettings {
+    /// Keys understood in the vault settings.
+    pub const FIELDS: &'static [&'static str] = &["layout", "max_inbox_size", "inbox_overflow"];
+
+    /// Render a field as text.
+    pub fn get(&self, key: &str) -> CoreResult<String> {
+        Ok(match key {
+            "layout" => self.layout.as_str().to_string(),
+            "max_inbox_size" => self
+                .max_inbox_size
+                .map(|max| max.to_string())
+                .unwrap_or_default(),
+            "inbox_overflow" => self.inbox_overflow.as_str().to_string(),
+            _ => return Err(unknown_config_key(key)),
+        })
+    }
+
+    /// Parse `value` into a field; an empty value restores its default.
+    pub fn set(&mut self, key: &str, value: &str) -> CoreResult<()> {
+        let value = value.trim();
+        match key {
+            "layout" => {
+                return Err(CoreError::Validation(
+                    "change the layout with `setupvault migrate-layout <nested|flat>`, which \
+                     also moves existing entries"
+                        .into(),
+                ))
+            }
+            "max_inbox_size" => {
+                self.max_inbox_size = if value.is_empty() {
+                    None
+                } else {
+                    Some(value.parse().ok().filter(|max| *max > 0).ok_or_else(|| {
+                        CoreError::Validation(format!(
+                            "max_inbox_size must be a positive whole number, got `{value}`"
+                        ))
+                    })?)
+                };
+            }
+            "inbox_overflow" => {
+                self.inbox_overflow = if value.is_empty() {
+                    InboxOverflow::default()
+                } else {
+                    value.parse()?
+                };
+            }
+            _ => return Err(unknown_config_key(key)),
+        }
+        Ok(())
+    }
+}
+
+fn unknown_config_key(key: &str) -> CoreError {
+    CoreError::Validation(format!(
+        "unknown config key `{key}`; known keys: {}",
+        VaultConfig::FIELDS
+            .iter()
+            .chain(VaultSettings::FIELDS)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_insertion(order: &InboxOrder) -> bool {
+    *order == InboxOrder::Insertion
+}
+
+fn is_nested(layout: &VaultLayout) -> bool {
+    *layout == VaultLayout::Nested
+}
+
+fn is_warn(overflow: &InboxOverflow) -> bool {
+    *overflow == InboxOverflow::Warn
 }
 
 fn config_path() -> CoreResult<PathBuf> {
@@ -277,34 +1143,124 @@ pub fn save_config(config: &VaultConfig) -> CoreResult<()> {
     }
     let contents = serde_yaml::to_string(config)
         .map_err(|err| CoreError::Storage(err.to_string()))?;
+    tracing::debug!(path = %path.display(), "writing config");
     fs::write(path, contents).map_err(|err| CoreError::Storage(err.to_string()))?;
     Ok(())
 }
 
 pub fn set_config_path(path: &std::path::Path) -> CoreResult<()> {
-    let config = VaultConfig {
-        path: Some(path.to_string_lossy().to_string()),
-    };
+    let mut config = load_config()?;
+    config.path = Some(path.to_string_lossy().to_string());
     save_config(&config)
 }
 
+/// Expand a user-supplied path into an absolute path.
+///
+/// A leading `~` or `~/` resolves to the home directory and `~user` to a sibling of it;
+/// relative paths resolve against the current directory.
+pub fn expand_path(input: &str) -> PathBuf {
+    let input = input.trim();
+    let path = match input.strip_prefix('~') {
+        Some(rest) => expand_home(rest).unwrap_or_else(|| PathBuf::from(input)),
+        None => PathBuf::from(input),
+    };
+    if path.is_absolute() {
+        return path;
+    }
+    match std::env::current_dir() {
+        Ok(cwd) => cwd.join(path),
+        Err(_) => path,
+    }
+}
+
+fn expand_home(rest: &str) -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    let (user, sub) = match rest.find(['/', std::path::MAIN_SEPARATOR]) {
+        Some(index) => (&rest[..index], &rest[index + 1..]),
+        None => (rest, ""),
+    };
+    let base = if user.is_empty() {
+        home
+    } else {
+        home.parent()?.join(user)
+    };
+    Some(if sub.is_empty() { base } else { base.join(sub) })
+}
+
+/// Resolve the active vault: `SETUPVAULT_PATH`, then a project vault found from the current
+/// directory, then the configured path, then the default.
 pub fn resolve_vault_path() -> CoreResult<PathBuf> {
     if let Ok(value) = std::env::var("SETUPVAULT_PATH") {
         if !value.trim().is_empty() {
-            return Ok(PathBuf::from(value));
+            return Ok(expand_path(&value));
         }
     }
 
+    if let Some(path) = project_vault_path()? {
+        return Ok(path);
+    }
+
     let config = load_config()?;
     if let Some(path) = config.path {
         if !path.trim().is_empty() {
-            return Ok(PathBuf::from(path));
+            return Ok(expand_path(&path));
         }
     }
 
     FsVault::default_path()
 }
 
+/// Project vault for the current directory, searched up to (but not including) the home
+/// directory, whose `.setupvault` is the default vault.
+pub fn project_vault_path() -> CoreResult<Option<PathBuf>> {
+    let cwd = std::env::current_dir().map_err(|err| CoreError::Storage(err.to_string()))?;
+    find_project_vault(&cwd, dirs::home_dir().as_deref())
+}
+
+/// Walk up from `start` looking for a project vault, the way git looks for `.git`.
+///
+/// Each directory is checked for a `.setupvault` directory, which is the vault itself, and
+/// then for a `.setupvault.yaml` pointer file whose `path` names the vault (relative paths
+/// resolve against the file's directory). The walk stops at `stop` without checking it.
+pub fn find_project_vault(start: &Path, stop: Option<&Path>) -> CoreResult<Option<PathBuf>> {
+    for dir in start.ancestors().take(PROJECT_MARKER_MAX_DEPTH) {
+        if Some(dir) == stop {
+            break;
+        }
+        let marker = dir.join(PROJECT_MARKER_NAME);
+        if marker.is_dir() {
+            return Ok(Some(marker));
+        }
+        let pointer = dir.join(format!("{PROJECT_MARKER_NAME}.yaml"));
+        if pointer.is_file() {
+            return read_vault_pointer(&pointer, dir).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+/// Contents of a `.setupvault.yaml` pointer file.
+#[derive(Deserialize)]
+struct VaultPointer {
+    path: String,
+}
+
+fn read_vault_pointer(pointer: &Path, dir: &Path) -> CoreResult<PathBuf> {
+    let invalid = |message: String| CoreError::Storage(format!("{}: {message}", pointer.display()));
+    let contents = fs::read_to_string(pointer).map_err(|err| invalid(err.to_string()))?;
+    let parsed: VaultPointer =
+        serde_yaml::from_str(&contents).map_err(|err| invalid(err.to_string()))?;
+    let path = parsed.path.trim();
+    if path.is_empty() {
+        return Err(invalid("path is empty".into()));
+    }
+    let path = match path.strip_prefix('~') {
+        Some(rest) => expand_home(rest).unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    };
+    Ok(if path.is_absolute() { path } else { dir.join(path) })
+}
+
 impl VaultRepository for FsVault {
     fn list(&self) -> CoreResult<Vec<Entry>> {
         let entries_root = self.entries_root();
@@ -324,6 +1280,8 @@ impl VaultRepository for FsVault {
             let parsed = parse_entry(&contents)?;
             entries.push(parsed);
         }
+        // Directory walk order varies by platform; detection time, then id, is stable.
+        entries.sort_by(|a, b| a.detected_at.cmp(&b.detected_at).then(a.id.cmp(&b.id)));
         Ok(entries)
     }
 
@@ -338,25 +1296,37 @@ impl VaultRepository for FsVault {
     }
 
     fn create(&self, entry: &Entry) -> CoreResult<()> {
+        if self.find_entry_path(entry.id)?.is_some() {
+            return Err(CoreError::Validation("entry id already exists".into()));
+        }
         let path = self.entry_path(entry);
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)
                 .map_err(|err| CoreError::Storage(err.to_string()))?;
         }
         let content = render_entry(entry)?;
+        tracing::debug!(path = %path.display(), id = %entry.id, "writing entry");
         fs::write(path, content).map_err(|err| CoreError::Storage(err.to_string()))?;
         Ok(())
     }
 
     fn update(&self, entry: &Entry) -> CoreResult<()> {
         let existing = self.find_entry_path(entry.id)?;
-        let path = existing.unwrap_or_else(|| self.entry_path(entry));
+        let path = self.entry_path(entry);
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)
                 .map_err(|err| CoreError::Storage(err.to_string()))?;
         }
         let content = render_entry(entry)?;
-        fs::write(path, content).map_err(|err| CoreError::Storage(err.to_string()))?;
+        tracing::debug!(path = %path.display(), id = %entry.id, "writing entry");
+        fs::write(&path, content).map_err(|err| CoreError::Storage(err.to_string()))?;
+        // A changed title, type, or source moves the file; drop the stale copy so the id
+        // stays unique on disk.
+        if let Some(stale) = existing.filter(|stale| *stale != path) {
+            tracing::debug!(path = %stale.display(), id = %entry.id, "removing stale entry file");
+            fs::remove_file(&stale).map_err(|err| CoreError::Storage(err.to_string()))?;
+            self.prune_empty_dirs(&stale);
+        }
         Ok(())
     }
 
@@ -364,41 +1334,600 @@ impl VaultRepository for FsVault {
         let Some(path) = self.find_entry_path(id)? else {
             return Ok(());
         };
+        tracing::debug!(path = %path.display(), %id, "deleting entry");
         fs::remove_file(path).map_err(|err| CoreError::Storage(err.to_string()))?;
         Ok(())
     }
 }
 
 impl FsVault {
-    /// Remove an entry and restore it to the inbox.
-    pub fn restore_to_inbox(&self, id: Uuid) -> CoreResult<()> {
-        let Some(entry) = self.get(id)? else {
-            return Ok(());
-        };
-        
-        let change = DetectedChange {
-            id: Uuid::new_v4(), // Assign new ID for inbox instance
-            path: None, // Path info is lost in Entry conversion unfortunately, or could be inferred
-            title: entry.title,
-            entry_type: entry.entry_type,
-            source: entry.source,
-            cmd: entry.cmd,
-            system: entry.system,
-            detected_at: entry.detected_at,
-            tags: entry.tags,
-        };
-
-        self.delete(id)?;
-        self.add_inbox_item(change)?;
-        Ok(())
+    /// Summarize the library and queues; see [`VaultStats`].
+    pub fn stats(&self) -> CoreResult<VaultStats> {
+        Ok(VaultStats::from_parts(&self.list()?, &self.load_inbox()?, &self.load_snoozed()?))
     }
-}
 
-/// Render an entry into Markdown with YAML frontmatter.
+    /// Rewrite an entry's file in canonical form from its parsed model.
+    ///
+    /// Returns `None` when no entry has the id, and whether the file changed otherwise.
+    pub fn repair_entry(&self, id: Uuid) -> CoreResult<Option<bool>> {
+        let Some(path) = self.find_entry_path(id)? else {
+            return Ok(None);
+        };
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| CoreError::Storage(err.to_string()))?;
+        self.rewrite_canonical(&path, &contents).map(Some)
+    }
+
+    /// Replace tag `from` with `to` on every entry that has it, returning how many changed.
+    pub fn merge_tag(&self, from: &Tag, to: &Tag) -> CoreResult<usize> {
+        let mut merged = 0;
+        for mut entry in self.list()? {
+            if entry.replace_tag(from, to) {
+                self.update(&entry)?;
+                merged += 1;
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Rewrite every parseable entry file in the current on-disk format, adding sections and
+    /// defaults introduced since it was written. Unparseable files are left alone and
+    /// reported, so running it again changes nothing.
+    pub fn migrate_entries(&self) -> CoreResult<MigrationReport> {
+        let mut report = MigrationReport::default();
+        for path in self.entry_files()? {
+            report.entries_checked += 1;
+            let rewritten = fs::read_to_string(&path)
+                .map_err(|err| CoreError::Storage(err.to_string()))
+                .and_then(|contents| self.rewrite_canonical(&path, &contents));
+            match rewritten {
+                Ok(true) => report.migrated += 1,
+                Ok(false) => {}
+                Err(err) => report.skipped.push((path, err.to_string())),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Move every parseable entry file to where `layout` stores it, leaving file contents
+    /// untouched. Unparseable files stay in place and are reported.
+    ///
+    /// The vault switches to `layout`, and records it in its settings, only when every file
+    /// was moved; otherwise it keeps its layout so the migration can be rerun.
+    pub fn migrate_layout(&mut self, layout: VaultLayout) -> CoreResult<MigrationReport> {
+        let target_vault = self.clone().with_layout(layout);
+        let mut report = MigrationReport::default();
+        for path in self.entry_files()? {
+            report.entries_checked += 1;
+            let contents =
+                fs::read_to_string(&path).map_err(|err| CoreError::Storage(err.to_string()))?;
+            let entry = match parse_entry(&contents) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    report.skipped.push((path, err.to_string()));
+                    continue;
+                }
+            };
+            let target = target_vault.entry_path(&entry);
+            if target == path {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|err| CoreError::Storage(err.to_string()))?;
+            }
+            tracing::debug!(from = %path.display(), to = %target.display(), "moving entry");
+            fs::rename(&path, &target).map_err(|err| CoreError::Storage(err.to_string()))?;
+            self.prune_empty_dirs(&path);
+            report.migrated += 1;
+        }
+        if report.skipped.is_empty() {
+            let mut settings = self.settings.clone();
+            settings.layout = layout;
+            self.save_settings(settings)?;
+        }
+        Ok(report)
+    }
+
+    /// Every `*.md` file under `entries/`, collected up front because migrations move files
+    /// while the tree would otherwise be walked.
+    fn entry_files(&self) -> CoreResult<Vec<PathBuf>> {
+        let entries_root = self.entries_root();
+        if !entries_root.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(WalkDir::new(&entries_root)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|file| {
+                file.file_type().is_file()
+                    && file.path().extension().and_then(|ext| ext.to_str()) == Some("md")
+            })
+            .map(|file| file.into_path())
+            .collect())
+    }
+
+    /// Rewrite the entry stored at `path` from its parsed model; returns whether it changed.
+    fn rewrite_canonical(&self, path: &Path, contents: &str) -> CoreResult<bool> {
+        let entry = parse_entry(contents)?;
+        if contents == render_entry(&entry)? && path == self.entry_path(&entry) {
+            return Ok(false);
+        }
+        self.update(&entry)?;
+        Ok(true)
+    }
+
+    /// SHA-256 of an entry's file as stored, as lowercase hex, or `None` when no entry has
+    /// the id.
+    pub fn entry_checksum(&self, id: Uuid) -> CoreResult<Option<String>> {
+        let Some(path) = self.find_entry_path(id)? else {
+            return Ok(None);
+        };
+        let contents = fs::read(&path).map_err(|err| CoreError::Storage(err.to_string()))?;
+        Ok(Some(sha256_hex(&contents)))
+    }
+
+    /// Move the whole vault directory to `target` and return the vault at its new location.
+    ///
+    /// Missing parent directories are created. A target that is a file or a non-empty
+    /// directory is refused unless `force` is set, in which case it is replaced once the
+    /// vault has been copied beside it. Targets inside the vault, or containing it, are
+    /// always refused. Falls back to copy-and-delete when a rename is not possible (for
+    /// example across filesystems).
+    pub fn move_to(&self, target: &Path, force: bool) -> CoreResult<FsVault> {
+        let storage = |err: std::io::Error| CoreError::Storage(err.to_string());
+        if !self.root.exists() {
+            return Err(CoreError::Validation("source vault path does not exist".into()));
+        }
+        let root = canonicalize_existing_prefix(&self.root);
+        let canonical_target = canonicalize_existing_prefix(target);
+        if canonical_target.starts_with(&root) {
+            return Err(CoreError::Validation(
+                "target path is inside the current vault".into(),
+            ));
+        }
+        if root.starts_with(&canonical_target) {
+            return Err(CoreError::Validation(
+                "target path contains the current vault".into(),
+            ));
+        }
+
+        if target.exists() {
+            let occupied = !target.is_dir()
+                || fs::read_dir(target).map_err(storage)?.next().is_some();
+            if occupied && !force {
+                return Err(CoreError::Validation(if target.is_dir() {
+                    "target directory is not empty".into()
+                } else {
+                    "target path exists and is not a directory".into()
+                }));
+            }
+            return self.replace_target(target);
+        } else if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(storage)?;
+        }
+
+        tracing::debug!(from = %self.root.display(), to = %target.display(), "moving vault");
+        if fs::rename(&self.root, target).is_err() {
+            copy_dir_all(&self.root, target)?;
+            fs::remove_dir_all(&self.root).map_err(storage)?;
+        }
+        Ok(self.with_root(target.to_path_buf()))
+    }
+
+    /// Copy the vault beside an existing `target`, then swap it in and remove the original.
+    ///
+    /// Nothing is deleted until the copy is complete, so a failed copy leaves both the vault
+    /// and the target untouched.
+    fn replace_target(&self, target: &Path) -> CoreResult<FsVault> {
+        let storage = |err: std::io::Error| CoreError::Storage(err.to_string());
+        let name = target.file_name().unwrap_or_default().to_string_lossy();
+        let staging = target.with_file_name(format!(".{name}.setupvault-move-{}", Uuid::new_v4()));
+        tracing::debug!(from = %self.root.display(), to = %target.display(), "replacing target");
+        if let Err(err) = copy_dir_all(&self.root, &staging) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(err);
+        }
+        if target.is_dir() {
+            fs::remove_dir_all(target).map_err(storage)?;
+        } else {
+            fs::remove_file(target).map_err(storage)?;
+        }
+        fs::rename(&staging, target).map_err(storage)?;
+        fs::remove_dir_all(&self.root).map_err(storage)?;
+        Ok(self.with_root(target.to_path_buf()))
+    }
+
+    /// Remove an entry and restore it to the inbox, returning whether it was moved.
+    ///
+    /// Nothing changes when the entry is missing or a change with the same source and title
+    /// is already queued, so the entry is never deleted without being re-queued.
+    pub fn restore_to_inbox(&self, id: Uuid) -> CoreResult<bool> {
+        let Some(entry) = self.get(id)? else {
+            return Ok(false);
+        };
+        if self
+            .load_inbox()?
+            .iter()
+            .any(|change| change.dedupe_key() == (entry.source.as_str(), entry.title.as_str()))
+        {
+            return Ok(false);
+        }
+
+        let change = DetectedChange {
+            id: Uuid::new_v4(), // Assign new ID for inbox instance
+            path: None, // Entries do not record the path the change was detected at
+            title: entry.title,
+            entry_type: entry.entry_type,
+            source: entry.source,
+            cmd: entry.cmd,
+            system: entry.system,
+            detected_at: entry.detected_at,
+            tags: entry.tags,
+            detector: entry.detector,
+        };
+
+        self.delete(id)?;
+        self.force_add_inbox_item(change)?;
+        Ok(true)
+    }
+
+    /// Check the vault for unparseable entries, duplicate ids, queue ids that collide with
+    /// library ids, and state files that are not valid YAML.
+    pub fn verify_integrity(&self) -> CoreResult<IntegrityReport> {
+        self.check_integrity(false)
+    }
+
+    /// Like [`FsVault::verify_integrity`], but also reports entries whose frontmatter carries
+    /// keys this version does not know, such as typos or fields from a newer schema.
+    pub fn verify_integrity_strict(&self) -> CoreResult<IntegrityReport> {
+        self.check_integrity(true)
+    }
+
+    fn check_integrity(&self, strict: bool) -> CoreResult<IntegrityReport> {
+        let mut report = IntegrityReport::default();
+        // Ids in first-seen order, so duplicates are reported in walk order; the map keeps
+        // lookups constant-time on large vaults.
+        let mut paths_by_id: Vec<(Uuid, Vec<PathBuf>)> = Vec::new();
+        let mut index_by_id: HashMap<Uuid, usize> = HashMap::new();
+
+        let entries_root = self.entries_root();
+        if entries_root.exists() {
+            for file in WalkDir::new(&entries_root)
+                .into_iter()
+                .filter_map(Result::ok)
+            {
+                if !file.file_type().is_file()
+                    || file.path().extension().and_then(|ext| ext.to_str()) != Some("md")
+                {
+                    continue;
+                }
+                report.entries_checked += 1;
+                let parsed = fs::read_to_string(file.path())
+                    .map_err(|err| CoreError::Storage(err.to_string()))
+                    .and_then(|contents| {
+                        let entry = parse_entry(&contents)?;
+                        let unknown = if strict {
+                            unknown_frontmatter_fields(&contents)?
+                        } else {
+                            Vec::new()
+                        };
+                        Ok((entry, unknown))
+                    });
+                match parsed {
+                    Ok((entry, unknown)) => {
+                        if !unknown.is_empty() {
+                            report
+                                .issues
+                                .push(IntegrityIssue::UnknownFrontmatterFields {
+                                    path: file.path().to_path_buf(),
+                                    fields: unknown,
+                                });
+                        }
+                        match index_by_id.get(&entry.id) {
+                            Some(&index) => paths_by_id[index].1.push(file.into_path()),
+                            None => {
+                                index_by_id.insert(entry.id, paths_by_id.len());
+                                paths_by_id.push((entry.id, vec![file.into_path()]));
+                            }
+                        }
+                    }
+                    Err(err) => report.issues.push(IntegrityIssue::UnparseableEntry {
+                        path: file.into_path(),
+                        error: err.to_string(),
+                    }),
+                }
+            }
+        }
+
+        for (id, paths) in &paths_by_id {
+            if paths.len() > 1 {
+                report.issues.push(IntegrityIssue::DuplicateEntryId {
+                    id: *id,
+                    paths: paths.clone(),
+                });
+            }
+        }
+
+        let queues = [
+            ("inbox", self.inbox_path(), self.load_inbox()),
+            ("snoozed", self.snoozed_path(), self.load_snoozed()),
+        ];
+        for (queue, path, changes) in queues {
+            match changes {
+                Ok(changes) => {
+                    for change in changes {
+                        if index_by_id.contains_key(&change.id) {
+                            report.issues.push(IntegrityIssue::QueueIdCollision {
+                                queue,
+                                id: change.id,
+                            });
+                        }
+                    }
+                }
+                Err(err) => report.issues.push(IntegrityIssue::InvalidStateFile {
+                    path,
+                    error: err.to_string(),
+                }),
+            }
+        }
+
+        let snapshots_root = self.state_root().join("detectors");
+        if snapshots_root.exists() {
+            for file in WalkDir::new(&snapshots_root)
+                .into_iter()
+                .filter_map(Result::ok)
+            {
+                if !file.file_type().is_file() {
+                    continue;
+                }
+                let parsed = fs::read_to_string(file.path())
+                    .map_err(|err| err.to_string())
+                    .and_then(|contents| {
+                        serde_yaml::from_str::<Vec<DetectedChange>>(&contents)
+                            .map_err(|err| err.to_string())
+                    });
+                if let Err(error) = parsed {
+                    report.issues.push(IntegrityIssue::InvalidStateFile {
+                        path: file.into_path(),
+                        error,
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Move unparseable entries and invalid state files from `report` into `.state/quarantine/`.
+    ///
+    /// Returns the new location of each moved file.
+    pub fn quarantine(&self, report: &IntegrityReport) -> CoreResult<Vec<PathBuf>> {
+        let quarantine_root = self.state_root().join("quarantine");
+        let mut moved = Vec::new();
+        for issue in &report.issues {
+            let path = match issue {
+                IntegrityIssue::UnparseableEntry { path, .. }
+                | IntegrityIssue::InvalidStateFile { path, .. } => path,
+                _ => continue,
+            };
+            if !path.exists() {
+                continue;
+            }
+            fs::create_dir_all(&quarantine_root)
+                .map_err(|err| CoreError::Storage(err.to_string()))?;
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "file".into());
+            let mut target = quarantine_root.join(&file_name);
+            let mut counter = 1;
+            while target.exists() {
+                target = quarantine_root.join(format!("{counter}-{file_name}"));
+                counter += 1;
+            }
+            tracing::debug!(from = %path.display(), to = %target.display(), "quarantining file");
+            fs::rename(path, &target).map_err(|err| CoreError::Storage(err.to_string()))?;
+            if path.starts_with(self.entries_root()) {
+                self.prune_empty_dirs(path);
+            }
+            moved.push(target);
+        }
+        Ok(moved)
+    }
+
+    /// Watch `entries/` and the inbox and snoozed queues for changes made outside this
+    /// process, such as an entry edited in a text editor.
+    pub fn watch(&self) -> CoreResult<VaultWatcher> {
+        use notify::{RecursiveMode, Watcher};
+
+        let storage = |err: notify::Error| CoreError::Storage(err.to_string());
+        // Some backends report canonical paths, so classify against the canonical root.
+        let root = fs::canonicalize(&self.root).map_err(|err| CoreError::Storage(err.to_string()))?;
+        let vault = Self::new(root);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let classifier = vault.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else { return };
+            use notify::event::{CreateKind, EventKind, RemoveKind};
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            // Files written into a brand-new directory can land before it is watched.
+            let is_dir = matches!(
+                event.kind,
+                EventKind::Create(CreateKind::Folder) | EventKind::Remove(RemoveKind::Folder)
+            );
+            for path in &event.paths {
+                let Some(change) = classifier.classify_change(path, is_dir) else { continue };
+                // The receiver is gone once the watcher is dropped; nothing is left to notify.
+                let _ = sender.send(change);
+            }
+        })
+        .map_err(storage)?;
+        watcher.watch(&vault.entries_root(), RecursiveMode::Recursive).map_err(storage)?;
+        watcher.watch(&vault.state_root(), RecursiveMode::NonRecursive).map_err(storage)?;
+        Ok(VaultWatcher {
+            _watcher: watcher,
+            receiver,
+            pending: BTreeSet::new(),
+            last_event: None,
+        })
+    }
+
+    fn classify_change(&self, path: &Path, is_dir: bool) -> Option<VaultChange> {
+        if path.starts_with(self.entries_root()) {
+            // Editors drop swap and backup files next to the entry; only entries matter.
+            let is_entry = is_dir || path.extension().is_some_and(|ext| ext == "md");
+            return is_entry.then_some(VaultChange::Entries);
+        }
+        if path == self.inbox_path() {
+            Some(VaultChange::Inbox)
+        } else if path == self.snoozed_path() {
+            Some(VaultChange::Snoozed)
+        } else {
+            None
+        }
+    }
+}
+
+/// Part of the vault that changed on disk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VaultChange {
+    /// An entry file was created, modified, moved, or removed.
+    Entries,
+    /// The inbox queue was rewritten.
+    Inbox,
+    /// The snoozed queue was rewritten.
+    Snoozed,
+}
+
+/// Quiet period after the last filesystem event before [`VaultWatcher::poll`] reports it.
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Filesystem watch over a vault, created by [`FsVault::watch`]; watching stops when dropped.
+pub struct VaultWatcher {
+    _watcher: notify::RecommendedWatcher,
+    receiver: std::sync::mpsc::Receiver<VaultChange>,
+    pending: BTreeSet<VaultChange>,
+    last_event: Option<Instant>,
+}
+
+impl VaultWatcher {
+    /// Return the changed parts once no event has arrived for [`WATCH_DEBOUNCE`], so a burst
+    /// of writes (an editor saving, a refresh) is reported once. Empty while events are
+    /// still arriving or nothing changed.
+    pub fn poll(&mut self) -> BTreeSet<VaultChange> {
+        let now = Instant::now();
+        for change in self.receiver.try_iter() {
+            self.pending.insert(change);
+            self.last_event = Some(now);
+        }
+        match self.last_event {
+            Some(last) if now.duration_since(last) >= WATCH_DEBOUNCE => {
+                self.last_event = None;
+                std::mem::take(&mut self.pending)
+            }
+            _ => BTreeSet::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for VaultWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultWatcher")
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Render an entry into Markdown with YAML frontmatter.
 pub fn render_entry_markdown(entry: &Entry) -> CoreResult<String> {
     render_entry(entry)
 }
 
+/// Parse an entry from Markdown with YAML frontmatter, as written by
+/// [`render_entry_markdown`].
+pub fn parse_entry_markdown(contents: &str) -> CoreResult<Entry> {
+    parse_entry(contents)
+}
+
+/// Parse an entry like [`parse_entry_markdown`], but reject frontmatter keys outside
+/// [`FRONTMATTER_FIELDS`] instead of ignoring them.
+///
+/// Normal loads stay lenient so entries written by newer versions still open; this is for
+/// validation, where schema drift should fail loudly.
+pub fn parse_entry_strict(contents: &str) -> CoreResult<Entry> {
+    let entry = parse_entry(contents)?;
+    let unknown = unknown_frontmatter_fields(contents)?;
+    if !unknown.is_empty() {
+        return Err(CoreError::Storage(format!(
+            "unknown frontmatter field(s): {}",
+            unknown.join(", ")
+        )));
+    }
+    Ok(entry)
+}
+
+/// Extension appended to an entry file's name for its checksum sidecar.
+pub const CHECKSUM_EXTENSION: &str = "sha256";
+
+/// Sidecar path for `path`, for example `jq.md` → `jq.md.sha256`.
+pub fn checksum_sidecar_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(CHECKSUM_EXTENSION);
+    path.with_file_name(name)
+}
+
+/// Render a sidecar line for `contents` in `sha256sum` format, so `sha256sum -c` can check
+/// it as well.
+pub fn render_checksum_sidecar(contents: &[u8], file_name: &str) -> String {
+    format!("{}  {file_name}\n", sha256_hex(contents))
+}
+
+/// Check a file against its checksum sidecar.
+///
+/// Returns whether the digests match; a missing or malformed sidecar is an error. The
+/// sidecar sits next to the file, so this detects corruption, not tampering: whoever can
+/// edit the file can rewrite its sidecar too.
+pub fn verify_checksum_sidecar(path: &Path) -> CoreResult<bool> {
+    let sidecar = checksum_sidecar_path(path);
+    let recorded = fs::read_to_string(&sidecar).map_err(|err| {
+        CoreError::Storage(format!("failed to read {}: {err}", sidecar.display()))
+    })?;
+    let recorded = recorded
+        .split_whitespace()
+        .next()
+        .filter(|digest| digest.len() == 64 && digest.chars().all(|ch| ch.is_ascii_hexdigit()))
+        .ok_or_else(|| {
+            CoreError::Validation(format!("malformed checksum in {}", sidecar.display()))
+        })?;
+    let contents = fs::read(path).map_err(|err| CoreError::Storage(err.to_string()))?;
+    Ok(recorded.eq_ignore_ascii_case(&sha256_hex(&contents)))
+}
+
+/// SHA-256 digest of `bytes` as lowercase hex.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Keys written to entry frontmatter, in the order `Frontmatter` serializes them; a test
+/// keeps the two in sync.
+pub const FRONTMATTER_FIELDS: &[&str] = &[
+    "id",
+    "title",
+    "type",
+    "source",
+    "detector",
+    "cmd",
+    "system",
+    "detected_at",
+    "status",
+    "tags",
+    "pinned",
+    "last_verified",
+];
+
 #[derive(Debug, Deserialize, Serialize)]
 struct Frontmatter {
     id: Uuid,
@@ -406,11 +1935,17 @@ struct Frontmatter {
     #[serde(rename = "type")]
     entry_type: EntryType,
     source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detector: Option<String>,
     cmd: String,
     system: SystemInfo,
     detected_at: DateTime<Utc>,
     status: EntryStatus,
     tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_verified: Option<DateTime<Utc>>,
 }
 
 fn render_entry(entry: &Entry) -> CoreResult<String> {
@@ -419,11 +1954,14 @@ fn render_entry(entry: &Entry) -> CoreResult<String> {
         title: entry.title.clone(),
         entry_type: entry.entry_type.clone(),
         source: entry.source.clone(),
+        detector: entry.detector.clone(),
         cmd: entry.cmd.clone(),
         system: entry.system.clone(),
         detected_at: entry.detected_at,
         status: entry.status.clone(),
         tags: entry.tags.iter().map(|tag| tag.as_str().to_string()).collect(),
+        pinned: entry.pinned,
+        last_verified: entry.last_verified,
     };
     let yaml = serde_yaml::to_string(&frontmatter)
         .map_err(|err| CoreError::Storage(err.to_string()))?;
@@ -438,9 +1976,41 @@ fn render_entry(entry: &Entry) -> CoreResult<String> {
         content.push_str(verification);
     }
     content.push('\n');
+    if let Some(uninstall_cmd) = &entry.uninstall_cmd {
+        content.push_str("\n# Uninstall\n");
+        content.push_str(uninstall_cmd);
+        content.push('\n');
+    }
+    if let Some(notes) = &entry.notes {
+        content.push_str("\n# Notes\n");
+        content.push_str(notes.trim_end());
+        content.push('\n');
+    }
+    if !entry.rationale_history.is_empty() {
+        let revisions: Vec<_> = entry
+            .rationale_history
+            .iter()
+            .map(|(replaced_at, rationale)| {
+                let stamp = replaced_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+                format!("## {stamp}\n{}\n", rationale.trim())
+            })
+            .collect();
+        content.push_str("\n# Rationale History\n");
+        content.push_str(&revisions.join("\n"));
+    }
+    if let Some(script_body) = &entry.script_body {
+        // Fence the body so shell comments are not mistaken for headings.
+        let fence = "`".repeat(longest_backtick_run(script_body).max(2) + 1);
+        content.push_str("\n# Script\n");
+        content.push_str(&format!("{fence}\n{}\n{fence}\n", script_body.trim_end()));
+    }
     Ok(content)
 }
 
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|ch| ch != '`').map(str::len).max().unwrap_or(0)
+}
+
 fn parse_entry(contents: &str) -> CoreResult<Entry> {
     let frontmatter = parse_frontmatter(contents)?;
     let body = parse_body(contents)?;
@@ -448,6 +2018,8 @@ fn parse_entry(contents: &str) -> CoreResult<Entry> {
         .ok_or_else(|| CoreError::Storage("missing rationale section".into()))?;
     let rationale = Rationale::new(rationale)?;
     let verification = extract_section(&body, "Verification");
+    let uninstall_cmd =
+        extract_section(&body, "Uninstall").filter(|section| !section.is_empty());
 
     let tags = frontmatter
         .tags
@@ -455,7 +2027,7 @@ fn parse_entry(contents: &str) -> CoreResult<Entry> {
         .map(Tag::new)
         .collect::<CoreResult<Vec<_>>>()?;
 
-    Entry::new(
+    let mut entry = Entry::new(
         frontmatter.id,
         frontmatter.title,
         frontmatter.entry_type,
@@ -467,7 +2039,38 @@ fn parse_entry(contents: &str) -> CoreResult<Entry> {
         tags,
         rationale,
         verification,
-    )
+    )?;
+    entry.pinned = frontmatter.pinned;
+    entry.detector = frontmatter.detector;
+    entry.last_verified = frontmatter.last_verified;
+    entry.uninstall_cmd = uninstall_cmd;
+    entry.notes = extract_section(&body, "Notes").filter(|section| !section.is_empty());
+    entry.script_body = extract_script(&body);
+    entry.rationale_history = extract_section(&body, "Rationale History")
+        .map(|section| parse_rationale_history(&section))
+        .unwrap_or_default();
+    Ok(entry)
+}
+
+/// Split the `# Rationale History` section into revisions at `## <RFC 3339>` lines; a `##`
+/// line that is not a timestamp stays part of the revision text.
+fn parse_rationale_history(section: &str) -> Vec<(DateTime<Utc>, String)> {
+    let mut history: Vec<(DateTime<Utc>, Vec<&str>)> = Vec::new();
+    for line in section.lines() {
+        let stamp = line
+            .strip_prefix("## ")
+            .and_then(|stamp| DateTime::parse_from_rfc3339(stamp.trim()).ok());
+        match (stamp, history.last_mut()) {
+            (Some(stamp), _) => history.push((stamp.with_timezone(&Utc), Vec::new())),
+            (None, Some((_, lines))) => lines.push(line),
+            (None, None) => {}
+        }
+    }
+    history
+        .into_iter()
+        .map(|(stamp, lines)| (stamp, lines.join("\n").trim().to_string()))
+        .filter(|(_, text)| !text.is_empty())
+        .collect()
 }
 
 fn parse_frontmatter(contents: &str) -> CoreResult<Frontmatter> {
@@ -475,44 +2078,162 @@ fn parse_frontmatter(contents: &str) -> CoreResult<Frontmatter> {
     serde_yaml::from_str(frontmatter).map_err(|err| CoreError::Storage(err.to_string()))
 }
 
+fn unknown_frontmatter_fields(contents: &str) -> CoreResult<Vec<String>> {
+    let (frontmatter, _) = split_frontmatter(contents)?;
+    let mapping: serde_yaml::Mapping =
+        serde_yaml::from_str(frontmatter).map_err(|err| CoreError::Storage(err.to_string()))?;
+    Ok(mapping
+        .keys()
+        .map(|key| match key.as_str() {
+            Some(key) => key.to_string(),
+            None => serde_yaml::to_string(key).unwrap_or_default().trim().to_string(),
+        })
+        .filter(|key| !FRONTMATTER_FIELDS.contains(&key.as_str()))
+        .collect())
+}
+
 fn parse_body(contents: &str) -> CoreResult<String> {
     let (_, body) = split_frontmatter(contents)?;
     Ok(body.to_string())
 }
 
 fn split_frontmatter(contents: &str) -> CoreResult<(&str, &str)> {
-    let header = "---\n";
-    if !contents.starts_with(header) {
-        return Err(CoreError::Storage("missing frontmatter header".into()));
+    // Accept both LF and CRLF delimiters so entries edited on Windows still parse.
+    let remainder = contents
+        .strip_prefix("---\n")
+        .or_else(|| contents.strip_prefix("---\r\n"))
+        .ok_or_else(|| CoreError::Storage("missing frontmatter header".into()))?;
+
+    let mut offset = 0;
+    for line in remainder.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            let frontmatter = &remainder[..offset];
+            let body = &remainder[offset + line.len()..];
+            return Ok((frontmatter.trim_end(), body.trim_start()));
+        }
+        offset += line.len();
     }
+    Err(CoreError::Storage("unterminated frontmatter".into()))
+}
 
-    let marker = "\n---\n";
-    let remainder = &contents[header.len()..];
-    let end = remainder
-        .find(marker)
-        .ok_or_else(|| CoreError::Storage("unterminated frontmatter".into()))?;
+/// Headings `render_entry` writes in every entry. Only headings end a section, so free text
+/// such as a rationale may contain its own `# ` lines (Markdown headings, shell comments).
+const CORE_SECTION_HEADINGS: [&str; 3] = ["Rationale", "Verification", "Uninstall"];
 
-    let frontmatter = &remainder[..end];
-    let body_start = end + marker.len();
-    let body = &remainder[body_start..];
-    Ok((frontmatter.trim_end(), body.trim_start()))
+/// Headings that end a section wherever they appear; hand edits may reorder them.
+const SECTION_HEADINGS: [&str; 4] = ["Rationale", "Verification", "Uninstall", "Script"];
+
+/// Optional headings `render_entry` writes after the core sections, in this order. They only
+/// count in that position, so a rationale saved before they existed keeps its own `# Notes`.
+const TRAILING_SECTION_HEADINGS: [&str; 2] = ["Notes", "Rationale History"];
+
+/// Line indexes and names of the headings in `body`, in file order.
+fn section_headings(body: &str) -> Vec<(usize, &'static str)> {
+    fn heading(line: &str) -> Option<&str> {
+        line.trim().strip_prefix("# ").map(str::trim)
+    }
+    let lines: Vec<&str> = body.lines().collect();
+    let script = lines
+        .iter()
+        .position(|line| heading(line) == Some("Script"))
+        .unwrap_or(lines.len());
+    let last_core = lines[..script]
+        .iter()
+        .rposition(|line| heading(line).is_some_and(|name| CORE_SECTION_HEADINGS.contains(&name)));
+    let mut next_trailing = 0;
+    let mut headings = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(name) = heading(line) else {
+            continue;
+        };
+        if let Some(known) = SECTION_HEADINGS.iter().find(|known| **known == name) {
+            headings.push((index, *known));
+            continue;
+        }
+        let Some(rank) = TRAILING_SECTION_HEADINGS.iter().position(|trailing| *trailing == name)
+        else {
+            continue;
+        };
+        if rank >= next_trailing && last_core.is_none_or(|core| index > core) && index < script {
+            headings.push((index, TRAILING_SECTION_HEADINGS[rank]));
+            next_trailing = rank + 1;
+        }
+    }
+    headings
 }
 
 fn extract_section(body: &str, heading: &str) -> Option<String> {
-    let mut lines = body.lines();
-    while let Some(line) = lines.next() {
-        if line.trim() == format!("# {heading}") {
-            let mut section = Vec::new();
-            for line in lines.by_ref() {
-                if line.trim_start().starts_with("# ") {
-                    break;
+    // `lines` strips both `\n` and `\r\n`, so section content never carries stray `\r`.
+    let lines: Vec<&str> = body.lines().collect();
+    let headings = section_headings(body);
+    let position = headings.iter().position(|(_, name)| *name == heading)?;
+    let start = headings[position].0 + 1;
+    let end = headings.get(position + 1).map_or(lines.len(), |(index, _)| *index);
+    Some(lines[start..end].join("\n").trim().to_string())
+}
+
+/// Read the fenced block under `# Script`, keeping its lines verbatim.
+fn extract_script(body: &str) -> Option<String> {
+    let mut lines = body.lines().skip_while(|line| line.trim() != "# Script").skip(1);
+    let opening = lines.find(|line| !line.trim().is_empty())?.trim();
+    let fence_len = opening.len() - opening.trim_start_matches('`').len();
+    if fence_len < 3 {
+        return None;
+    }
+    let fence = &opening[..fence_len];
+    let script: Vec<_> = lines.take_while(|line| line.trim_end() != fence).collect();
+    let script = script.join("\n");
+    (!script.trim().is_empty()).then_some(script)
+}
+
+/// `path` with its longest existing ancestor canonicalized and the missing rest appended, so
+/// paths that do not exist yet still compare correctly against real ones.
+///
+/// Relative paths resolve against the current directory, and `.` and `..` in the missing
+/// rest are applied lexically (it holds no symlinks to follow).
+fn canonicalize_existing_prefix(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        if let Ok(mut resolved) = existing.canonicalize() {
+            for part in missing.iter().rev() {
+                match part {
+                    Component::CurDir => {}
+                    Component::ParentDir => {
+                        resolved.pop();
+                    }
+                    part => resolved.push(part),
                 }
-                section.push(line);
             }
-            return Some(section.join("\n").trim().to_string());
+            return resolved;
+        }
+        let mut components = existing.components();
+        match components.next_back() {
+            Some(part) if existing != Path::new(".") => {
+                missing.push(part);
+                existing = components.as_path();
+                if existing.as_os_str().is_empty() {
+                    existing = Path::new(".");
+                }
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+fn copy_dir_all(source: &Path, target: &Path) -> CoreResult<()> {
+    let storage = |err: std::io::Error| CoreError::Storage(err.to_string());
+    fs::create_dir_all(target).map_err(storage)?;
+    for entry in fs::read_dir(source).map_err(storage)? {
+        let path = entry.map_err(storage)?.path();
+        let dest = target.join(path.file_name().unwrap_or_default());
+        if path.is_dir() {
+            copy_dir_all(&path, &dest)?;
+        } else {
+            fs::copy(&path, &dest).map_err(storage)?;
         }
     }
-    None
+    Ok(())
 }
 
 fn slugify(input: &str) -> String {
@@ -533,6 +2254,7 @@ fn slugify(input: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sv_core::InMemoryVault;
     use tempfile::TempDir;
 
     #[test]
@@ -548,6 +2270,7 @@ mod tests {
             SystemInfo {
                 os: "macos".into(),
                 arch: "arm64".into(),
+                hostname: None,
             },
             Utc::now(),
             EntryStatus::Active,
@@ -556,10 +2279,956 @@ mod tests {
             Some("jq --version".into()),
         )
         .unwrap();
+        let mut entry = entry;
+        entry.pinned = true;
+        entry.detector = Some("homebrew".into());
+
+        vault.create(&entry).expect("create entry");
+        let fetched = vault.get(entry.id).expect("get entry").expect("entry exists");
+        assert_eq!(fetched.title, "jq");
+        assert!(fetched.pinned);
+        assert_eq!(fetched.detector.as_deref(), Some("homebrew"));
+    }
+
+    #[test]
+    fn find_project_vault_walks_up_to_a_marker() {
+        let temp = TempDir::new().expect("temp dir");
+        let project = temp.path().join("project");
+        let nested = project.join("src/bin/tool");
+        fs::create_dir_all(&nested).expect("nested dir");
+        assert_eq!(find_project_vault(&nested, Some(temp.path())).expect("search"), None);
+
+        let pointer = project.join(".setupvault.yaml");
+        fs::write(&pointer, "path: ../shared-vault\n").expect("write pointer");
+        assert_eq!(
+            find_project_vault(&nested, Some(temp.path())).expect("search"),
+            Some(project.join("../shared-vault"))
+        );
+
+        let marker = project.join("src/.setupvault");
+        fs::create_dir_all(&marker).expect("marker dir");
+        assert_eq!(
+            find_project_vault(&nested, Some(temp.path())).expect("search"),
+            Some(marker)
+        );
+    }
+
+    #[test]
+    fn find_project_vault_stops_at_the_stop_directory() {
+        let temp = TempDir::new().expect("temp dir");
+        let home = temp.path().join("home");
+        let nested = home.join("code/app");
+        fs::create_dir_all(&nested).expect("nested dir");
+        fs::create_dir_all(home.join(".setupvault")).expect("default vault");
+        assert_eq!(find_project_vault(&nested, Some(&home)).expect("search"), None);
+
+        fs::write(home.join("code/.setupvault.yaml"), "path: \"\"\n").expect("write pointer");
+        assert!(find_project_vault(&nested, Some(&home)).is_err());
+    }
+
+    #[test]
+    fn flat_layout_stores_entries_directly_under_entries() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf()).with_layout(VaultLayout::Flat);
+        let entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+
+        let path = vault.entry_path(&entry);
+        assert_eq!(path.parent(), Some(temp.path().join("entries").as_path()));
+        assert!(path.exists());
+        assert_eq!(vault.get(entry.id).expect("get").expect("entry").title, "jq");
+
+        let mut nested = FsVault::new(temp.path().to_path_buf());
+        assert_eq!(nested.list().expect("list").len(), 1, "reads find flat files");
+        let report = nested.migrate_layout(VaultLayout::Nested).expect("migrate to nested");
+        assert_eq!((report.migrated, report.entries_checked), (1, 1));
+        let nested_path = nested.entry_path(&entry);
+        let file_name = path.file_name().expect("file name");
+        assert!(nested_path.ends_with(Path::new("packages/homebrew").join(file_name)));
+        assert!(nested_path.exists() && !path.exists());
+
+        let mut flat = nested.clone();
+        let report = flat.migrate_layout(VaultLayout::Flat).expect("migrate to flat");
+        assert_eq!(report.migrated, 1);
+        assert!(path.exists());
+        assert!(!temp.path().join("entries/packages").exists(), "emptied dirs are pruned");
+        assert_eq!(flat.migrate_layout(VaultLayout::Flat).expect("rerun").migrated, 0);
+        let reopened = FsVault::open(temp.path().to_path_buf()).expect("open vault");
+        assert_eq!(
+            reopened.layout(),
+            VaultLayout::Flat,
+            "the layout is stored in the vault"
+        );
+
+        fs::write(temp.path().join("entries/broken.md"), "not an entry").expect("write broken");
+        let report = flat
+            .migrate_layout(VaultLayout::Nested)
+            .expect("partial migration");
+        assert_eq!((report.migrated, report.skipped.len()), (1, 1));
+        assert_eq!(
+            flat.layout(),
+            VaultLayout::Flat,
+            "a partial migration keeps the layout"
+        );
+        let reopened = FsVault::open(temp.path().to_path_buf()).expect("open vault");
+        assert_eq!(reopened.layout(), VaultLayout::Flat);
+    }
+
+    #[test]
+    fn watch_reports_debounced_entry_and_queue_changes() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let mut watcher = vault.watch().expect("watch vault");
+        let wait_for_changes = |watcher: &mut VaultWatcher| {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                let changes = watcher.poll();
+                if !changes.is_empty() || Instant::now() > deadline {
+                    return changes;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        };
+
+        let entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+        fs::write(vault.entries_root().join(".jq.md.swp"), "swap").expect("write swap file");
+        assert_eq!(wait_for_changes(&mut watcher), BTreeSet::from([VaultChange::Entries]));
+
+        vault.save_inbox(&[]).expect("save inbox");
+        vault.save_snoozed(&[]).expect("save snoozed");
+        vault.record_access(entry.id).expect("record access");
+        let changes = wait_for_changes(&mut watcher);
+        assert_eq!(changes, BTreeSet::from([VaultChange::Inbox, VaultChange::Snoozed]));
+        assert!(watcher.poll().is_empty());
+    }
+
+    #[test]
+    fn inbox_limit_warns_or_drops_the_oldest_items() {
+        let temp = TempDir::new().expect("temp dir");
+        let now = Utc::now();
+        let change = |title: &str, minutes_ago: i64| {
+            DetectedChange::builder(Uuid::new_v4(), title)
+                .entry_type(EntryType::Package)
+                .source("homebrew")
+                .cmd(format!("brew install {title}"))
+                .system(SystemInfo {
+                    os: "macos".into(),
+                    arch: "arm64".into(),
+                    hostname: None,
+                })
+                .detected_at(now - chrono::Duration::minutes(minutes_ago))
+                .build()
+                .unwrap()
+        };
+        let inbox = [change("jq", 1), change("fd", 30), change("bat", 2), change("rg", 20)];
+        let titles = |vault: &FsVault| -> Vec<String> {
+            vault.load_inbox().expect("load inbox").into_iter().map(|c| c.title).collect()
+        };
+
+        let vault = FsVault::new(temp.path().to_path_buf());
+        assert_eq!(vault.save_inbox(&inbox).expect("save unlimited"), None);
+        let vault = vault.with_inbox_limit(Some(2), InboxOverflow::Warn);
+        assert_eq!(
+            vault.save_inbox(&inbox).expect("save over limit"),
+            Some(InboxOverflowNotice::Over { count: 4, max: 2 })
+        );
+        assert_eq!(titles(&vault), ["jq", "fd", "bat", "rg"]);
+
+        let vault = vault.with_inbox_limit(Some(2), InboxOverflow::DropOldest);
+        let notice = vault.save_inbox(&inbox).expect("save with eviction");
+        assert_eq!(notice, Some(InboxOverflowNotice::Dropped { dropped: 2, max: 2 }));
+        assert_eq!(titles(&vault), ["jq", "bat"]);
+        assert_eq!(vault.with_root(temp.path().join("moved")).save_inbox(&inbox).unwrap(), notice);
+
+        let old = change("htop", 60);
+        vault.force_add_inbox_item(old.clone()).expect("add old item");
+        assert!(vault.snooze_inbox_item(old.id).expect("snooze"));
+        assert!(vault.unsnooze_item(old.id).expect("unsnooze into a full inbox"));
+        assert_eq!(titles(&vault), ["jq", "bat", "htop"]);
+        assert!(vault.load_snoozed().expect("load snoozed").is_empty());
+        assert_eq!(vault.clear_inbox().expect("clear inbox"), 3);
+        assert!(titles(&vault).is_empty());
+        assert_eq!(vault.clear_inbox().expect("clear empty inbox"), 0);
+
+        let mut settings = VaultSettings::default();
+        settings.set("max_inbox_size", "500").expect("set max");
+        settings
+            .set("inbox_overflow", "drop_oldest")
+            .expect("set policy");
+        assert_eq!(settings.get("max_inbox_size").expect("get max"), "500");
+        assert!(settings.set("max_inbox_size", "0").is_err());
+        assert!(settings.set("inbox_overflow", "evict").is_err());
+        let mut stored = FsVault::new(temp.path().join("stored"));
+        stored
+            .save_settings(settings.clone())
+            .expect("save settings");
+        let reopened = FsVault::open(temp.path().join("stored")).expect("open vault");
+        assert_eq!(
+            reopened.settings(),
+            &settings,
+            "the limit is stored in the vault"
+        );
+        settings.set("max_inbox_size", "").expect("clear max");
+        assert_eq!(settings.max_inbox_size, None);
+    }
+
+    #[test]
+    fn merge_tag_rewrites_every_tagged_entry() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let mut tagged = sample_entry();
+        tagged.tags = vec![Tag::new("js").unwrap(), Tag::new("cli").unwrap()];
+        let mut both = sample_entry();
+        both.id = Uuid::new_v4();
+        both.tags = vec![Tag::new("javascript").unwrap(), Tag::new("js").unwrap()];
+        let mut untagged = sample_entry();
+        untagged.id = Uuid::new_v4();
+        for entry in [&tagged, &both, &untagged] {
+            vault.create(entry).expect("create entry");
+        }
+
+        let (js, javascript) = (Tag::new("js").unwrap(), Tag::new("javascript").unwrap());
+        assert_eq!(vault.merge_tag(&js, &javascript).expect("merge"), 2);
+        let tags = |id| vault.get(id).expect("get").expect("entry").tags;
+        assert_eq!(tags(tagged.id), vec![javascript.clone(), Tag::new("cli").unwrap()]);
+        assert_eq!(tags(both.id), vec![javascript.clone()]);
+        assert_eq!(tags(untagged.id), untagged.tags);
+        assert_eq!(vault.merge_tag(&js, &javascript).expect("merge again"), 0);
+
+        let mut config = VaultConfig::default();
+        config.set("tag_aliases.node", "javascript").expect("set alias");
+        assert_eq!(config.get("tag_aliases").expect("get aliases"), "node=javascript");
+        assert!(config.set("tag_aliases", "node=javascript").is_err());
+        config.set("tag_aliases.node", "").expect("remove alias");
+        assert!(config.tag_aliases.is_empty());
+    }
+
+    #[test]
+    fn create_rejects_existing_id() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        let entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+
+        let mut duplicate = entry.clone();
+        duplicate.title = "yq".into();
+        let err = vault.create(&duplicate).expect_err("duplicate id");
+        assert!(matches!(err, CoreError::Validation(_)));
+        let stored = vault.get(entry.id).expect("get entry").expect("entry exists");
+        assert_eq!(stored.title, "jq");
+        assert!(!vault.entry_path(&duplicate).exists());
+    }
+
+    fn sample_entry() -> Entry {
+        Entry::new(
+            Uuid::new_v4(),
+            "jq",
+            EntryType::Package,
+            "homebrew",
+            "brew install jq",
+            SystemInfo {
+                os: "macos".into(),
+                arch: "arm64".into(),
+                hostname: None,
+            },
+            Utc::now(),
+            EntryStatus::Active,
+            Vec::new(),
+            Rationale::new("json parsing").unwrap(),
+            Some("jq --version".into()),
+        )
+        .unwrap()
+    }
+
+    fn assert_update_moves(change: impl FnOnce(&mut Entry)) {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        let mut entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+        let old_path = vault.entry_path(&entry);
+
+        change(&mut entry);
+        vault.update(&entry).expect("update entry");
+
+        let new_path = vault.entry_path(&entry);
+        assert_ne!(old_path, new_path);
+        assert!(!old_path.exists());
+        assert!(new_path.exists());
+        let listed = vault.list().expect("list");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0], entry);
+    }
+
+    #[test]
+    fn update_moves_file_when_title_changes() {
+        assert_update_moves(|entry| entry.title = "jq (json)".into());
+    }
+
+    #[test]
+    fn update_moves_file_when_type_changes() {
+        assert_update_moves(|entry| entry.entry_type = EntryType::Application);
+    }
+
+    #[test]
+    fn update_moves_file_when_source_changes() {
+        assert_update_moves(|entry| entry.source = "manual".into());
+    }
+
+    #[test]
+    fn expand_path_resolves_home_and_relative_inputs() {
+        let home = dirs::home_dir().expect("home dir");
+        let cwd = std::env::current_dir().expect("current dir");
+        assert_eq!(expand_path("~"), home);
+        assert_eq!(expand_path("~/vault/sub"), home.join("vault/sub"));
+        assert_eq!(
+            expand_path("~other/vault"),
+            home.parent().expect("home parent").join("other/vault")
+        );
+        assert_eq!(expand_path("vault"), cwd.join("vault"));
+        let absolute = cwd.join("abs-vault");
+        assert_eq!(expand_path(&absolute.display().to_string()), absolute);
+    }
+
+    #[test]
+    fn advertised_fields_match_serialized_keys() {
+        fn keys(value: serde_yaml::Value) -> Vec<String> {
+            let serde_yaml::Value::Mapping(map) = value else {
+                panic!("expected a mapping");
+            };
+            map.keys()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect()
+        }
+
+        let mut entry = sample_entry();
+        entry.pinned = true;
+        entry.detector = Some("homebrew".into());
+        entry.last_verified = Some(Utc::now());
+        let rendered = render_entry(&entry).expect("render entry");
+        let (yaml, _) = split_frontmatter(&rendered).expect("split frontmatter");
+        assert_eq!(
+            keys(serde_yaml::from_str(yaml).expect("frontmatter yaml")),
+            FRONTMATTER_FIELDS,
+            "every optional Frontmatter field is set, so each must be listed"
+        );
+
+        let config = VaultConfig {
+            path: Some("/vault".into()),
+            mac_defaults_domains: vec!["com.apple.dock".into()],
+            dotfile_paths: vec!["/home/me/.zshrc".into()],
+            detector_binaries: BTreeMap::from([("brew".into(), "/opt/homebrew/bin/brew".into())]),
+            detector_cache_ttl_secs: 60,
+            ignore_patterns: vec!["lib*".into()],
+            rationale_templates: BTreeMap::from([("cli".into(), "CLI tool".into())]),
+            entry_templates: BTreeMap::from([(
+                "config".into(),
+                EntryTemplate {
+                    tags: vec!["dotfiles".into()],
+                    ..EntryTemplate::default()
+                },
+            )]),
+            tag_aliases: BTreeMap::from([("js".into(), "javascript".into())]),
+            rationale_quality_warnings: false,
+            source_priority: vec!["homebrew".into()],
+            custom_detectors: vec![CustomDetectorSpec {
+                name: "gh-extensions".into(),
+                list_cmd: "gh extension list".into(),
+                install_cmd_template: "gh extension install {name}".into(),
+            }],
+            inbox_order: InboxOrder::SourceGrouped,
+            enabled_detectors: Some(vec!["homebrew".into()]),
+            disabled_detectors: vec!["mas".into()],
+        };
+        let value = serde_yaml::to_value(&config).expect("config yaml");
+        assert_eq!(keys(value), VaultConfig::FIELDS);
+
+        let settings = VaultSettings {
+            layout: VaultLayout::Flat,
+            max_inbox_size: Some(500),
+            inbox_overflow: InboxOverflow::DropOldest,
+        };
+        let value = serde_yaml::to_value(&settings).expect("settings yaml");
+        assert_eq!(keys(value), VaultSettings::FIELDS);
+    }
+
+    #[test]
+    fn verify_integrity_reports_and_quarantines_problems() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+        assert!(vault.verify_integrity().expect("verify").is_ok());
+
+        let copy = vault.entries_root().join("copy.md");
+        fs::copy(vault.entry_path(&entry), &copy).expect("copy entry");
+        let broken = vault.entries_root().join("other").join("broken.md");
+        fs::create_dir_all(broken.parent().unwrap()).expect("create dir");
+        fs::write(&broken, "no frontmatter").expect("write broken entry");
+        fs::create_dir_all(vault.state_root().join("detectors")).expect("create dir");
+        fs::write(vault.detector_snapshot_path("npm"), "{not: [valid").expect("write snapshot");
+
+        let report = vault.verify_integrity().expect("verify");
+        assert_eq!(report.entries_checked, 3);
+        assert_eq!(report.issues.len(), 3, "{:?}", report.issues);
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            IntegrityIssue::DuplicateEntryId { id, .. } if *id == entry.id
+        )));
+
+        let moved = vault.quarantine(&report).expect("quarantine");
+        assert_eq!(moved.len(), 2);
+        assert!(!broken.exists());
+        assert!(vault.state_root().join("quarantine").join("broken.md").exists());
+
+        fs::remove_file(&copy).expect("remove duplicate");
+        assert!(vault.verify_integrity().expect("verify").is_ok());
+    }
+
+    #[test]
+    fn rationale_templates_substitute_title() {
+        let config = VaultConfig {
+            rationale_templates: BTreeMap::from([(
+                "cli".into(),
+                "{title} is a daily CLI tool".into(),
+            )]),
+            ..VaultConfig::default()
+        };
+        let rationale = config.expand_rationale_template("cli", "jq").expect("template");
+        assert_eq!(rationale.as_str(), "jq is a daily CLI tool");
+
+        let err = config.expand_rationale_template("gui", "jq").unwrap_err();
+        assert!(err.to_string().contains("known templates: cli"));
+    }
+
+    #[test]
+    fn in_memory_vault_matches_fs_vault() {
+        let temp = TempDir::new().expect("temp dir");
+        let fs_vault = FsVault::new(temp.path().to_path_buf());
+        let memory_vault = InMemoryVault::new();
+        let vaults: [&dyn VaultRepository; 2] = [&fs_vault, &memory_vault];
+        let mut entry = sample_entry();
+        entry.tags = vec![Tag::new("cli").unwrap()];
+        entry.uninstall_cmd = Some("brew uninstall jq".into());
+
+        for vault in vaults {
+            vault.create(&entry).expect("create entry");
+        }
+        let fetched: Vec<_> = vaults
+            .iter()
+            .map(|vault| vault.get(entry.id).expect("get entry"))
+            .collect();
+        assert_eq!(fetched[0], Some(entry.clone()));
+        assert_eq!(fetched[0], fetched[1]);
+
+        entry.title = "jq (json)".into();
+        for vault in vaults {
+            vault.update(&entry).expect("update entry");
+            assert_eq!(vault.list().expect("list entries"), vec![entry.clone()]);
+            vault.delete(entry.id).expect("delete entry");
+            vault.delete(entry.id).expect("delete missing entry");
+            assert!(vault.get(entry.id).expect("get entry").is_none());
+        }
+    }
+
+    #[test]
+    fn inbox_mutations_report_missing_ids() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let missing = Uuid::new_v4();
+
+        assert!(!vault.snooze_inbox_item(missing).expect("snooze"));
+        assert!(!vault.unsnooze_item(missing).expect("unsnooze"));
+        assert!(!vault.remove_inbox_item(missing).expect("remove"));
+    }
+
+    #[test]
+    fn restore_to_inbox_keeps_the_detector() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let mut entry = sample_entry();
+        entry.detector = Some("homebrew".into());
+        vault.create(&entry).expect("create entry");
+
+        assert!(vault.restore_to_inbox(entry.id).expect("restore"));
+        assert!(vault.get(entry.id).expect("get entry").is_none());
+        let inbox = vault.load_inbox().expect("load inbox");
+        assert_eq!(inbox.len(), 1);
+        assert_eq!(inbox[0].title, "jq");
+        assert_eq!(inbox[0].detector.as_deref(), Some("homebrew"));
+        assert!(!vault.restore_to_inbox(entry.id).expect("restore missing"));
+    }
+
+    #[test]
+    fn restore_to_inbox_keeps_the_entry_when_already_queued() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+        let queued = DetectedChange::builder(Uuid::new_v4(), "jq")
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd("brew install jq")
+            .system(entry.system.clone())
+            .build()
+            .expect("change");
+        vault.add_inbox_item(queued.clone()).expect("queue change");
+
+        assert!(!vault.restore_to_inbox(entry.id).expect("restore"));
+        assert_eq!(vault.get(entry.id).expect("get entry"), Some(entry));
+        assert_eq!(vault.load_inbox().expect("load inbox"), vec![queued]);
+    }
+
+    #[test]
+    fn parse_entry_without_optional_fields() {
+        let contents = "---\nid: 550e8400-e29b-41d4-a716-446655440000\ntitle: jq\ntype: package\nsource: homebrew\ncmd: brew install jq\nsystem:\n  os: macos\n  arch: arm64\ndetected_at: 2023-10-27T10:00:00Z\nstatus: active\ntags: []\n---\n\n# Rationale\njson parsing\n\n# Verification\n";
+        let entry = parse_entry(contents).expect("parse entry");
+        assert_eq!(entry.system.hostname, None);
+        assert!(!entry.pinned);
+        assert!(entry.rationale_history.is_empty());
+
+        let rendered = render_entry(&entry).expect("render entry");
+        assert!(!rendered.contains("hostname"));
+        assert!(!rendered.contains("pinned"));
+        assert!(!rendered.contains("# Uninstall"));
+    }
+
+    #[test]
+    fn repair_rewrites_messy_entry_in_canonical_form() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        let entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+        let path = vault.entry_path(&entry);
+        let canonical = fs::read_to_string(&path).expect("read entry");
+        assert_eq!(vault.repair_entry(entry.id).expect("repair"), Some(false));
+
+        let messy = canonical
+            .replace("# Rationale\n", "\n\n# Verification\n  jq --version  \n\n# Rationale\n   ")
+            .replacen("\n\n# Verification\njq --version\n", "\n", 1);
+        assert_ne!(messy, canonical);
+        fs::write(&path, &messy).expect("write messy entry");
+        assert_eq!(vault.repair_entry(entry.id).expect("repair"), Some(true));
+        assert_eq!(fs::read_to_string(&path).expect("read entry"), canonical);
+        assert_eq!(vault.repair_entry(Uuid::new_v4()).expect("repair"), None);
+    }
+
+    #[test]
+    fn migrate_upgrades_old_entries_once() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let old = vault.entries_root().join("packages").join("jq.md");
+        fs::create_dir_all(old.parent().unwrap()).expect("create dir");
+        fs::write(
+            &old,
+            "---\nid: 550e8400-e29b-41d4-a716-446655440000\ntitle: jq\ntype: package\n\
+             source: homebrew\ncmd: brew install jq\nsystem:\n  os: macos\n  arch: arm64\n\
+             detected_at: 2023-10-27T10:00:00Z\nstatus: active\ntags: []\n---\n\n\
+             # Rationale\njson parsing\n",
+        )
+        .expect("write old entry");
+        let broken = vault.entries_root().join("broken.md");
+        fs::write(&broken, "no frontmatter").expect("write broken entry");
+
+        let report = vault.migrate_entries().expect("migrate");
+        assert_eq!((report.entries_checked, report.migrated), (2, 1));
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].0, broken);
+
+        let id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
+        let entry = vault.get(id).expect("get").expect("migrated entry");
+        let migrated = fs::read_to_string(vault.entry_path(&entry)).expect("read entry");
+        assert!(!old.exists());
+        assert!(migrated.contains("# Rationale\njson parsing\n\n# Verification\n"));
+
+        let again = vault.migrate_entries().expect("migrate again");
+        assert_eq!((again.entries_checked, again.migrated), (2, 0));
+        assert_eq!(fs::read_to_string(vault.entry_path(&entry)).expect("read"), migrated);
+    }
+
+    #[test]
+    fn multi_line_rationale_round_trips() {
+        let mut entry = sample_entry();
+        entry.rationale = Rationale::new(
+            "Parses API responses.\n\nAlso used by the deploy scripts.\n# Note: pinned to 1.6",
+        )
+        .unwrap();
+        entry.notes = Some("# Upgrade log\nBroke after 1.7.".into());
+        let parsed = parse_entry(&render_entry(&entry).expect("render")).expect("parse");
+        assert_eq!(parsed.rationale, entry.rationale);
+        assert_eq!(parsed.notes, entry.notes);
+    }
+
+    #[test]
+    fn uninstall_section_round_trips() {
+        let mut entry = sample_entry();
+        entry.uninstall_cmd = Some("brew uninstall jq".into());
+        let rendered = render_entry(&entry).expect("render entry");
+        assert!(rendered.ends_with("# Verification\njq --version\n\n# Uninstall\nbrew uninstall jq\n"));
+
+        let parsed = parse_entry(&rendered).expect("parse entry");
+        assert_eq!(parsed.uninstall_cmd.as_deref(), Some("brew uninstall jq"));
+        assert_eq!(parsed.verification.as_deref(), Some("jq --version"));
+    }
+
+    #[test]
+    fn notes_section_round_trips_and_stays_optional() {
+        let mut entry = sample_entry();
+        assert!(!render_entry(&entry).expect("render").contains("# Notes"));
+
+        entry.notes = Some("Broke after the macOS 14 upgrade.\n\nReinstalled from source.".into());
+        entry.uninstall_cmd = Some("brew uninstall jq".into());
+        let parsed = parse_entry(&render_entry(&entry).expect("render")).expect("parse");
+        assert_eq!(parsed.notes, entry.notes);
+        assert_eq!(parsed.uninstall_cmd, entry.uninstall_cmd);
+        assert_eq!(parsed.rationale, entry.rationale);
+    }
+
+    #[test]
+    fn legacy_rationale_keeps_its_notes_heading() {
+        let contents = "---\nid: 550e8400-e29b-41d4-a716-446655440000\ntitle: jq\ntype: package\nsource: homebrew\ncmd: brew install jq\nsystem:\n  os: macos\n  arch: arm64\ndetected_at: 2023-10-27T10:00:00Z\nstatus: active\ntags: []\n---\n\n# Rationale\njson parsing\n\n# Notes\nneeded by the deploy scripts\n\n# Verification\njq --version\n";
+        let entry = parse_entry(contents).expect("parse legacy entry");
+        let rationale = "json parsing\n\n# Notes\nneeded by the deploy scripts";
+        assert_eq!(entry.rationale.as_str(), rationale);
+        assert_eq!(entry.notes, None);
+        assert_eq!(entry.verification.as_deref(), Some("jq --version"));
+
+        let parsed = parse_entry(&render_entry(&entry).expect("render")).expect("round trip");
+        assert_eq!(parsed.rationale.as_str(), rationale);
+        assert_eq!(parsed.notes, None);
+    }
+
+    #[test]
+    fn legacy_rationale_keeps_its_rationale_history_heading() {
+        let contents = "---\nid: 550e8400-e29b-41d4-a716-446655440000\ntitle: jq\ntype: package\nsource: homebrew\ncmd: brew install jq\nsystem:\n  os: macos\n  arch: arm64\ndetected_at: 2023-10-27T10:00:00Z\nstatus: active\ntags: []\n---\n\n# Rationale\njson parsing\n\n# Rationale History\n## 2023-11-14T22:13:20Z\nused to parse logs\n\n# Verification\njq --version\n";
+        let entry = parse_entry(contents).expect("parse legacy entry");
+        let rationale =
+            "json parsing\n\n# Rationale History\n## 2023-11-14T22:13:20Z\nused to parse logs";
+        assert_eq!(entry.rationale.as_str(), rationale);
+        assert!(entry.rationale_history.is_empty());
+        assert_eq!(entry.verification.as_deref(), Some("jq --version"));
+
+        let parsed = parse_entry(&render_entry(&entry).expect("render")).expect("round trip");
+        assert_eq!(parsed.rationale.as_str(), rationale);
+        assert!(parsed.rationale_history.is_empty());
+    }
+
+    #[test]
+    fn rationale_history_is_appended_on_revision_and_round_trips() {
+        let mut entry = sample_entry();
+        assert!(!render_entry(&entry).expect("render").contains("# Rationale History"));
+        let first = entry.rationale.clone();
+        let at = |secs| DateTime::from_timestamp(secs, 0).expect("timestamp");
+
+        assert!(!entry.revise_rationale(first.clone(), at(1_700_000_000)));
+        assert!(entry.rationale_history.is_empty());
+        let revised = Rationale::new("jq for API\n\n## debugging").unwrap();
+        assert!(entry.revise_rationale(revised, at(1_700_000_000)));
+        let latest = Rationale::new("yq covers this now").unwrap();
+        assert!(entry.revise_rationale(latest, at(1_800_000_000)));
+        assert_eq!(entry.rationale_history.len(), 2);
+        assert_eq!(entry.rationale_history[0].1, first.as_str());
+
+        entry.notes = Some("Pinned to 1.6".into());
+        let rendered = render_entry(&entry).expect("render");
+        assert!(rendered.contains("# Rationale History\n## 2023-11-14T22:13:20Z\n"));
+        let parsed = parse_entry(&rendered).expect("parse");
+        assert_eq!(parsed, entry);
+        assert_eq!(parsed.rationale.as_str(), "yq covers this now");
+    }
+
+    #[test]
+    fn script_section_round_trips_with_comments_and_fences() {
+        let mut entry = sample_entry();
+        entry.entry_type = EntryType::Script;
+        entry.uninstall_cmd = Some("brew uninstall jq".into());
+        entry.script_body = Some("#!/usr/bin/env bash\n# Setup\necho '```'\n\nbrew install jq".into());
+
+        let parsed = parse_entry(&render_entry(&entry).expect("render")).expect("parse");
+        assert_eq!(parsed.script_body, entry.script_body);
+        assert_eq!(parsed.uninstall_cmd, entry.uninstall_cmd);
+    }
+
+    #[test]
+    fn parse_entry_with_crlf_line_endings() {
+        let contents = "---\nid: 550e8400-e29b-41d4-a716-446655440000\ntitle: jq\ntype: package\nsource: homebrew\ncmd: brew install jq\nsystem:\n  os: windows\n  arch: x86_64\ndetected_at: 2023-10-27T10:00:00Z\nstatus: active\ntags:\n- cli\n---\n\n# Rationale\njson parsing\nin scripts\n\n# Verification\njq --version\n"
+            .replace('\n', "\r\n");
+        let entry = parse_entry(&contents).expect("parse crlf entry");
+        assert_eq!(entry.title, "jq");
+        assert_eq!(entry.tags, vec![Tag::new("cli").unwrap()]);
+        assert_eq!(entry.rationale.as_str(), "json parsing\nin scripts");
+        assert_eq!(entry.verification.as_deref(), Some("jq --version"));
+    }
+
+    #[test]
+    fn config_set_and_get_parse_by_field_type() {
+        let mut config = VaultConfig::default();
+        config.set("detector_cache_ttl_secs", "120").expect("set ttl");
+        config.set("source_priority", "homebrew, npm,,cargo").expect("set list");
+        config
+            .set("rationale_templates.cli", "CLI tool for {title}")
+            .expect("set template");
+        assert_eq!(config.detector_cache_ttl_secs, 120);
+        assert_eq!(config.get("source_priority").unwrap(), "homebrew,npm,cargo");
+        assert_eq!(config.get("rationale_templates.cli").unwrap(), "CLI tool for {title}");
+        assert_eq!(config.get("rationale_templates").unwrap(), "cli=CLI tool for {title}");
+
+        config.set("source_priority", "").expect("clear list");
+        assert!(config.source_priority.is_empty());
+        assert!(config.set("detector_cache_ttl_secs", "soon").is_err());
+        config.set("inbox_order", "alphabetical").expect("set order");
+        assert_eq!(config.inbox_order, InboxOrder::Alphabetical);
+        assert!(config.set("inbox_order", "newest").is_err());
+        config.set("enabled_detectors", "brew, npm").expect("set allow-list");
+        assert_eq!(config.enabled_detectors, Some(vec!["brew".into(), "npm".into()]));
+        config.set("enabled_detectors", "").expect("clear allow-list");
+        assert_eq!(config.enabled_detectors, None);
+        config.set("detector_binaries.brew", "/opt/homebrew/bin/brew").expect("set binary");
+        assert_eq!(config.get("detector_binaries").unwrap(), "brew=/opt/homebrew/bin/brew");
+        assert!(config.set("detector_binaries", "brew=/usr/bin/brew").is_err());
+        config.set("detector_binaries.brew", "").expect("clear binary");
+        assert!(config.detector_binaries.is_empty());
+        assert!(config.rationale_quality_warnings);
+        config.set("rationale_quality_warnings", "false").expect("disable warnings");
+        assert_eq!(config.get("rationale_quality_warnings").unwrap(), "false");
+        assert!(config.set("rationale_quality_warnings", "maybe").is_err());
+        let template = |tag: &str| EntryTemplate {
+            tags: vec![tag.into()],
+            rationale: Some("Keeps {title} around".into()),
+            ..EntryTemplate::default()
+        };
+        config.entry_templates =
+            BTreeMap::from([("config".into(), template("dotfiles")), ("manual".into(), template("misc"))]);
+        let chosen = |source, entry_type| config.entry_template(source, &entry_type).map(|t| &t.tags);
+        assert_eq!(chosen("manual", EntryType::Config), Some(&vec!["misc".to_string()]));
+        assert_eq!(chosen("homebrew", EntryType::Config), Some(&vec!["dotfiles".to_string()]));
+        assert_eq!(chosen("homebrew", EntryType::Package), None);
+        assert_eq!(
+            config.entry_templates["config"].rationale_for("starship").as_deref(),
+            Some("Keeps starship around")
+        );
+        assert_eq!(config.get("entry_templates").unwrap(), "config,manual");
+        assert!(config.set("entry_templates", "config").is_err());
+        config.set("rationale_quality_warnings", "").expect("reset warnings");
+        assert!(config.rationale_quality_warnings);
+        let err = config.get("git_autocommit").unwrap_err().to_string();
+        assert!(err.contains("unknown config key `git_autocommit`"), "{err}");
+    }
 
+    #[test]
+    fn sha256_matches_known_vectors() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn checksum_sidecar_detects_corruption() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().join("vault"));
+        vault.init().expect("init vault");
+        let entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+
+        let path = temp.path().join("jq.md");
+        let contents = render_entry_markdown(&entry).expect("render entry");
+        fs::write(&path, &contents).expect("write entry");
+        fs::write(
+            checksum_sidecar_path(&path),
+            render_checksum_sidecar(contents.as_bytes(), "jq.md"),
+        )
+        .expect("write sidecar");
+        assert_eq!(
+            vault.entry_checksum(entry.id).expect("checksum"),
+            Some(sha256_hex(contents.as_bytes()))
+        );
+        assert_eq!(vault.entry_checksum(Uuid::new_v4()).expect("checksum"), None);
+        assert!(verify_checksum_sidecar(&path).expect("verify matching file"));
+
+        fs::write(&path, contents.replace("jq", "jq-")).expect("corrupt entry");
+        assert!(!verify_checksum_sidecar(&path).expect("verify corrupted file"));
+
+        fs::remove_file(checksum_sidecar_path(&path)).expect("remove sidecar");
+        assert!(verify_checksum_sidecar(&path).is_err());
+    }
+
+    #[test]
+    fn move_to_relocates_vault_and_guards_targets() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().join("vault"))
+            .with_layout(VaultLayout::Flat)
+            .with_inbox_limit(Some(5), InboxOverflow::DropOldest);
+        vault.init().expect("init vault");
+        let entry = sample_entry();
         vault.create(&entry).expect("create entry");
-        let fetched = vault.get(entry.id).expect("get entry");
-        assert!(fetched.is_some());
-        assert_eq!(fetched.unwrap().title, "jq");
+
+        let occupied = temp.path().join("occupied");
+        fs::create_dir_all(&occupied).expect("create occupied");
+        fs::write(occupied.join("notes.txt"), "keep").expect("write file");
+        assert!(vault.move_to(&occupied, false).is_err());
+        assert!(occupied.join("notes.txt").exists());
+        assert!(vault.move_to(&vault.path().join("nested"), false).is_err());
+
+        let target = temp.path().join("new/parent/vault");
+        let moved = vault.move_to(&target, false).expect("move vault");
+        assert!(!vault.path().exists());
+        assert_eq!(moved.path(), target.as_path());
+        assert_eq!(moved.get(entry.id).expect("load entry"), Some(entry.clone()));
+
+        let replaced = moved.move_to(&occupied, true).expect("force move");
+        assert!(!occupied.join("notes.txt").exists());
+        assert_eq!(replaced.get(entry.id).expect("load entry"), Some(entry));
+        assert_eq!(replaced.layout(), VaultLayout::Flat);
+        assert_eq!(replaced.settings(), vault.settings());
+    }
+
+    #[test]
+    fn force_move_refuses_a_target_containing_the_vault() {
+        let temp = TempDir::new().expect("temp dir");
+        let parent = temp.path().join("parent");
+        let vault = FsVault::new(parent.join("vault"));
+        vault.init().expect("init vault");
+        let entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+        fs::write(parent.join("other-file.txt"), "keep").expect("write file");
+
+        assert!(vault.move_to(&parent, true).is_err());
+        assert!(vault.move_to(&parent.join("../parent/vault"), true).is_err());
+        assert!(vault.move_to(&parent.join("missing/../vault"), true).is_err());
+        assert_eq!(
+            canonicalize_existing_prefix(&parent.join("vault/new/./../../vault/new")),
+            parent.canonicalize().expect("canonical parent").join("vault/new")
+        );
+        assert!(parent.join("other-file.txt").exists());
+        assert_eq!(vault.get(entry.id).expect("load entry"), Some(entry));
+    }
+
+    #[test]
+    fn record_access_tracks_latest_time_per_entry() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        assert!(vault.load_access_times().expect("empty log").is_empty());
+
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        vault.record_access(first).expect("record first");
+        let before = vault.load_access_times().expect("load log")[&first];
+        vault.record_access(second).expect("record second");
+        vault.record_access(first).expect("record first again");
+
+        let times = vault.load_access_times().expect("load log");
+        assert_eq!(times.len(), 2);
+        assert!(times[&first] >= before);
+        assert!(times[&first] >= times[&second]);
+    }
+
+    #[test]
+    fn add_inbox_item_skips_same_source_and_title() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let change = || {
+            DetectedChange::builder(Uuid::new_v4(), "jq")
+                .entry_type(EntryType::Package)
+                .source("homebrew")
+                .cmd("brew install jq")
+                .system(sample_entry().system)
+                .build()
+                .expect("change")
+        };
+
+        let first = change();
+        assert!(vault.add_inbox_item(first.clone()).expect("first add"));
+        assert!(!vault.add_inbox_item(change()).expect("second add"));
+        assert_eq!(vault.load_inbox().expect("load inbox"), vec![first]);
+
+        vault.force_add_inbox_item(change()).expect("forced add");
+        assert_eq!(vault.load_inbox().expect("load inbox").len(), 2);
+    }
+
+    #[test]
+    fn stats_aggregate_a_seeded_vault() {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        assert_eq!(vault.stats().expect("empty stats"), VaultStats::default());
+
+        let now = DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z").unwrap().to_utc();
+        let seed = [
+            ("jq", EntryType::Package, "homebrew", now - chrono::Duration::days(3)),
+            ("fd", EntryType::Package, "homebrew", now),
+            (".zshrc", EntryType::Config, "dotfiles", now - chrono::Duration::days(1)),
+        ];
+        for (title, entry_type, source, detected_at) in seed {
+            let mut entry = sample_entry();
+            entry.id = Uuid::new_v4();
+            entry.title = title.into();
+            entry.entry_type = entry_type;
+            entry.source = source.into();
+            entry.detected_at = detected_at;
+            vault.create(&entry).expect("create entry");
+        }
+        let change = DetectedChange::builder(Uuid::new_v4(), "rg")
+            .entry_type(EntryType::Package)
+            .source("cargo")
+            .cmd("cargo install ripgrep")
+            .system(sample_entry().system)
+            .build()
+            .expect("change");
+        vault.save_inbox(std::slice::from_ref(&change)).expect("save inbox");
+        vault.save_snoozed(&[change.clone(), change]).expect("save snoozed");
+
+        let stats = vault.stats().expect("stats");
+        assert_eq!((stats.total_entries, stats.inbox, stats.snoozed), (3, 1, 2));
+        assert_eq!(
+            stats.by_type,
+            BTreeMap::from([(EntryType::Package, 2), (EntryType::Config, 1)])
+        );
+        assert_eq!(stats.top_sources(), [("homebrew", 2), ("dotfiles", 1)]);
+        assert_eq!(stats.oldest_detected_at, Some(now - chrono::Duration::days(3)));
+        assert_eq!(stats.newest_detected_at, Some(now));
+    }
+
+    #[test]
+    fn strict_parse_rejects_unknown_frontmatter_fields() {
+        let rendered = render_entry(&sample_entry()).expect("render entry");
+        assert!(parse_entry_strict(&rendered).is_ok());
+
+        let drifted = rendered.replacen("---\n", "---\nfoo: bar\n", 1);
+        assert!(parse_entry_markdown(&drifted).is_ok());
+        let err = parse_entry_strict(&drifted).expect_err("strict parse");
+        assert!(err.to_string().contains("foo"), "{err}");
+
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        let entry = sample_entry();
+        vault.create(&entry).expect("create entry");
+        let path = vault.entry_path(&entry);
+        let contents = fs::read_to_string(&path).expect("read entry");
+        fs::write(&path, contents.replacen("---\n", "---\nfoo: bar\n", 1)).expect("write");
+
+        assert!(vault.verify_integrity().expect("verify").is_ok());
+        let report = vault.verify_integrity_strict().expect("verify strict");
+        assert_eq!(
+            report.issues,
+            [IntegrityIssue::UnknownFrontmatterFields { path, fields: vec!["foo".into()] }]
+        );
     }
 }
@@ -2,24 +2,43 @@ use anyhow::{Context, Result};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use crossterm::execute;
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
+use ratatui::widgets::block::Title;
 use ratatui::widgets::{
     BarChart, Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap,
 };
 use ratatui::{Frame, Terminal};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::io::{self, Stdout};
 use std::time::{Duration, Instant};
 
-use sv_core::{DetectedChange, Entry, EntryStatus, EntryType, Rationale, SystemInfo, VaultRepository};
-use sv_core::Tag;
-use sv_detectors::{default_detectors, run_detectors};
-use sv_fs::{resolve_vault_path, set_config_path, FsVault};
+use sv_core::{
+    DetectedChange, Entry, EntryStatus, EntryTemplate, EntryType, InboxOrder, Rationale,
+    SystemInfo, VaultRepository,
+};
+use sv_core::{canonical_tags, Tag};
+use sv_detectors::{
+    check_presence, configured_detectors, dedupe_by_source_priority, derive_uninstall_cmd,
+    diff_changes, scan_detectors_cached, IgnorePatterns, PresenceCheck,
+};
+use sv_fs::{
+    expand_path, load_config, resolve_vault_path, set_config_path, FsVault, VaultChange,
+    VaultConfig, VaultStats, VaultWatcher,
+};
 
 const TICK_RATE: Duration = Duration::from_millis(200);
 
+/// Status shown instead of scanning when the session runs with `--no-detectors`.
+const DETECTORS_OFF_STATUS: &str = "Detectors are off for this session (--no-detectors)";
+
+/// Status messages kept for the `M` history popup.
+const STATUS_HISTORY_LIMIT: usize = 20;
+
+/// Entries shown in the dashboard's Recent Activity list by default.
+const RECENT_ACTIVITY_LIMIT: usize = 5;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Tab {
     Dashboard,
@@ -35,10 +54,23 @@ enum Focus {
     Detail,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InboxSort {
+    Detected,
+    OldestFirst,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LibrarySort {
+    Stored,
+    RecentlyUsed,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum InputMode {
     None,
     Rationale,
+    Notes,
     Palette,
     Init,
     Filter,
@@ -46,6 +78,8 @@ enum InputMode {
     SettingsPath,
     Confirm,
     ManualCapture,
+    Jump,
+    GotoId,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -54,22 +88,109 @@ enum ConfirmAction {
     SwitchVault,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusLevel {
+    Info,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StatusMessage {
+    text: String,
+    level: StatusLevel,
+}
+
+/// An action waiting for `y` in the confirmation popup.
 #[derive(Debug, Clone)]
-struct PendingConfirm {
-    action: ConfirmAction,
-    target: std::path::PathBuf,
+enum PendingConfirm {
+    /// Move or switch the vault to a checked path.
+    Vault {
+        action: ConfirmAction,
+        target: std::path::PathBuf,
+        will_create: bool,
+    },
+    /// Remove every inbox item; `count` is shown in the prompt.
+    ClearInbox { count: usize },
+}
+
+/// Result of checking a vault path typed into the TUI before acting on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathCheck {
+    /// The directory exists and is writable.
+    Exists,
+    /// The directory is missing and its nearest existing ancestor is writable.
+    WillCreate,
+    /// The path, or one of its ancestors, is a file.
+    NotDirectory(std::path::PathBuf),
+    /// The directory, or its nearest existing ancestor, cannot be written to.
+    NotWritable(std::path::PathBuf),
+}
+
+impl PathCheck {
+    fn is_usable(&self) -> bool {
+        matches!(self, PathCheck::Exists | PathCheck::WillCreate)
+    }
+
+    /// Status line for `path`; `None` when it exists and nothing needs saying.
+    fn message(&self, path: &std::path::Path) -> Option<String> {
+        match self {
+            PathCheck::Exists => None,
+            PathCheck::WillCreate => Some(format!("Will create {}", path.display())),
+            PathCheck::NotDirectory(file) => {
+                Some(format!("Not a directory: {} is a file", file.display()))
+            }
+            PathCheck::NotWritable(dir) => Some(if dir == path {
+                format!("Directory not writable: {}", dir.display())
+            } else {
+                format!("Parent not writable: {}", dir.display())
+            }),
+        }
+    }
+}
+
+/// Check that `path` can hold a vault: it must be a writable directory, or be creatable
+/// under its nearest existing ancestor.
+fn check_vault_path(path: &std::path::Path) -> PathCheck {
+    let Some(ancestor) = path.ancestors().find(|dir| dir.exists()) else {
+        return PathCheck::NotWritable(path.to_path_buf());
+    };
+    if !ancestor.is_dir() {
+        return PathCheck::NotDirectory(ancestor.to_path_buf());
+    }
+    // Permission bits do not tell the whole story (ACLs, read-only mounts), so probe.
+    let probe = ancestor.join(format!(".setupvault-probe-{}", uuid::Uuid::new_v4()));
+    if std::fs::File::create(&probe).is_err() {
+        return PathCheck::NotWritable(ancestor.to_path_buf());
+    }
+    let _ = std::fs::remove_file(&probe);
+    if ancestor == path {
+        PathCheck::Exists
+    } else {
+        PathCheck::WillCreate
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CaptureStep {
     Title,
+    EntryType,
     Rationale,
     Command,
     Tags,
-    EntryType,
     Verification,
 }
 
+/// Detected state compared with the library on `(source, title)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DriftReport {
+    /// Detected `(source, title)` pairs with no library entry.
+    untracked: Vec<(String, String)>,
+    /// Library entries from scanned sources that detection no longer reports.
+    missing: Vec<(String, String)>,
+    /// Library entries that were detected.
+    tracked: usize,
+}
+
 #[derive(Debug, Clone)]
 struct ManualCapture {
     step: CaptureStep,
@@ -79,6 +200,20 @@ struct ManualCapture {
     tags: Vec<String>,
     entry_type: EntryType,
     verification: Option<String>,
+    /// Defaults for the `manual` source or the chosen type, pre-filled into later steps.
+    template: EntryTemplate,
+}
+
+impl ManualCapture {
+    /// Text the current step's input starts with, taken from the entry template.
+    fn step_default(&self) -> String {
+        match self.step {
+            CaptureStep::Rationale => self.template.rationale_for(&self.title).unwrap_or_default(),
+            CaptureStep::Tags => self.template.tags.join(", "),
+            CaptureStep::Verification => self.template.verification.clone().unwrap_or_default(),
+            CaptureStep::Title | CaptureStep::EntryType | CaptureStep::Command => String::new(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -94,7 +229,13 @@ struct App {
     selected_library: HashSet<uuid::Uuid>,
     input_mode: InputMode,
     input: TextInput,
+    /// Latest informational message; cleared on the next key press.
     status: Option<String>,
+    /// Recent messages, oldest first, capped at `STATUS_HISTORY_LIMIT`.
+    status_history: VecDeque<StatusMessage>,
+    /// Error or warning shown in red until dismissed with Esc.
+    error: Option<String>,
+    show_messages: bool,
     show_help: bool,
     palette_input: TextInput,
     palette_state: ListState,
@@ -102,15 +243,42 @@ struct App {
     filter_input: TextInput,
     active_filter: Option<String>,
     inbox_source_index: usize,
+    inbox_sort: InboxSort,
+    inbox_order: InboxOrder,
+    /// Mark library entries with short or reused rationales (`rationale_quality_warnings`).
+    rationale_quality_warnings: bool,
+    /// Library entries marked as having a weak rationale, recomputed when the library changes.
+    weak_rationales: HashSet<uuid::Uuid>,
+    /// Render source labels without icons (set when `NO_COLOR` is present).
+    plain_labels: bool,
+    /// Skip every detector scan this session (`--no-detectors`).
+    no_detectors: bool,
     snoozed: Vec<DetectedChange>,
     snoozed_state: ListState,
 
     selected_snoozed: HashSet<uuid::Uuid>,
     library_source_index: usize,
+    /// Entry type the library is narrowed to, cycled with `t`.
+    library_type_filter: Option<EntryType>,
+    library_sort: LibrarySort,
+    /// Show only the focused pane at full width in the list/detail tabs.
+    zoomed: bool,
+    /// Narrow the current list to its checkbox-selected items, toggled with `S`.
+    show_selected_only: bool,
+    /// Last drift report, kept until the next refresh.
+    drift: Option<DriftReport>,
+    show_drift: bool,
+    /// Number of entries in the dashboard's Recent Activity list.
+    recent_limit: usize,
+    /// Last time each entry was viewed or acted on, from `.state/access.yaml`.
+    access_times: BTreeMap<uuid::Uuid, chrono::DateTime<chrono::Utc>>,
     current_vault_path: String,
     settings_path: String,
     pending_confirm: Option<PendingConfirm>,
     manual_capture: Option<ManualCapture>,
+    /// `config.yaml` as loaded at startup; captures and approvals read its entry templates and
+    /// tag aliases.
+    config: VaultConfig,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -129,27 +297,51 @@ impl TextInput {
     fn insert(&mut self, c: char) {
         if self.cursor <= self.content.len() {
             self.content.insert(self.cursor, c);
-            self.cursor += 1;
+            self.cursor += c.len_utf8();
         }
     }
 
     fn delete_back(&mut self) {
-        if self.cursor > 0 && self.cursor <= self.content.len() {
-            self.content.remove(self.cursor - 1);
-            self.cursor -= 1;
+        if let Some(previous) = self.previous_boundary() {
+            self.content.remove(previous);
+            self.cursor = previous;
         }
     }
 
     fn move_left(&mut self) {
-        if self.cursor > 0 {
-            self.cursor -= 1;
+        if let Some(previous) = self.previous_boundary() {
+            self.cursor = previous;
         }
     }
 
     fn move_right(&mut self) {
-        if self.cursor < self.content.len() {
-            self.cursor += 1;
+        if let Some(c) = self.content.get(self.cursor..).and_then(|rest| rest.chars().next()) {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    /// Byte offset of the character before the cursor.
+    fn previous_boundary(&self) -> Option<usize> {
+        let before = self.content.get(..self.cursor)?;
+        before.char_indices().next_back().map(|(index, _)| index)
+    }
+
+    /// Cursor column and row when the content is word-wrapped to `width` columns.
+    fn wrapped_cursor(&self, width: usize) -> (usize, usize) {
+        let mut row = 0;
+        let mut line_start = 0;
+        for line in self.content.split('\n') {
+            let line_end = line_start + line.len();
+            if self.cursor <= line_end {
+                let column = line[..self.cursor - line_start].chars().count();
+                let starts = wrap_row_starts(line, width);
+                let within = starts.iter().rposition(|&start| start <= column).unwrap_or(0);
+                return (column - starts[within], row + within);
+            }
+            row += wrap_row_starts(line, width).len();
+            line_start = line_end + 1;
         }
+        (0, row)
     }
 
     fn move_home(&mut self) {
@@ -186,6 +378,9 @@ impl App {
             input_mode: InputMode::None,
             input: TextInput::default(),
             status: None,
+            status_history: VecDeque::new(),
+            error: None,
+            show_messages: false,
             show_help: false,
             palette_input: TextInput::default(),
             palette_state: ListState::default(),
@@ -193,17 +388,45 @@ impl App {
             filter_input: TextInput::default(),
             active_filter: None,
             inbox_source_index: 0,
+            inbox_sort: InboxSort::Detected,
+            inbox_order: InboxOrder::default(),
+            rationale_quality_warnings: true,
+            weak_rationales: HashSet::new(),
+            plain_labels: false,
+            no_detectors: false,
             snoozed: Vec::new(),
             snoozed_state,
             selected_snoozed: HashSet::new(),
             library_source_index: 0,
+            library_type_filter: None,
+            library_sort: LibrarySort::Stored,
+            zoomed: false,
+            show_selected_only: false,
+            drift: None,
+            show_drift: false,
+            recent_limit: RECENT_ACTIVITY_LIMIT,
+            access_times: BTreeMap::new(),
             current_vault_path: String::new(),
             settings_path: String::new(),
             pending_confirm: None,
             manual_capture: None,
+            config: VaultConfig::default(),
         }
     }
 
+    fn record_status(&mut self, text: String, level: StatusLevel) {
+        if self.status_history.len() == STATUS_HISTORY_LIMIT {
+            self.status_history.pop_front();
+        }
+        self.status_history.push_back(StatusMessage { text, level });
+    }
+
+    /// Show `text` in red until dismissed, and keep it in the history.
+    fn report_error(&mut self, text: String) {
+        self.record_status(text.clone(), StatusLevel::Error);
+        self.error = Some(text);
+    }
+
     fn available_sources(&self) -> Vec<String> {
         let mut sources: Vec<String> = self.inbox
             .iter()
@@ -217,6 +440,14 @@ impl App {
         all
     }
 
+    /// Source of the selected inbox source tab, or `None` on the "All" tab.
+    fn current_inbox_source(&self) -> Option<String> {
+        self.available_sources()
+            .into_iter()
+            .nth(self.inbox_source_index)
+            .filter(|source| source != "All")
+    }
+
     fn next_source(&mut self) {
         let count = self.available_sources().len();
         if count > 0 {
@@ -270,6 +501,36 @@ impl App {
         }
     }
 
+    /// Advance the library type filter through every type and back to all types.
+    fn cycle_library_type(&mut self) {
+        let next = match &self.library_type_filter {
+            None => 0,
+            Some(current) => {
+                EntryType::ALL.iter().position(|entry_type| entry_type == current).unwrap_or(0) + 1
+            }
+        };
+        self.library_type_filter = EntryType::ALL.get(next).cloned();
+        self.library_state.select(Some(0));
+    }
+
+    /// Checkbox-selected ids of the current tab's list, if it has one.
+    fn current_selection(&self) -> Option<&HashSet<uuid::Uuid>> {
+        match self.tab {
+            Tab::Inbox => Some(&self.selected_inbox),
+            Tab::Library => Some(&self.selected_library),
+            Tab::Snoozed => Some(&self.selected_snoozed),
+            Tab::Dashboard | Tab::Settings => None,
+        }
+    }
+
+    /// Ids a list is narrowed to by `S`, or `None` when it shows every item.
+    fn selected_only<'a>(
+        &self,
+        selected: &'a HashSet<uuid::Uuid>,
+    ) -> Option<&'a HashSet<uuid::Uuid>> {
+        (self.show_selected_only && !selected.is_empty()).then_some(selected)
+    }
+
     fn filtered_inbox(&self) -> Vec<&DetectedChange> {
         let sources = self.available_sources();
         let current_source = if self.inbox_source_index < sources.len() {
@@ -278,21 +539,40 @@ impl App {
              "All"
         };
 
+        let selected_only = self.selected_only(&self.selected_inbox);
         let source_filtered = self.inbox.iter().filter(|item| {
-            current_source == "All" || &item.source == current_source
+            (current_source == "All" || item.source == current_source)
+                && selected_only.is_none_or(|ids| ids.contains(&item.id))
         });
 
-        if let Some(query) = &self.active_filter {
-            let query = query.to_lowercase();
-            source_filtered
-                .filter(|item| {
-                     item.title.to_lowercase().contains(&query)
-                        || item.cmd.to_lowercase().contains(&query)
-                })
-                .collect()
+        let mut items: Vec<&DetectedChange> = if let Some(query) = &self.active_filter {
+            source_filtered.filter(|item| item.matches(query)).collect()
         } else {
             source_filtered.collect()
+        };
+        self.inbox_order.sort(&mut items);
+        if self.inbox_sort == InboxSort::OldestFirst {
+            items.sort_by_key(|item| item.detected_at);
+        }
+        items
+    }
+
+    /// Recompute the library entries whose rationale looks weak, counting reuse over the whole
+    /// library.
+    fn refresh_weak_rationales(&mut self) {
+        if !self.rationale_quality_warnings {
+            self.weak_rationales.clear();
+            return;
         }
+        let rationales: Vec<_> =
+            self.library.iter().map(|entry| entry.rationale.as_str()).collect();
+        self.weak_rationales = self
+            .library
+            .iter()
+            .zip(sv_utils::rationale_warnings(&rationales))
+            .filter(|(_, warning)| warning.is_some())
+            .map(|(entry, _)| entry.id)
+            .collect();
     }
 
     fn filtered_library(&self) -> Vec<&Entry> {
@@ -303,35 +583,40 @@ impl App {
              "All"
         };
 
+        let selected_only = self.selected_only(&self.selected_library);
         let source_filtered = self.library.iter().filter(|item| {
-            current_source == "All" || &item.source == current_source
+            (current_source == "All" || item.source == current_source)
+                && selected_only.is_none_or(|ids| ids.contains(&item.id))
+                && self
+                    .library_type_filter
+                    .as_ref()
+                    .is_none_or(|entry_type| item.entry_type == *entry_type)
         });
 
-        if let Some(query) = &self.active_filter {
-            let query = query.to_lowercase();
-            source_filtered
-                .filter(|entry| {
-                     entry.title.to_lowercase().contains(&query)
-                        || entry.cmd.to_lowercase().contains(&query)
-                })
-                .collect()
+        let mut entries: Vec<&Entry> = if let Some(query) = &self.active_filter {
+            source_filtered.filter(|entry| entry.matches(query)).collect()
         } else {
             source_filtered.collect()
+        };
+        if self.library_sort == LibrarySort::RecentlyUsed {
+            // `None` sorts below every time, so reversing puts never-used entries last.
+            entries.sort_by_key(|entry| std::cmp::Reverse(self.access_times.get(&entry.id)));
         }
+        // Stable sort keeps the existing order within the pinned and unpinned groups.
+        entries.sort_by_key(|entry| !entry.pinned);
+        entries
     }
 
     fn filtered_snoozed(&self) -> Vec<&DetectedChange> {
+        let selected_only = self.selected_only(&self.selected_snoozed);
+        let snoozed = self
+            .snoozed
+            .iter()
+            .filter(|item| selected_only.is_none_or(|ids| ids.contains(&item.id)));
         if let Some(query) = &self.active_filter {
-            let query = query.to_lowercase();
-            self.snoozed
-                .iter()
-                .filter(|item| {
-                     item.title.to_lowercase().contains(&query)
-                        || item.cmd.to_lowercase().contains(&query)
-                })
-                .collect()
+            snoozed.filter(|item| item.matches(query)).collect()
         } else {
-            self.snoozed.iter().collect()
+            snoozed.collect()
         }
     }
 
@@ -411,15 +696,25 @@ impl App {
     }
 }
 
-pub fn run() -> Result<()> {
+/// Run the TUI until the user quits; `no_detectors` disables scanning for the session.
+pub fn run(no_detectors: bool) -> Result<()> {
+    // Load settings before taking over the terminal so a failure prints normally.
+    let config = load_config().context("failed to load config")?;
+    let mut vault =
+        FsVault::open(resolve_vault_path()?).context("failed to load vault settings")?;
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
     let backend = ratatui::backend::CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut vault = FsVault::new(resolve_vault_path()?);
     let mut app = App::new();
+    app.no_detectors = no_detectors;
+    app.plain_labels = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    app.inbox_order = config.inbox_order;
+    app.rationale_quality_warnings = config.rationale_quality_warnings;
+    app.config = config;
 
     if !vault.exists() {
         app.input_mode = InputMode::Init;
@@ -429,8 +724,23 @@ pub fn run() -> Result<()> {
     }
 
     let mut last_tick = Instant::now();
+    let mut watched_path: Option<std::path::PathBuf> = None;
+    let mut watcher: Option<VaultWatcher> = None;
 
     loop {
+        // Watch the vault once it exists, and again after Settings switches or moves it.
+        if vault.exists() && watched_path.as_deref() != Some(vault.path()) {
+            watched_path = Some(vault.path().to_path_buf());
+            watcher = vault.watch().ok();
+        }
+        // Leave popups alone; changes stay queued until the popup closes.
+        if let (Some(watcher), InputMode::None) = (&mut watcher, &app.input_mode) {
+            let changes = watcher.poll();
+            if !changes.is_empty() {
+                reload_external_changes(&vault, &mut app, &changes);
+            }
+        }
+
         terminal.draw(|frame| render_app(frame, &app))?;
 
         let timeout = TICK_RATE.saturating_sub(last_tick.elapsed());
@@ -455,6 +765,8 @@ fn load_data(vault: &FsVault, app: &mut App) -> Result<()> {
     app.inbox = vault.load_inbox().unwrap_or_default();
     app.snoozed = vault.load_snoozed().unwrap_or_default();
     app.library = vault.list().unwrap_or_default();
+    app.refresh_weak_rationales();
+    app.access_times = vault.load_access_times().unwrap_or_default();
     let current_path = vault.path().to_string_lossy().to_string();
     app.current_vault_path = current_path.clone();
     if app.settings_path.is_empty() || app.settings_path == app.current_vault_path {
@@ -472,11 +784,121 @@ fn load_data(vault: &FsVault, app: &mut App) -> Result<()> {
     Ok(())
 }
 
+/// Reload the parts of the vault another process changed, such as an entry edited in a text
+/// editor. Parts that already match what the app shows are left alone, so the app's own
+/// writes do not disturb the selection or the status line.
+fn reload_external_changes(vault: &FsVault, app: &mut App, changes: &BTreeSet<VaultChange>) {
+    let selected_entry = current_library_id(app);
+    let mut reloaded = Vec::new();
+    for change in changes {
+        match change {
+            VaultChange::Entries => {
+                // A half-written entry seen mid-save must not blank the library.
+                let library = match vault.list() {
+                    Ok(library) => library,
+                    Err(err) => {
+                        app.report_error(format!("Failed to reload library: {err}"));
+                        continue;
+                    }
+                };
+                let by_id = |entries: &[Entry]| -> BTreeMap<uuid::Uuid, Entry> {
+                    entries.iter().map(|entry| (entry.id, entry.clone())).collect()
+                };
+                if by_id(&library) != by_id(&app.library) {
+                    app.library = library;
+                    app.refresh_weak_rationales();
+                    reloaded.push("library");
+                }
+            }
+            VaultChange::Inbox => {
+                let inbox = match vault.load_inbox() {
+                    Ok(inbox) => inbox,
+                    Err(err) => {
+                        app.report_error(format!("Failed to reload inbox: {err}"));
+                        continue;
+                    }
+                };
+                if inbox != app.inbox {
+                    app.inbox = inbox;
+                    reloaded.push("inbox");
+                }
+            }
+            VaultChange::Snoozed => {
+                let snoozed = match vault.load_snoozed() {
+                    Ok(snoozed) => snoozed,
+                    Err(err) => {
+                        app.report_error(format!("Failed to reload snoozed items: {err}"));
+                        continue;
+                    }
+                };
+                if snoozed != app.snoozed {
+                    app.snoozed = snoozed;
+                    reloaded.push("snoozed");
+                }
+            }
+        }
+    }
+    if reloaded.is_empty() {
+        return;
+    }
+
+    let App { inbox, library, snoozed, .. } = &*app;
+    let inbox_ids: HashSet<_> = inbox.iter().map(|item| item.id).collect();
+    let library_ids: HashSet<_> = library.iter().map(|entry| entry.id).collect();
+    let snoozed_ids: HashSet<_> = snoozed.iter().map(|item| item.id).collect();
+    app.selected_inbox.retain(|id| inbox_ids.contains(id));
+    app.selected_library.retain(|id| library_ids.contains(id));
+    app.selected_snoozed.retain(|id| snoozed_ids.contains(id));
+    let clamp = |state: &mut ListState, len: usize| {
+        state.select((len > 0).then(|| state.selected().unwrap_or(0).min(len - 1)));
+    };
+    let (inbox_len, library_len) = (app.filtered_inbox().len(), app.filtered_library().len());
+    let snoozed_len = app.filtered_snoozed().len();
+    clamp(&mut app.inbox_state, inbox_len);
+    clamp(&mut app.library_state, library_len);
+    clamp(&mut app.snoozed_state, snoozed_len);
+    // Entry files can be listed in a different order after a rename; keep the same entry.
+    let position = selected_entry
+        .and_then(|id| app.filtered_library().iter().position(|entry| entry.id == id));
+    if let Some(position) = position {
+        app.library_state.select(Some(position));
+    }
+
+    let text = format!("Reloaded {} after an external change", reloaded.join(" and "));
+    app.status = Some(text.clone());
+    app.record_status(text, StatusLevel::Info);
+}
+
 fn handle_key(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool> {
+    if app.error.is_some() && key.code == KeyCode::Esc {
+        app.error = None;
+        // In a popup, Esc also closes it; otherwise it only dismisses the error.
+        if matches!(app.input_mode, InputMode::None) {
+            return Ok(false);
+        }
+    }
+    // Statuses last one key press; the history keeps them for the `M` popup.
+    app.status = None;
+    // A failed action is reported instead of tearing down the terminal.
+    let quit = dispatch_key(vault, app, key).unwrap_or_else(|err| {
+        app.report_error(format!("{err:#}"));
+        false
+    });
+    if let Some(text) = app.status.clone() {
+        app.record_status(text, StatusLevel::Info);
+    }
+    // Once the selection is acted on (or the tab has none), show the whole list again.
+    if app.show_selected_only && app.current_selection().is_none_or(HashSet::is_empty) {
+        app.show_selected_only = false;
+    }
+    Ok(quit)
+}
+
+fn dispatch_key(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool> {
     if matches!(app.input_mode, InputMode::Init) {
         return handle_init_input(vault, app, key);
     }
-    if matches!(app.input_mode, InputMode::Rationale) {
+    if matches!(app.input_mode, InputMode::Rationale | InputMode::Notes) {
         return handle_rationale_input(vault, app, key);
     }
     if matches!(app.input_mode, InputMode::Palette) {
@@ -488,6 +910,9 @@ fn handle_key(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool>
     if matches!(app.input_mode, InputMode::SnoozeQuery) {
         return handle_snooze_query(vault, app, key);
     }
+    if matches!(app.input_mode, InputMode::GotoId) {
+        return handle_goto_id_input(app, key);
+    }
     if matches!(app.input_mode, InputMode::SettingsPath) {
         return handle_settings_path_input(app, key);
     }
@@ -497,6 +922,28 @@ fn handle_key(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool>
     if matches!(app.input_mode, InputMode::ManualCapture) {
         return handle_manual_capture_input(vault, app, key);
     }
+    if matches!(app.input_mode, InputMode::Jump) {
+        app.input_mode = InputMode::None;
+        app.status = None;
+        if let KeyCode::Char(letter) = key.code {
+            jump_to_letter(app, letter);
+        }
+        return Ok(false);
+    }
+
+    if app.show_drift {
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('D') | KeyCode::Char('q')) {
+            app.show_drift = false;
+        }
+        return Ok(false);
+    }
+
+    if app.show_messages {
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('M') | KeyCode::Char('q')) {
+            app.show_messages = false;
+        }
+        return Ok(false);
+    }
 
     if key.modifiers.contains(KeyModifiers::CONTROL) {
         match key.code {
@@ -512,6 +959,7 @@ fn handle_key(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool>
         }
     }
 
+    let previous_focus = app.focus;
     match key.code {
         KeyCode::Char('q') => return Ok(true),
         KeyCode::Char('?') => {
@@ -566,6 +1014,8 @@ fn handle_key(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool>
         KeyCode::End | KeyCode::Char('G') => handle_list_move(app, Move::Last),
         KeyCode::Char('d') => handle_ignore(vault, app)?,
         KeyCode::Char('s') => handle_snooze(vault, app)?,
+        KeyCode::Char('Z') => handle_snooze_source(vault, app)?,
+        KeyCode::Char('X') if app.tab == Tab::Inbox => confirm_clear_inbox(app),
         KeyCode::Char('u') => handle_unsnooze(vault, app)?,
         KeyCode::Char('a') => {
             if app.tab == Tab::Settings {
@@ -581,54 +1031,77 @@ fn handle_key(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool>
                 handle_edit_rationale(app);
             }
         }
-        KeyCode::Char('m') => {
-            if app.tab == Tab::Settings {
-                confirm_settings_change(app, ConfirmAction::MoveVault);
-            }
+        KeyCode::Char('m') if app.tab == Tab::Settings => {
+            confirm_settings_change(app, ConfirmAction::MoveVault);
         }
         KeyCode::Char('r') => handle_refresh(vault, app)?,
         KeyCode::Char('c') => open_manual_capture(app),
         KeyCode::Char('x') => handle_remove(vault, app)?,
+        KeyCode::Char('f') => handle_toggle_pin(vault, app)?,
+        KeyCode::Char('R') => handle_repair(vault, app)?,
+        KeyCode::Char('t') if app.tab == Tab::Library => app.cycle_library_type(),
+        KeyCode::Char('n') if app.tab == Tab::Library => handle_edit_notes(app),
+        KeyCode::Char('i') if app.tab == Tab::Library => handle_check_installed(app)?,
+        KeyCode::Char('O') if app.tab == Tab::Library => handle_open_entry(vault, app)?,
+        KeyCode::Char('z') if matches!(app.tab, Tab::Inbox | Tab::Library | Tab::Snoozed) => {
+            app.zoomed = !app.zoomed;
+            app.status = Some(
+                if app.zoomed { "Zoomed focused pane" } else { "Split view restored" }.into(),
+            );
+        }
+        KeyCode::Char('S') => toggle_selected_only(app),
+        KeyCode::Char('M') => app.show_messages = true,
+        KeyCode::Char('D') if app.tab == Tab::Dashboard => open_drift_report(vault, app)?,
+        KeyCode::Char('o') if app.tab == Tab::Library => toggle_library_sort(app),
+        KeyCode::Char('o') => toggle_inbox_sort(app),
+        KeyCode::Char('\'') if matches!(app.tab, Tab::Inbox | Tab::Library | Tab::Snoozed) => {
+            app.input_mode = InputMode::Jump;
+            app.status = Some("Jump: press a letter".into());
+        }
         KeyCode::Char(' ') => toggle_selection(app),
         KeyCode::Tab if app.tab != Tab::Dashboard && app.tab != Tab::Settings => app.toggle_focus(),
         KeyCode::BackTab if app.tab != Tab::Dashboard && app.tab != Tab::Settings => app.toggle_focus(),
-        KeyCode::Enter => {
-            if app.tab != Tab::Dashboard && app.tab != Tab::Settings {
-                app.toggle_focus();
-            }
+        KeyCode::Enter if app.tab != Tab::Dashboard && app.tab != Tab::Settings => {
+            app.toggle_focus();
         }
         _ => {}
     }
 
+    if app.tab == Tab::Library && previous_focus == Focus::List && app.focus == Focus::Detail {
+        record_library_access(vault, app);
+    }
     Ok(false)
 }
 
+/// Multi-line popup editing shared by the rationale and notes popups.
 fn handle_rationale_input(vault: &FsVault, app: &mut App, key: KeyEvent) -> Result<bool> {
+    let submit = |vault: &FsVault, app: &mut App| -> Result<()> {
+        if matches!(app.input_mode, InputMode::Notes) {
+            submit_notes(vault, app)?;
+        } else {
+            submit_rationale(vault, app)?;
+        }
+        app.input_mode = InputMode::None;
+        app.input.reset();
+        Ok(())
+    };
     match key.code {
         KeyCode::Esc => {
             app.input_mode = InputMode::None;
             app.input.reset();
         }
-        KeyCode::Enter => {
-            submit_rationale(vault, app)?;
-            app.input_mode = InputMode::None;
-            app.input.reset();
-        }
+        KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => submit(vault, app)?,
+        KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => submit(vault, app)?,
+        KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => app.input.move_home(),
+        KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => app.input.move_end(),
+        KeyCode::Enter => app.input.insert('\n'),
         KeyCode::Char(c) => app.input.insert(c),
         KeyCode::Backspace => app.input.delete_back(),
         KeyCode::Left => app.input.move_left(),
         KeyCode::Right => app.input.move_right(),
         KeyCode::Home => app.input.move_home(),
         KeyCode::End => app.input.move_end(),
-        _ => {
-            if key.modifiers.contains(KeyModifiers::CONTROL) {
-                 match key.code {
-                     KeyCode::Char('a') => app.input.move_home(),
-                     KeyCode::Char('e') => app.input.move_end(),
-                     _ => {}
-                 }
-            }
-        }
+        _ => {}
     }
     Ok(false)
 }
@@ -700,10 +1173,20 @@ fn handle_settings_path_input(app: &mut App, key: KeyEvent) -> Result<bool> {
             app.input.reset();
         }
         KeyCode::Enter => {
+            let target = expand_path(&app.input.content);
+            let check = check_vault_path(&target);
+            let message = check.message(&target);
+            if !check.is_usable() {
+                app.status = message;
+                return Ok(false);
+            }
             app.settings_path = app.input.content.clone();
             app.input_mode = InputMode::None;
             app.input.reset();
-            app.status = Some("Updated pending vault path".into());
+            app.status = Some(match message {
+                Some(message) => format!("Updated pending vault path. {message} when applied."),
+                None => "Updated pending vault path".into(),
+            });
         }
         KeyCode::Char(c) => app.input.insert(c),
         KeyCode::Backspace => app.input.delete_back(),
@@ -727,16 +1210,22 @@ fn handle_settings_path_input(app: &mut App, key: KeyEvent) -> Result<bool> {
 fn handle_confirm_input(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool> {
     match key.code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
-            if let Some(pending) = app.pending_confirm.clone() {
-                apply_settings_change(vault, app, pending)?;
+            match app.pending_confirm.clone() {
+                Some(PendingConfirm::Vault { action, target, .. }) => {
+                    apply_settings_change(vault, app, action, target)?;
+                }
+                Some(PendingConfirm::ClearInbox { .. }) => clear_inbox(vault, app)?,
+                None => {}
             }
             app.pending_confirm = None;
             app.input_mode = InputMode::None;
         }
         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-            app.pending_confirm = None;
+            app.status = Some(match app.pending_confirm.take() {
+                Some(PendingConfirm::ClearInbox { .. }) => "Inbox left unchanged".into(),
+                _ => "Cancelled settings change".into(),
+            });
             app.input_mode = InputMode::None;
-            app.status = Some("Cancelled settings change".into());
         }
         _ => {}
     }
@@ -760,6 +1249,15 @@ fn handle_manual_capture_input(vault: &FsVault, app: &mut App, key: KeyEvent) ->
             match capture.step {
                 CaptureStep::Title => {
                     capture.title = app.input.content.trim().to_string();
+                    capture.step = CaptureStep::EntryType;
+                }
+                CaptureStep::EntryType => {
+                    capture.entry_type = parse_entry_type(&app.input.content);
+                    capture.template = app
+                        .config
+                        .entry_template("manual", &capture.entry_type)
+                        .cloned()
+                        .unwrap_or_default();
                     capture.step = CaptureStep::Rationale;
                 }
                 CaptureStep::Rationale => {
@@ -772,10 +1270,6 @@ fn handle_manual_capture_input(vault: &FsVault, app: &mut App, key: KeyEvent) ->
                 }
                 CaptureStep::Tags => {
                     capture.tags = parse_tag_list(&app.input.content);
-                    capture.step = CaptureStep::EntryType;
-                }
-                CaptureStep::EntryType => {
-                    capture.entry_type = parse_entry_type(&app.input.content);
                     capture.step = CaptureStep::Verification;
                 }
                 CaptureStep::Verification => {
@@ -790,7 +1284,10 @@ fn handle_manual_capture_input(vault: &FsVault, app: &mut App, key: KeyEvent) ->
                     app.manual_capture = None;
                 }
             }
-            app.input.reset();
+            // Each step starts from the entry template's default, which stays editable.
+            app.input = TextInput::from(
+                app.manual_capture.as_ref().map(ManualCapture::step_default).unwrap_or_default(),
+            );
         }
         KeyCode::Char(c) => app.input.insert(c),
         KeyCode::Backspace => app.input.delete_back(),
@@ -915,25 +1412,83 @@ fn handle_edit_rationale(app: &mut App) {
     }
 }
 
+fn handle_edit_notes(app: &mut App) {
+    if app.tab != Tab::Library {
+        return;
+    }
+    let Some(id) = current_library_id(app) else {
+        return;
+    };
+    let notes = app
+        .library
+        .iter()
+        .find(|entry| entry.id == id)
+        .and_then(|entry| entry.notes.clone())
+        .unwrap_or_default();
+    app.input_mode = InputMode::Notes;
+    app.input = TextInput::from(notes);
+}
+
+/// Save the notes popup to the selected library entry; empty input clears them.
+fn submit_notes(vault: &FsVault, app: &mut App) -> Result<()> {
+    let Some(id) = current_library_id(app) else {
+        return Ok(());
+    };
+    if let Some(entry) = app.library.iter_mut().find(|entry| entry.id == id) {
+        let notes = app.input.content.trim_end();
+        entry.notes = (!notes.trim().is_empty()).then(|| notes.to_string());
+        vault.update(entry)?;
+        app.status = Some("Updated notes".into());
+    }
+    Ok(())
+}
+
 fn open_settings_path_input(app: &mut App) {
     app.input_mode = InputMode::SettingsPath;
     app.input = TextInput::from(app.settings_path.clone());
 }
 
 fn confirm_settings_change(app: &mut App, action: ConfirmAction) {
-    let target = std::path::PathBuf::from(app.settings_path.clone());
     if app.settings_path.trim().is_empty() {
         app.status = Some("Pending path is empty".into());
         return;
     }
-    if app.settings_path == app.current_vault_path {
+    let target = expand_path(&app.settings_path);
+    if target == std::path::Path::new(&app.current_vault_path) {
         app.status = Some("Pending path matches current vault path".into());
         return;
     }
-    app.pending_confirm = Some(PendingConfirm { action, target });
+    let check = check_vault_path(&target);
+    if !check.is_usable() {
+        app.status = check.message(&target);
+        return;
+    }
+    let will_create = check == PathCheck::WillCreate;
+    app.pending_confirm = Some(PendingConfirm::Vault { action, target, will_create });
+    app.input_mode = InputMode::Confirm;
+}
+
+/// Ask before removing every inbox item; `y` in the popup runs [`clear_inbox`].
+fn confirm_clear_inbox(app: &mut App) {
+    if app.inbox.is_empty() {
+        app.status = Some("Inbox is already empty".into());
+        return;
+    }
+    app.pending_confirm = Some(PendingConfirm::ClearInbox { count: app.inbox.len() });
     app.input_mode = InputMode::Confirm;
 }
 
+/// Remove every item queued in the vault's inbox, not only those shown by the current tab.
+fn clear_inbox(vault: &FsVault, app: &mut App) -> Result<()> {
+    let cleared = vault.clear_inbox()?;
+    app.inbox.clear();
+    app.selected_inbox.clear();
+    app.inbox_source_index = 0;
+    app.inbox_state.select(None);
+    app.status = Some(format!("Cleared {cleared} inbox item(s)"));
+    Ok(())
+}
+
 fn open_manual_capture(app: &mut App) {
     app.manual_capture = Some(ManualCapture {
         step: CaptureStep::Title,
@@ -943,6 +1498,7 @@ fn open_manual_capture(app: &mut App) {
         tags: Vec::new(),
         entry_type: EntryType::Other,
         verification: None,
+        template: EntryTemplate::default(),
     });
     app.input_mode = InputMode::ManualCapture;
     app.input.reset();
@@ -973,21 +1529,18 @@ fn finalize_manual_capture(vault: &FsVault, app: &mut App) -> Result<()> {
         SystemInfo {
             os: std::env::consts::OS.into(),
             arch: std::env::consts::ARCH.into(),
+            hostname: sv_utils::hostname(),
         },
         chrono::Utc::now(),
         EntryStatus::Active,
-        capture
-            .tags
-            .into_iter()
-            .map(Tag::new)
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|err| anyhow::anyhow!(err.to_string()))?,
+        canonical_tags(capture.tags, &app.config.tag_aliases)?,
         Rationale::new(capture.rationale)?,
         capture.verification,
     )?;
 
     vault.create(&entry)?;
     app.library.push(entry);
+    app.refresh_weak_rationales();
     app.status = Some("Manual entry saved".into());
     Ok(())
 }
@@ -1011,17 +1564,34 @@ fn parse_entry_type(input: &str) -> EntryType {
     }
 }
 
+/// Run the configured detectors (reusing fresh cache entries) with exclusions and dedup applied.
+fn detect_changes(vault: &FsVault) -> Result<Vec<DetectedChange>> {
+    let config = load_config().context("failed to load config")?;
+    let ignore = IgnorePatterns::compile(&config.ignore_patterns)
+        .context("invalid ignore_patterns in config")?;
+    let detector_config = config.detector_config();
+    let detectors = configured_detectors(&detector_config);
+    let mut changes = scan_detectors_cached(
+        detectors,
+        &detector_config,
+        &vault.detector_cache_dir(),
+        config.detector_cache_ttl_secs,
+        true,
+    )
+    .context("detector run failed")?;
+    ignore.apply(&mut changes);
+    dedupe_by_source_priority(&mut changes, &config.source_priority);
+    Ok(changes)
+}
+
 fn handle_refresh(vault: &FsVault, app: &mut App) -> Result<()> {
     if app.tab == Tab::Dashboard || app.tab == Tab::Inbox {
-        let detectors = default_detectors();
-
-        let runtime = tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .context("failed to initialize runtime")?;
-        let changes = runtime
-            .block_on(run_detectors(detectors))
-            .context("detector run failed")?;
+        if app.no_detectors {
+            app.status = Some(DETECTORS_OFF_STATUS.into());
+            return Ok(());
+        }
+        let changes = detect_changes(vault)?;
+        app.drift = None;
 
         let mut inbox = vault.load_inbox().unwrap_or_default();
         let mut new_changes = Vec::new();
@@ -1033,7 +1603,10 @@ fn handle_refresh(vault: &FsVault, app: &mut App) -> Result<()> {
         }
         if !new_changes.is_empty() {
             append_unique(&mut inbox, new_changes);
-            vault.save_inbox(&inbox)?;
+            if let Some(notice) = vault.save_inbox(&inbox)? {
+                app.report_error(format!("Warning: {notice}"));
+                inbox = vault.load_inbox().unwrap_or_default();
+            }
         }
         app.inbox = inbox;
         if app.inbox_state.selected().is_none() && !app.inbox.is_empty() {
@@ -1045,14 +1618,130 @@ fn handle_refresh(vault: &FsVault, app: &mut App) -> Result<()> {
 
 
 
+/// Show the drift overlay, scanning only when no report is cached since the last refresh.
+fn open_drift_report(vault: &FsVault, app: &mut App) -> Result<()> {
+    if app.no_detectors {
+        app.status = Some(DETECTORS_OFF_STATUS.into());
+        return Ok(());
+    }
+    if app.drift.is_none() {
+        let changes = detect_changes(vault)?;
+        app.drift = Some(drift_report(&app.library, &changes));
+    }
+    app.show_drift = true;
+    Ok(())
+}
+
+/// Compare detected changes with library entries on `(source, title)`.
+///
+/// Only entries whose source was scanned can be missing, so manual captures never are.
+fn drift_report(library: &[Entry], detected: &[DetectedChange]) -> DriftReport {
+    let key = |source: &str, title: &str| (source.to_string(), title.to_string());
+    let detected_keys: HashSet<(String, String)> =
+        detected.iter().map(|change| key(&change.source, &change.title)).collect();
+    let scanned_sources: HashSet<&str> =
+        detected.iter().map(|change| change.source.as_str()).collect();
+    let library_keys: HashSet<(String, String)> =
+        library.iter().map(|entry| key(&entry.source, &entry.title)).collect();
+
+    let mut report = DriftReport::default();
+    for entry in library {
+        if detected_keys.contains(&key(&entry.source, &entry.title)) {
+            report.tracked += 1;
+        } else if scanned_sources.contains(entry.source.as_str()) {
+            report.missing.push(key(&entry.source, &entry.title));
+        }
+    }
+    for change in detected {
+        let change_key = key(&change.source, &change.title);
+        if !library_keys.contains(&change_key) && !report.untracked.contains(&change_key) {
+            report.untracked.push(change_key);
+        }
+    }
+    report.untracked.sort();
+    report.missing.sort();
+    report
+}
+
+fn render_drift_popup(frame: &mut ratatui::Frame, area: Rect, report: &DriftReport) {
+    let popup_area = centered_rect(80, 70, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Drift: {} tracked", report.tracked));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(inner);
+    let sections = [
+        ("Untracked (detected, not in library)", &report.untracked, Color::Yellow),
+        ("Missing (in library, not detected)", &report.missing, Color::Red),
+    ];
+    for ((title, items, color), column) in sections.into_iter().zip(columns.iter()) {
+        let lines: Vec<Line> = if items.is_empty() {
+            vec![Line::from("None")]
+        } else {
+            items
+                .iter()
+                .map(|(source, title)| Line::from(format!("{title}  [{source}]")))
+                .collect()
+        };
+        let section = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{title}: {}", items.len()))
+                .border_style(Style::default().fg(color)),
+        );
+        frame.render_widget(section, *column);
+    }
+}
+
+fn render_messages_popup(
+    frame: &mut ratatui::Frame,
+    area: Rect,
+    history: &VecDeque<StatusMessage>,
+) {
+    let popup_area = centered_rect(70, 60, area);
+    frame.render_widget(Clear, popup_area);
+    let lines: Vec<Line> = if history.is_empty() {
+        vec![Line::from("No messages yet")]
+    } else {
+        history
+            .iter()
+            .rev()
+            .map(|message| match message.level {
+                StatusLevel::Info => Line::from(message.text.as_str()),
+                StatusLevel::Error => Line::styled(
+                    format!("error: {}", message.text),
+                    Style::default().fg(Color::Red),
+                ),
+            })
+            .collect()
+    };
+    let messages = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Messages (newest first)"))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(messages, popup_area);
+}
+
 fn handle_init_input(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool> {
     match key.code {
         KeyCode::Esc => {
             app.input.reset();
+            app.status = None;
         }
         KeyCode::Enter => {
-            let path = std::path::PathBuf::from(&app.input.content);
-            *vault = FsVault::new(path);
+            let path = expand_path(&app.input.content);
+            let check = check_vault_path(&path);
+            if !check.is_usable() {
+                app.status = check.message(&path);
+                return Ok(false);
+            }
+            app.status = None;
+            *vault = FsVault::open(path)?;
             vault.init().context("failed to initialize vault")?;
             set_config_path(vault.path())?;
             app.input_mode = InputMode::None;
@@ -1123,22 +1812,49 @@ fn handle_snooze(vault: &FsVault, app: &mut App) -> Result<()> {
         app.inbox.retain(|item| item.id != *id);
     }
 
+    app.snoozed = vault.load_snoozed().unwrap_or_default();
     app.selected_inbox.clear();
     app.status = Some(format!("Snoozed {} item(s)", ids_to_snooze.len()));
     Ok(())
 }
 
-fn handle_unsnooze(vault: &FsVault, app: &mut App) -> Result<()> {
-    if app.tab != Tab::Snoozed {
+/// Snooze every inbox item shown under the current source tab (narrowed by any filter).
+fn handle_snooze_source(vault: &FsVault, app: &mut App) -> Result<()> {
+    if app.tab != Tab::Inbox {
         return Ok(());
     }
-
-    let ids_to_unsnooze: Vec<uuid::Uuid> = if !app.selected_snoozed.is_empty() {
-        app.selected_snoozed.iter().cloned().collect()
-    } else {
-        current_snoozed_id(app).into_iter().collect()
+    let Some(source) = app.current_inbox_source() else {
+        app.status = Some("Pick a source with h/l to snooze all of its items".into());
+        return Ok(());
     };
-
+    let ids: Vec<uuid::Uuid> = app.filtered_inbox().iter().map(|item| item.id).collect();
+    for id in &ids {
+        vault.snooze_inbox_item(*id)?;
+        app.inbox.retain(|item| item.id != *id);
+        app.selected_inbox.remove(id);
+    }
+    app.snoozed = vault.load_snoozed().unwrap_or_default();
+    let remaining = app.available_sources().len();
+    if app.inbox_source_index >= remaining {
+        app.inbox_source_index = 0;
+    }
+    let len = app.filtered_inbox().len();
+    app.inbox_state.select((len > 0).then(|| app.inbox_state.selected().unwrap_or(0).min(len - 1)));
+    app.status = Some(format!("Snoozed {} item(s) from {source}", ids.len()));
+    Ok(())
+}
+
+fn handle_unsnooze(vault: &FsVault, app: &mut App) -> Result<()> {
+    if app.tab != Tab::Snoozed {
+        return Ok(());
+    }
+
+    let ids_to_unsnooze: Vec<uuid::Uuid> = if !app.selected_snoozed.is_empty() {
+        app.selected_snoozed.iter().cloned().collect()
+    } else {
+        current_snoozed_id(app).into_iter().collect()
+    };
+
     if ids_to_unsnooze.is_empty() {
         return Ok(());
     }
@@ -1168,20 +1884,21 @@ fn submit_rationale(vault: &FsVault, app: &mut App) -> Result<()> {
                 return Ok(());
             }
 
-            let rationale = Rationale::new(app.input.content.clone())?;
             let mut approved_count = 0;
+            let mut warnings = Vec::new();
 
             for id in ids_to_approve {
                 if let Some(change) = app.inbox.iter().find(|c| c.id == id).cloned() {
                     if let Some(path) = change.path.as_ref() {
-                        if let Ok(contents) = std::fs::read_to_string(path) {
-                            if sv_utils::contains_potential_secret(&contents) {
-                                app.status = Some(format!("Warning: potential secret in {path}"));
-                            }
+                        if let Some(warning) = sv_utils::inspect_change_path(path) {
+                            warnings.push(format!("{warning}: {path}"));
                         }
                     }
 
-                    let entry = Entry::new(
+                    let uninstall_cmd = derive_uninstall_cmd(&change.cmd);
+                    let rationale =
+                        rationale_from_input(&app.config, &app.input.content, &change.title)?;
+                    let mut entry = Entry::new(
                         uuid::Uuid::new_v4(),
                         change.title,
                         change.entry_type,
@@ -1190,10 +1907,15 @@ fn submit_rationale(vault: &FsVault, app: &mut App) -> Result<()> {
                         change.system,
                         change.detected_at,
                         EntryStatus::Active,
-                        change.tags,
-                        rationale.clone(),
+                        canonical_tags(
+                            change.tags.iter().map(Tag::as_str),
+                            &app.config.tag_aliases,
+                        )?,
+                        rationale,
                         None,
                     )?;
+                    entry.uninstall_cmd = uninstall_cmd;
+                    entry.detector = change.detector;
 
                     vault.create(&entry)?;
                     vault.remove_inbox_item(change.id)?;
@@ -1203,28 +1925,48 @@ fn submit_rationale(vault: &FsVault, app: &mut App) -> Result<()> {
                 }
             }
 
+            let input = app.input.content.trim();
+            if approved_count > 0 && app.rationale_quality_warnings && !input.starts_with('@') {
+                if let Some(warning) = sv_utils::rationale_warning(input, approved_count) {
+                    warnings.push(format!("{warning}; consider a specific rationale per entry"));
+                }
+            }
             app.selected_inbox.clear();
-            app.status = Some(format!("Approved {} item(s)", approved_count));
+            app.status = Some(format!("Approved {approved_count} item(s)"));
+            if let Some(warning) = warnings.first() {
+                app.report_error(format!("Warning: {warning}"));
+            }
         }
         Tab::Library => {
              if let Some(id) = current_library_id(app) {
                  if let Some(entry) = app.library.iter_mut().find(|e| e.id == id) {
-                    entry.rationale = Rationale::new(app.input.content.clone())?;
+                    let rationale =
+                        rationale_from_input(&app.config, &app.input.content, &entry.title)?;
+                    entry.revise_rationale(rationale, chrono::Utc::now());
                     vault.update(entry)?;
                     app.status = Some("Updated rationale".into());
                  }
              }
         }
     }
+    app.refresh_weak_rationales();
     Ok(())
 }
 
+/// Build a rationale from popup input; `@name` expands the named template from `config`.
+fn rationale_from_input(config: &VaultConfig, input: &str, title: &str) -> Result<Rationale> {
+    match input.trim().strip_prefix('@') {
+        Some(name) => Ok(config.expand_rationale_template(name.trim(), title)?),
+        None => Ok(Rationale::new(input.trim_end().to_string())?),
+    }
+}
+
 fn apply_settings_change(
     vault: &mut FsVault,
     app: &mut App,
-    pending: PendingConfirm,
+    action: ConfirmAction,
+    target: std::path::PathBuf,
 ) -> Result<()> {
-    let target = pending.target;
     let current = vault.path().to_path_buf();
 
     if target == current {
@@ -1232,15 +1974,14 @@ fn apply_settings_change(
         return Ok(());
     }
 
-    match pending.action {
+    match action {
         ConfirmAction::MoveVault => {
-            move_vault(&current, &target)?;
-            *vault = FsVault::new(target.clone());
+            *vault = vault.move_to(&target, false)?;
             set_config_path(&target)?;
             app.status = Some("Vault moved to new location".into());
         }
         ConfirmAction::SwitchVault => {
-            let new_vault = FsVault::new(target.clone());
+            let new_vault = FsVault::open(target.clone())?;
             if !new_vault.exists() {
                 new_vault.init().context("failed to initialize vault")?;
             }
@@ -1256,53 +1997,6 @@ fn apply_settings_change(
     Ok(())
 }
 
-fn move_vault(source: &std::path::Path, target: &std::path::Path) -> Result<()> {
-    if !source.exists() {
-        return Err(anyhow::anyhow!("source vault path does not exist"));
-    }
-
-    if target.exists() {
-        if !target.is_dir() {
-            return Err(anyhow::anyhow!(
-                "target path exists and is not a directory"
-            ));
-        }
-        if !is_dir_empty(target)? {
-            return Err(anyhow::anyhow!(
-                "target directory is not empty"
-            ));
-        }
-    } else if let Some(parent) = target.parent() {
-        std::fs::create_dir_all(parent).context("failed to create target parent")?;
-    }
-
-    if let Err(_) = std::fs::rename(source, target) {
-        copy_dir_all(source, target)?;
-        std::fs::remove_dir_all(source).context("failed to remove source vault")?;
-    }
-    Ok(())
-}
-
-fn is_dir_empty(path: &std::path::Path) -> Result<bool> {
-    let mut entries = std::fs::read_dir(path).context("failed to read target directory")?;
-    Ok(entries.next().is_none())
-}
-
-fn copy_dir_all(source: &std::path::Path, target: &std::path::Path) -> Result<()> {
-    std::fs::create_dir_all(target).context("failed to create target directory")?;
-    for entry in std::fs::read_dir(source).context("failed to read source directory")? {
-        let entry = entry.context("failed to read source entry")?;
-        let path = entry.path();
-        let dest = target.join(entry.file_name());
-        if path.is_dir() {
-            copy_dir_all(&path, &dest)?;
-        } else {
-            std::fs::copy(&path, &dest).context("failed to copy file")?;
-        }
-    }
-    Ok(())
-}
-
 fn render_filter_popup(frame: &mut Frame, app: &App) {
     let area = centered_rect(60, 20, frame.size());
     let r = Layout::default()
@@ -1399,9 +2093,14 @@ fn render_app(frame: &mut ratatui::Frame, app: &App) {
         .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(3)].as_ref())
         .split(size);
 
-    let titles = vec!["Dashboard", "Library", "Inbox", "Snoozed", "Settings"]
+    let snoozed_title = if app.snoozed.is_empty() {
+        "Snoozed".to_string()
+    } else {
+        format!("Snoozed ({})", app.snoozed.len())
+    };
+    let titles = ["Dashboard", "Library", "Inbox", snoozed_title.as_str(), "Settings"]
         .iter()
-        .map(|title| Line::from(Span::styled(*title, Style::default())))
+        .map(|title| Line::from(Span::styled(title.to_string(), Style::default())))
         .collect::<Vec<_>>();
 
     let tabs = Tabs::new(titles)
@@ -1427,20 +2126,30 @@ fn render_app(frame: &mut ratatui::Frame, app: &App) {
 
     render_guide_bar(frame, chunks[2], app);
 
-    if matches!(app.input_mode, InputMode::Rationale) {
-        render_input_popup(frame, size, &app.input);
+    match app.input_mode {
+        InputMode::Rationale => render_input_popup(frame, size, "Rationale", &app.input),
+        InputMode::Notes => render_input_popup(frame, size, "Notes", &app.input),
+        _ => {}
     }
 
     if app.show_help {
         render_help_popup(frame, size, &help_text(app));
     }
 
+    if let Some(report) = app.drift.as_ref().filter(|_| app.show_drift) {
+        render_drift_popup(frame, size, report);
+    }
+
+    if app.show_messages {
+        render_messages_popup(frame, size, &app.status_history);
+    }
+
     if matches!(app.input_mode, InputMode::Palette) {
         render_palette_popup(frame, size, app);
     }
 
     if matches!(app.input_mode, InputMode::Init) {
-        render_init_popup(frame, size, &app.input);
+        render_init_popup(frame, size, &app.input, app.error.as_deref().or(app.status.as_deref()));
     }
 
     if matches!(app.input_mode, InputMode::Filter) {
@@ -1451,6 +2160,10 @@ fn render_app(frame: &mut ratatui::Frame, app: &App) {
         render_snooze_popup(frame, size, &app.input);
     }
 
+    if matches!(app.input_mode, InputMode::GotoId) {
+        render_input_popup(frame, size, "Go to Entry by Id", &app.input);
+    }
+
     if matches!(app.input_mode, InputMode::SettingsPath) {
         render_settings_path_popup(frame, size, &app.input);
     }
@@ -1484,8 +2197,9 @@ fn render_dashboard(frame: &mut ratatui::Frame, area: Rect, app: &App) {
         ])
         .split(chunks[0]);
 
-    let inbox_count = app.inbox.len();
-    let library_count = app.library.len();
+    let stats = VaultStats::from_parts(&app.library, &app.inbox, &app.snoozed);
+    let inbox_count = stats.inbox;
+    let library_count = stats.total_entries;
     let total_count = inbox_count + library_count;
 
     let s1 = Paragraph::new(format!("\n{}", inbox_count))
@@ -1517,19 +2231,15 @@ fn render_dashboard(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     frame.render_widget(s3, summary_chunks[2]);
 
     // Center: Source Breakdown (BarChart)
-    let mut source_counts: HashMap<String, u64> = HashMap::new();
-    for entry in &app.library {
-        *source_counts.entry(entry.source.clone()).or_insert(0) += 1;
-    }
-    let mut counts_vec: Vec<(String, u64)> = source_counts.into_iter().collect();
-    counts_vec.sort_by(|a, b| {
-        b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0))
-    });
-
-    let bars_data: Vec<(&str, u64)> = counts_vec
-        .iter()
+    let bar_labels: Vec<(String, u64)> = stats
+        .top_sources()
+        .into_iter()
         .take(5)
-        .map(|(k, v)| (k.as_str(), *v))
+        .map(|(source, count)| (source_display(source, app.plain_labels), count as u64))
+        .collect();
+    let bars_data: Vec<(&str, u64)> = bar_labels
+        .iter()
+        .map(|(label, count)| (label.as_str(), *count))
         .collect();
 
     let barchart = BarChart::default()
@@ -1539,14 +2249,41 @@ fn render_dashboard(frame: &mut ratatui::Frame, area: Rect, app: &App) {
         .bar_gap(2)
         .bar_style(Style::default().fg(Color::Yellow))
         .value_style(Style::default().fg(Color::Black).bg(Color::Yellow));
-    frame.render_widget(barchart, chunks[1]);
+
+    // Pinned entries share the center row with the chart as a quick reference.
+    let pinned: Vec<&Entry> = app.library.iter().filter(|entry| entry.pinned).collect();
+    if pinned.is_empty() {
+        frame.render_widget(barchart, chunks[1]);
+    } else {
+        let center_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .split(chunks[1]);
+        frame.render_widget(barchart, center_chunks[0]);
+
+        let pinned_items = pinned
+            .iter()
+            .map(|entry| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("★ {} ", entry.title),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::styled(entry.cmd.clone(), Style::default().fg(Color::DarkGray)),
+                ]))
+            })
+            .collect::<Vec<_>>();
+        let pinned_list = List::new(pinned_items).block(
+            Block::default()
+                .title(format!("Pinned ({})", pinned.len()))
+                .borders(Borders::ALL),
+        );
+        frame.render_widget(pinned_list, center_chunks[1]);
+    }
 
     // Row 3: Recent Activity
-    let recent_items = app
-        .library
-        .iter()
-        .rev()
-        .take(5)
+    let recent_items = recent_entries(&app.library, app.recent_limit)
+        .into_iter()
         .map(|e| {
             ListItem::new(Line::from(vec![
                 Span::styled(format!("[{}] ", e.source), Style::default().fg(Color::Blue)),
@@ -1564,6 +2301,53 @@ fn render_dashboard(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     frame.render_widget(recent_list, chunks[2]);
 }
 
+/// The `limit` most recently detected entries, newest first, each listed once.
+///
+/// Ties keep the later-stored entry first, matching the old insertion-order view.
+fn recent_entries(library: &[Entry], limit: usize) -> Vec<&Entry> {
+    let mut seen = HashSet::new();
+    let mut recent: Vec<&Entry> =
+        library.iter().rev().filter(|entry| seen.insert(entry.id)).collect();
+    recent.sort_by_key(|entry| std::cmp::Reverse(entry.detected_at));
+    recent.truncate(limit);
+    recent
+}
+
+/// Slice of a `len`-item bordered list that fits in `area`, scrolled the way `List` would so
+/// the selection stays visible, plus a state whose selection is relative to that slice.
+///
+/// Building `ListItem`s only for this window keeps per-frame work proportional to the
+/// viewport rather than the whole list.
+fn visible_window(
+    state: &ListState,
+    len: usize,
+    area: Rect,
+) -> (std::ops::Range<usize>, ListState) {
+    let height = usize::from(area.height.saturating_sub(2)).max(1);
+    let last = len.saturating_sub(1);
+    let selected = state.selected().filter(|_| len > 0).map(|index| index.min(last));
+    let mut start = state.offset().min(last);
+    if let Some(selected) = selected {
+        if selected < start {
+            start = selected;
+        } else if selected >= start + height {
+            start = selected + 1 - height;
+        }
+    }
+    let window_state = ListState::default().with_selected(selected.map(|index| index - start));
+    (start..(start + height).min(len), window_state)
+}
+
+/// Widths of the list and detail panes; when zoomed, the focused pane takes the full width
+/// and the other collapses to nothing.
+fn pane_constraints(app: &App) -> [Constraint; 2] {
+    match (app.zoomed, app.focus) {
+        (false, _) => [Constraint::Percentage(40), Constraint::Percentage(60)],
+        (true, Focus::List) => [Constraint::Percentage(100), Constraint::Length(0)],
+        (true, Focus::Detail) => [Constraint::Length(0), Constraint::Percentage(100)],
+    }
+}
+
 fn render_inbox(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -1574,7 +2358,7 @@ fn render_inbox(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     let sources = app.available_sources();
     let source_titles: Vec<Line> = sources
         .iter()
-        .map(|s| Line::from(s.as_str()))
+        .map(|s| Line::from(source_display(s, app.plain_labels)))
         .collect();
     
     // Clamp index for safety
@@ -1590,28 +2374,38 @@ fn render_inbox(frame: &mut ratatui::Frame, area: Rect, app: &App) {
 
     let list_chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .constraints(pane_constraints(app))
         .split(chunks[1]);
 
-    let items = app
-        .filtered_inbox()
+    let inbox = app.filtered_inbox();
+    let (window, mut list_state) = visible_window(&app.inbox_state, inbox.len(), list_chunks[0]);
+    let items = inbox[window]
         .iter()
         .map(|change| {
-            let mut title = change.title.clone();
+            let mut title = with_source_icon(&change.title, &change.source, app.plain_labels);
             if app.selected_inbox.contains(&change.id) {
                 title = format!("[x] {title}");
             } else {
                 title = format!("[ ] {title}");
             }
-            ListItem::new(title)
+            ListItem::new(with_tag_suffix(title, &change.tags))
         })
         .collect::<Vec<_>>();
     let list_block = Block::default()
         .borders(Borders::ALL)
-        .title(if let Some(filter) = &app.active_filter {
-            format!("Inbox (Filtered: {})", filter)
-        } else {
-            "Inbox".into()
+        .title({
+            let mut title = if let Some(filter) = &app.active_filter {
+                format!("Inbox (Filtered: {})", filter)
+            } else {
+                "Inbox".into()
+            };
+            if app.inbox_sort == InboxSort::OldestFirst {
+                title.push_str(" (Oldest first)");
+            }
+            if app.selected_only(&app.selected_inbox).is_some() {
+                title.push_str(" (Selected)");
+            }
+            title
         })
         .border_style(if app.focus == Focus::List {
             Style::default().fg(Color::Yellow)
@@ -1621,21 +2415,29 @@ fn render_inbox(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     let list = List::new(items)
         .block(list_block)
         .highlight_style(Style::default().bg(Color::DarkGray));
-    frame.render_stateful_widget(list, list_chunks[0], &mut app.inbox_state.clone());
+    frame.render_stateful_widget(list, list_chunks[0], &mut list_state);
 
     let detail = match app.inbox_state.selected().and_then(|i| app.filtered_inbox().get(i).copied()) {
         Some(change) => {
             let mut lines = Vec::new();
             lines.push(Line::from(Span::styled(
-                format!("{}", change.title),
+                change.title.clone(),
                 Style::default().add_modifier(Modifier::BOLD),
             )));
             lines.push(Line::from(format!("Source: {}", change.source)));
             lines.push(Line::from(format!("Type: {:?}", change.entry_type)));
             lines.push(Line::from(format!("Cmd: {}", change.cmd)));
+            lines.extend(tags_line(&change.tags));
             if let Some(path) = &change.path {
                 lines.push(Line::from(format!("Path: {}", path)));
             }
+            if let Some(host) = &change.system.hostname {
+                lines.push(Line::from(format!("Host: {host}")));
+            }
+            lines.push(Line::from(format!(
+                "Age: {}",
+                humanize_age(change.detected_at, chrono::Utc::now())
+            )));
             lines
         }
         None => vec![Line::from("No item selected")],
@@ -1656,10 +2458,44 @@ fn render_inbox(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     frame.render_widget(detail_p, list_chunks[1]);
 }
 
+/// Number of tags shown in a list row before collapsing the rest into `+N`.
+const LIST_ROW_TAG_LIMIT: usize = 2;
+
+/// List row text followed by a dimmed, truncated tag suffix such as `#cli #json +1`.
+fn with_tag_suffix(title: String, tags: &[Tag]) -> Line<'static> {
+    if tags.is_empty() {
+        return Line::from(title);
+    }
+    let mut suffix: Vec<String> = tags
+        .iter()
+        .take(LIST_ROW_TAG_LIMIT)
+        .map(|tag| format!("#{}", tag.as_str()))
+        .collect();
+    if tags.len() > LIST_ROW_TAG_LIMIT {
+        suffix.push(format!("+{}", tags.len() - LIST_ROW_TAG_LIMIT));
+    }
+    Line::from(vec![
+        Span::raw(title),
+        Span::styled(
+            format!(" {}", suffix.join(" ")),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ])
+}
+
+/// Detail pane `Tags:` line; nothing for entries without tags.
+fn tags_line(tags: &[Tag]) -> Option<Line<'static>> {
+    if tags.is_empty() {
+        return None;
+    }
+    let tags: Vec<&str> = tags.iter().map(Tag::as_str).collect();
+    Some(Line::from(format!("Tags: {}", tags.join(", "))))
+}
+
 fn render_snoozed(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .constraints(pane_constraints(app))
         .split(area);
 
     let items = app.filtered_snoozed()
@@ -1671,15 +2507,21 @@ fn render_snoozed(frame: &mut ratatui::Frame, area: Rect, app: &App) {
             } else {
                 title = format!("[ ] {title}");
             }
-            ListItem::new(title)
+            ListItem::new(with_tag_suffix(title, &change.tags))
         })
         .collect::<Vec<_>>();
     let list_block = Block::default()
         .borders(Borders::ALL)
-        .title(if let Some(filter) = &app.active_filter {
-            format!("Snoozed Items (Filtered: {})", filter)
-        } else {
-            "Snoozed Items".into()
+        .title({
+            let mut title = if let Some(filter) = &app.active_filter {
+                format!("Snoozed Items (Filtered: {})", filter)
+            } else {
+                "Snoozed Items".into()
+            };
+            if app.selected_only(&app.selected_snoozed).is_some() {
+                title.push_str(" (Selected)");
+            }
+            title
         })
         .border_style(if app.focus == Focus::List {
             Style::default().fg(Color::Yellow)
@@ -1695,15 +2537,19 @@ fn render_snoozed(frame: &mut ratatui::Frame, area: Rect, app: &App) {
         Some(change) => {
             let mut lines = Vec::new();
             lines.push(Line::from(Span::styled(
-                format!("{}", change.title),
+                change.title.clone(),
                 Style::default().add_modifier(Modifier::BOLD),
             )));
             lines.push(Line::from(format!("Source: {}", change.source)));
             lines.push(Line::from(format!("Type: {:?}", change.entry_type)));
             lines.push(Line::from(format!("Cmd: {}", change.cmd)));
+            lines.extend(tags_line(&change.tags));
             if let Some(path) = &change.path {
                 lines.push(Line::from(format!("Path: {}", path)));
             }
+            if let Some(host) = &change.system.hostname {
+                lines.push(Line::from(format!("Host: {host}")));
+            }
             lines
         }
         None => vec![Line::from("No item selected")],
@@ -1734,7 +2580,7 @@ fn render_library(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     let sources = app.available_library_sources();
     let source_titles: Vec<Line> = sources
         .iter()
-        .map(|s| Line::from(s.as_str()))
+        .map(|s| Line::from(source_display(s, app.plain_labels)))
         .collect();
     
     // Clamp index for safety
@@ -1750,29 +2596,46 @@ fn render_library(frame: &mut ratatui::Frame, area: Rect, app: &App) {
 
     let list_chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .constraints(pane_constraints(app))
         .split(chunks[1]);
 
-    let items = app
-        .filtered_library()
+    let library = app.filtered_library();
+    let (window, mut list_state) =
+        visible_window(&app.library_state, library.len(), list_chunks[0]);
+    let items = library[window]
         .iter()
         .map(|entry| {
-            let mut title = entry.title.clone();
+            let mut title = with_source_icon(&entry.title, &entry.source, app.plain_labels);
+            if app.weak_rationales.contains(&entry.id) {
+                title = format!("⚠ {title}");
+            }
+            if entry.pinned {
+                title = format!("★ {title}");
+            }
             if app.selected_library.contains(&entry.id) {
                 title = format!("[x] {title}");
             } else {
                 title = format!("[ ] {title}");
             }
-            ListItem::new(title)
+            ListItem::new(with_tag_suffix(title, &entry.tags))
         })
         .collect::<Vec<_>>();
+    let mut list_title = match &app.library_type_filter {
+        Some(entry_type) => format!("Library [{entry_type:?}]"),
+        None => "Library".to_string(),
+    };
+    if let Some(filter) = &app.active_filter {
+        list_title = format!("{list_title} (Filtered: {filter})");
+    }
+    if app.library_sort == LibrarySort::RecentlyUsed {
+        list_title.push_str(" (Recent)");
+    }
+    if app.selected_only(&app.selected_library).is_some() {
+        list_title.push_str(" (Selected)");
+    }
     let list_block = Block::default()
         .borders(Borders::ALL)
-        .title(if let Some(filter) = &app.active_filter {
-            format!("Library (Filtered: {})", filter)
-        } else {
-            "Library".into()
-        })
+        .title(list_title)
         .border_style(if app.focus == Focus::List {
             Style::default().fg(Color::Yellow)
         } else {
@@ -1781,7 +2644,7 @@ fn render_library(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     let list = List::new(items)
         .block(list_block)
         .highlight_style(Style::default().bg(Color::DarkGray));
-    frame.render_stateful_widget(list, list_chunks[0], &mut app.library_state.clone());
+    frame.render_stateful_widget(list, list_chunks[0], &mut list_state);
 
     let detail = match app.library_state.selected().and_then(|i| app.filtered_library().get(i).copied()) {
         Some(entry) => {
@@ -1791,10 +2654,37 @@ fn render_library(frame: &mut ratatui::Frame, area: Rect, app: &App) {
                 Style::default().add_modifier(Modifier::BOLD),
             )));
             lines.push(Line::from(format!("Source: {}", entry.source)));
+            if let Some(detector) = &entry.detector {
+                lines.push(Line::from(format!("Detector: {detector}")));
+            }
             lines.push(Line::from(format!("Type: {:?}", entry.entry_type)));
             lines.push(Line::from(format!("Cmd: {}", entry.cmd)));
-            lines.push(Line::from("Rationale:"));
-            lines.push(Line::from(entry.rationale.as_str().to_string()));
+            lines.extend(tags_line(&entry.tags));
+            if let Some(uninstall_cmd) = &entry.uninstall_cmd {
+                lines.push(Line::from(format!("Uninstall: {uninstall_cmd}")));
+            }
+            if let Some(script_body) = &entry.script_body {
+                lines.push(Line::from(format!("Script: {} line(s)", script_body.lines().count())));
+            }
+            if let Some(host) = &entry.system.hostname {
+                lines.push(Line::from(format!("Host: {host}")));
+            }
+            if let Some(verified) = entry.last_verified {
+                lines.push(Line::from(format!(
+                    "Last verified: {}",
+                    humanize_age(verified, chrono::Utc::now())
+                )));
+            }
+            lines.push(Line::from(match entry.rationale_history.len() {
+                0 => "Rationale:".to_string(),
+                count => format!("Rationale ({count} prior revision(s)):"),
+            }));
+            lines.extend(entry.rationale.as_str().lines().map(|line| Line::from(line.to_string())));
+            if let Some(notes) = &entry.notes {
+                lines.push(Line::from(""));
+                lines.push(Line::from("Notes:"));
+                lines.extend(notes.lines().map(|line| Line::from(line.to_string())));
+            }
             Paragraph::new(lines)
                 .block(
                     Block::default()
@@ -1862,23 +2752,52 @@ fn render_settings(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     }
 }
 
-fn render_input_popup(frame: &mut ratatui::Frame, area: Rect, input_data: &TextInput) {
+fn render_input_popup(
+    frame: &mut ratatui::Frame,
+    area: Rect,
+    title: &str,
+    input_data: &TextInput,
+) {
     let popup_area = centered_rect(60, 20, area);
     frame.render_widget(Clear, popup_area);
-    let block = Block::default().borders(Borders::ALL).title("Rationale");
+    let block = Block::default().borders(Borders::ALL).title(title);
+    // Keep whitespace so the cursor math below matches what is drawn.
     let input_widget = Paragraph::new(input_data.content.as_str())
         .block(block)
-        .wrap(Wrap { trim: true });
+        .wrap(Wrap { trim: false });
     frame.render_widget(input_widget, popup_area);
-    
-    // Simple cursor positioning (approximate for wrapped text, better for single line)
-    // For wrap, we would need to calculate line breaks. For now let's assume end of text if flows.
-    // A robust impl would use the width.
-    let x_offset = (input_data.cursor as u16) % (popup_area.width - 2); 
-    let y_offset = (input_data.cursor as u16) / (popup_area.width - 2);
+
+    let width = popup_area.width.saturating_sub(2).max(1);
+    let (x_offset, y_offset) = input_data.wrapped_cursor(usize::from(width));
+    let x_offset = u16::try_from(x_offset).unwrap_or(u16::MAX).min(width - 1);
+    let y_offset = u16::try_from(y_offset)
+        .unwrap_or(u16::MAX)
+        .min(popup_area.height.saturating_sub(3));
     frame.set_cursor(popup_area.x + 1 + x_offset, popup_area.y + 1 + y_offset);
 }
 
+/// Character offsets where each wrapped row of `line` begins, breaking after spaces when a
+/// word would overflow `width` and mid-word only when a word is wider than a row.
+fn wrap_row_starts(line: &str, width: usize) -> Vec<usize> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut starts = vec![0];
+    let mut row_start = 0;
+    let mut last_break = None;
+    for (index, ch) in chars.iter().enumerate() {
+        if index - row_start == width {
+            row_start = match last_break {
+                Some(after_space) if after_space > row_start => after_space,
+                _ => index,
+            };
+            starts.push(row_start);
+        }
+        if ch.is_whitespace() {
+            last_break = Some(index + 1);
+        }
+    }
+    starts
+}
+
 fn render_settings_path_popup(frame: &mut ratatui::Frame, area: Rect, input_data: &TextInput) {
     let popup_area = centered_rect(70, 20, area);
     frame.render_widget(Clear, popup_area);
@@ -1920,20 +2839,26 @@ fn render_confirm_popup(frame: &mut ratatui::Frame, area: Rect, app: &App) {
     frame.render_widget(Clear, popup_area);
     let block = Block::default().borders(Borders::ALL).title("Confirm Change");
 
-    let message = if let Some(pending) = &app.pending_confirm {
-        match pending.action {
-            ConfirmAction::MoveVault => format!(
-                "Move vault data from:\n{}\n\nto:\n{}\n\nProceed?",
-                app.current_vault_path,
-                pending.target.to_string_lossy()
-            ),
-            ConfirmAction::SwitchVault => format!(
-                "Switch vault location to:\n{}\n\nProceed?",
-                pending.target.to_string_lossy()
-            ),
+    let message = match &app.pending_confirm {
+        Some(PendingConfirm::Vault { action, target, will_create }) => {
+            let created = if *will_create { " (will be created)" } else { "" };
+            match action {
+                ConfirmAction::MoveVault => format!(
+                    "Move vault data from:\n{}\n\nto:\n{}{created}\n\nProceed?",
+                    app.current_vault_path,
+                    target.to_string_lossy(),
+                ),
+                ConfirmAction::SwitchVault => format!(
+                    "Switch vault location to:\n{}{created}\n\nProceed?",
+                    target.to_string_lossy(),
+                ),
+            }
         }
-    } else {
-        "No pending action.".to_string()
+        Some(PendingConfirm::ClearInbox { count }) => format!(
+            "Remove all {count} inbox item(s) without capturing them?\n\n\
+             This cannot be undone.\n\nProceed?"
+        ),
+        None => "No pending action.".to_string(),
     };
 
     let chunks = Layout::default()
@@ -2004,7 +2929,12 @@ fn render_manual_capture_popup(frame: &mut ratatui::Frame, area: Rect, app: &App
 
 
 
-fn render_init_popup(frame: &mut ratatui::Frame, area: Rect, input_data: &TextInput) {
+fn render_init_popup(
+    frame: &mut ratatui::Frame,
+    area: Rect,
+    input_data: &TextInput,
+    status: Option<&str>,
+) {
     let popup_area = centered_rect(60, 20, area);
     frame.render_widget(Clear, popup_area);
     let block = Block::default().borders(Borders::ALL).title("Initialize SetupVault");
@@ -2034,36 +2964,78 @@ fn render_init_popup(frame: &mut ratatui::Frame, area: Rect, input_data: &TextIn
     let cx = chunks[1].x + 1 + (input_data.cursor as u16).min(chunks[1].width - 3);
     frame.set_cursor(cx, chunks[1].y + 1);
     
-    let help = Paragraph::new("Enter: Initialize | Esc: Reset")
-        .style(Style::default().fg(Color::DarkGray));
+    let help = match status {
+        Some(status) => Paragraph::new(format!("{status}\nEnter: Initialize | Esc: Reset"))
+            .style(Style::default().fg(Color::Red)),
+        None => Paragraph::new("Enter: Initialize | Esc: Reset")
+            .style(Style::default().fg(Color::DarkGray)),
+    };
     frame.render_widget(help, chunks[2]);
     
     frame.render_widget(block, popup_area);
 }
 
 fn render_guide_bar(frame: &mut ratatui::Frame, area: Rect, app: &App) {
-    let hints = get_key_hints(app);
-    let spans: Vec<Span> = hints
+    let mut block = Block::default().borders(Borders::ALL).title("Guide");
+    let latest = match (&app.error, &app.status) {
+        (Some(error), _) => {
+            Some(Span::styled(format!(" {error} "), Style::default().fg(Color::Red)))
+        }
+        (None, Some(status)) => Some(Span::raw(format!(" {status} "))),
+        (None, None) => None,
+    };
+    if let Some(latest) = latest {
+        block = block.title(Title::from(latest).alignment(Alignment::Right));
+    }
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    // Queue counts stay visible on every tab, but give way to the hints on narrow terminals.
+    let counts = format!(" Inbox: {} | Snoozed: {} ", app.inbox.len(), app.snoozed.len());
+    let counts_width = counts.chars().count() as u16;
+    let hints_area = if inner.width >= counts_width.saturating_mul(2) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(counts_width)])
+            .split(inner);
+        let style = if app.inbox.is_empty() {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::Red)
+        };
+        frame.render_widget(Paragraph::new(Span::styled(counts, style)), chunks[1]);
+        chunks[0]
+    } else {
+        inner
+    };
+
+    // Only whole hints are shown, so a cut never runs into the counts.
+    let mut room = usize::from(hints_area.width);
+    let spans: Vec<Span> = get_key_hints(app)
         .iter()
+        .map(|(key, desc)| (format!(" [{key}] "), format!("{desc}  ")))
+        .take_while(|(key, desc)| {
+            let width = key.chars().count() + desc.chars().count();
+            let fits = width <= room;
+            room = room.saturating_sub(width);
+            fits
+        })
         .flat_map(|(key, desc)| {
             vec![
-                Span::styled(format!(" [{}] ", key), Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
-                Span::raw(format!("{}  ", desc)),
+                Span::styled(key, Style::default().add_modifier(Modifier::BOLD).fg(Color::Cyan)),
+                Span::raw(desc),
             ]
         })
         .collect();
-    
-    let guide = Paragraph::new(Line::from(spans))
-        .block(Block::default().borders(Borders::ALL).title("Guide"));
-    frame.render_widget(guide, area);
+    frame.render_widget(Paragraph::new(Line::from(spans)), hints_area);
 }
 
 fn get_key_hints(app: &App) -> Vec<(&'static str, &'static str)> {
     if matches!(app.input_mode, InputMode::Init) {
         return vec![("Enter", "Initialize"), ("Esc", "Reset")];
     }
-    if matches!(app.input_mode, InputMode::Rationale) {
-        return vec![("Enter", "Submit"), ("Esc", "Cancel")];
+    if matches!(app.input_mode, InputMode::Rationale | InputMode::Notes) {
+        return vec![("Ctrl-S", "Submit"), ("Enter", "Newline"), ("Esc", "Cancel")];
     }
     if matches!(app.input_mode, InputMode::ManualCapture) {
         return vec![("Enter", "Next"), ("Esc", "Cancel")];
@@ -2071,26 +3043,41 @@ fn get_key_hints(app: &App) -> Vec<(&'static str, &'static str)> {
     if matches!(app.input_mode, InputMode::SettingsPath) {
         return vec![("Enter", "Save"), ("Esc", "Cancel")];
     }
+    if matches!(app.input_mode, InputMode::GotoId) {
+        return vec![("Enter", "Go"), ("Esc", "Cancel")];
+    }
     if matches!(app.input_mode, InputMode::Confirm) {
         return vec![("y", "Confirm"), ("n", "Cancel")];
     }
     if matches!(app.input_mode, InputMode::Palette) {
         return vec![("Enter", "Run"), ("Esc", "Close")];
     }
+    if matches!(app.input_mode, InputMode::Jump) {
+        return vec![("a-z", "Jump to title"), ("Esc", "Cancel")];
+    }
     if app.show_help {
         return vec![("?", "Close Help")];
     }
+    if app.show_drift {
+        return vec![("Esc", "Close Drift")];
+    }
+    if app.show_messages {
+        return vec![("Esc", "Close Messages")];
+    }
+    if app.error.is_some() {
+        return vec![("Esc", "Dismiss Error"), ("M", "Messages")];
+    }
 
     let mut hints = vec![("q", "Quit"), ("?", "Help"), ("p", "Cmds")];
 
     match app.tab {
         Tab::Dashboard => {
-            hints.extend_from_slice(&[("←/→", "Tabs"), ("r", "Refresh"), ("c", "Capture")]);
+            hints.extend_from_slice(&[("←/→", "Tabs"), ("r", "Refresh"), ("D", "Drift"), ("c", "Capture")]);
         }
         Tab::Inbox => {
             hints.extend_from_slice(&[("←/→", "Tabs"), ("h/l", "Src"), ("↑/↓", "Nav"), ("/", "Filter"), ("Space", "Select"), ("c", "Capture")]);
             if app.focus == Focus::List {
-                hints.extend_from_slice(&[("a", "Approve"), ("s", "Snooze"), ("d", "Ignore"), ("Enter", "Detail")]);
+                hints.extend_from_slice(&[("a", "Approve"), ("s", "Snooze"), ("d", "Ignore"), ("o", "Sort"), ("Enter", "Detail")]);
             } else {
                 hints.extend_from_slice(&[("Tab", "Focus List")]);
             }
@@ -2106,7 +3093,7 @@ fn get_key_hints(app: &App) -> Vec<(&'static str, &'static str)> {
         Tab::Library => {
             hints.extend_from_slice(&[("←/→", "Tabs"), ("h/l", "Src"), ("↑/↓", "Nav"), ("/", "Filter"), ("c", "Capture")]);
             if app.focus == Focus::List {
-                hints.extend_from_slice(&[("e", "Edit Rationale"), ("x", "Remove"), ("Enter", "Detail")]);
+                hints.extend_from_slice(&[("e", "Edit Rationale"), ("f", "Pin"), ("n", "Notes"), ("t", "Type"), ("o", "Recent"), ("i", "Installed?"), ("R", "Repair"), ("x", "Remove"), ("Enter", "Detail")]);
             } else {
                 hints.extend_from_slice(&[("Tab", "Focus List")]);
             }
@@ -2115,6 +3102,9 @@ fn get_key_hints(app: &App) -> Vec<(&'static str, &'static str)> {
             hints.extend_from_slice(&[("←/→", "Tabs"), ("e", "Edit Path"), ("m", "Move"), ("a", "Apply"), ("c", "Capture")]);
         }
     }
+    if matches!(app.tab, Tab::Inbox | Tab::Library | Tab::Snoozed) {
+        hints.push(("z", if app.zoomed { "Unzoom" } else { "Zoom" }));
+    }
     hints
 }
 
@@ -2190,37 +3180,25 @@ fn restore_terminal(mut terminal: Terminal<ratatui::backend::CrosstermBackend<St
 fn help_text(app: &App) -> String {
     match app.tab {
         Tab::Dashboard => {
-            "c: manual capture\nr: refresh inbox\nleft/right: switch tabs\np: command palette\nq: quit".into()
+            "c: manual capture\nr: refresh inbox\nD: drift report (detected vs library, cached until refresh)\nleft/right: switch tabs\np: command palette\nM: message history (Esc dismisses an error)\nq: quit".into()
         }
         Tab::Inbox => {
-            "a: accept\ns: snooze\nd: ignore\nspace: select\nc: manual capture\nr: refresh\np: command palette\n/: filter\nh/l: filter source\ntab: focus list/detail".into()
+            "a: accept\ns: snooze\nZ: snooze all in the current source\nd: ignore\nX: clear the whole inbox (asks first)\no: toggle oldest-first sort\n': jump to next title starting with a letter\nspace: select\nS: show only selected items\nc: manual capture\nr: refresh\np: command palette\nM: message history (Esc dismisses an error)\n/: filter\nh/l: filter source\ntab: focus list/detail\nz: zoom focused pane to full width".into()
         }
         Tab::Snoozed => {
-             "u: unsnooze\nx: remove\nc: manual capture\n↑/↓: navigate\nleft/right: switch tabs\ntab: focus list/detail".into()
+             "u: unsnooze\nx: remove\nspace: select\nS: show only selected items\n': jump to next title starting with a letter\nc: manual capture\n↑/↓: navigate\nleft/right: switch tabs\ntab: focus list/detail\nz: zoom focused pane to full width\nM: message history (Esc dismisses an error)".into()
         }
         Tab::Library => {
-            "e: edit rationale\nspace: select\nc: manual capture\np: command palette\n/: filter\nh/l: filter source\ntab: focus list/detail\nleft/right: switch tabs".into()
+            "e: edit rationale\nn: edit notes\nf: pin/unpin\nt: cycle type filter\no: toggle recently-used sort\ni: check the entry is still installed\nO: open the entry file\nR: rewrite entry file in canonical form\n': jump to next title starting with a letter\nspace: select\nS: show only selected items\nc: manual capture\np: command palette\nM: message history (Esc dismisses an error)\n/: filter\nh/l: filter source\ntab: focus list/detail\nz: zoom focused pane to full width\nleft/right: switch tabs".into()
         }
         Tab::Settings => {
-            "e: edit path\nm: apply & move\na: apply without move\nc: manual capture\nleft/right: switch tabs\np: command palette\nq: quit".into()
+            "e: edit path\nm: apply & move\na: apply without move\nc: manual capture\nleft/right: switch tabs\np: command palette\nM: message history (Esc dismisses an error)\nq: quit".into()
         }
     }
 }
 
 
 
-fn diff_changes(previous: &[DetectedChange], current: &[DetectedChange]) -> Vec<DetectedChange> {
-    let previous_keys: std::collections::HashSet<_> = previous
-        .iter()
-        .map(|change| (change.source.clone(), change.title.clone()))
-        .collect();
-    current
-        .iter()
-        .filter(|change| !previous_keys.contains(&(change.source.clone(), change.title.clone())))
-        .cloned()
-        .collect()
-}
-
 fn append_unique(target: &mut Vec<DetectedChange>, incoming: Vec<DetectedChange>) {
     let mut seen: std::collections::HashSet<_> = target
         .iter()
@@ -2254,8 +3232,11 @@ enum CommandAction {
     TabLibrary,
     TabSettings,
     Refresh,
+    DriftReport,
+    MessageHistory,
     Accept,
     Snooze,
+    SnoozeSource,
     Ignore,
     EditRationale,
     EditVaultPath,
@@ -2266,6 +3247,10 @@ enum CommandAction {
     ToggleHelp,
     Quit,
     Remove,
+    TogglePin,
+    RepairEntry,
+    CheckInstalled,
+    OpenEntry,
     Filter,
     SnoozeQuery,
     Unsnooze,
@@ -2276,6 +3261,8 @@ enum CommandAction {
     ToggleFocus,
     MoveTop,
     MoveBottom,
+    GotoId,
+    ClearInbox,
 }
 
 #[derive(Debug, Clone)]
@@ -2292,6 +3279,16 @@ fn build_commands() -> Vec<PaletteCommand> {
             description: "Run detectors and update inbox".into(),
             action: CommandAction::Refresh,
         },
+        PaletteCommand {
+            name: "Message History".into(),
+            description: "Show recent status messages and errors".into(),
+            action: CommandAction::MessageHistory,
+        },
+        PaletteCommand {
+            name: "Drift Report".into(),
+            description: "Compare detected items with the library".into(),
+            action: CommandAction::DriftReport,
+        },
         PaletteCommand {
             name: "Go to Dashboard".into(),
             description: "Switch to the dashboard tab".into(),
@@ -2362,6 +3359,26 @@ fn build_commands() -> Vec<PaletteCommand> {
             description: "Remove selected library entry".into(),
             action: CommandAction::Remove,
         },
+        PaletteCommand {
+            name: "Toggle Pin".into(),
+            description: "Pin or unpin selected library entry".into(),
+            action: CommandAction::TogglePin,
+        },
+        PaletteCommand {
+            name: "Repair Entry File".into(),
+            description: "Rewrite selected library entry's file in canonical form".into(),
+            action: CommandAction::RepairEntry,
+        },
+        PaletteCommand {
+            name: "Check Installed".into(),
+            description: "Re-run selected library entry's detector to see if it is still present".into(),
+            action: CommandAction::CheckInstalled,
+        },
+        PaletteCommand {
+            name: "Open Entry File".into(),
+            description: "Open selected library entry's file with the default application".into(),
+            action: CommandAction::OpenEntry,
+        },
         PaletteCommand {
             name: "Toggle Selection".into(),
             description: "Toggle selection checkbox".into(),
@@ -2377,6 +3394,21 @@ fn build_commands() -> Vec<PaletteCommand> {
             description: "Filter list items".into(),
             action: CommandAction::Filter,
         },
+        PaletteCommand {
+            name: "Snooze All in Source".into(),
+            description: "Snooze every inbox item in the current source tab".into(),
+            action: CommandAction::SnoozeSource,
+        },
+        PaletteCommand {
+            name: "Go to Entry by Id".into(),
+            description: "Select the library entry with a full or prefix id".into(),
+            action: CommandAction::GotoId,
+        },
+        PaletteCommand {
+            name: "Clear Inbox".into(),
+            description: "Remove every inbox item after confirmation".into(),
+            action: CommandAction::ClearInbox,
+        },
         PaletteCommand {
             name: "Snooze by Query".into(),
             description: "Snooze inbox items matching a query".into(),
@@ -2439,6 +3471,12 @@ fn filtered_commands(app: &App) -> Vec<PaletteCommand> {
                 CommandAction::SnoozeQuery => {
                     app.tab == Tab::Inbox
                 }
+                CommandAction::ClearInbox => {
+                    app.tab == Tab::Inbox && !app.inbox.is_empty()
+                }
+                CommandAction::SnoozeSource => {
+                    app.tab == Tab::Inbox && app.current_inbox_source().is_some()
+                }
                 CommandAction::Accept | CommandAction::Snooze | CommandAction::Ignore => {
                     app.tab == Tab::Inbox && app.focus == Focus::List
                 }
@@ -2448,7 +3486,11 @@ fn filtered_commands(app: &App) -> Vec<PaletteCommand> {
                 CommandAction::Unsnooze => {
                     app.tab == Tab::Snoozed && app.focus == Focus::List
                 }
-                CommandAction::EditRationale => {
+                CommandAction::EditRationale
+                | CommandAction::TogglePin
+                | CommandAction::RepairEntry
+                | CommandAction::CheckInstalled
+                | CommandAction::OpenEntry => {
                     app.tab == Tab::Library && app.focus == Focus::List
                 }
                 CommandAction::EditVaultPath
@@ -2460,6 +3502,7 @@ fn filtered_commands(app: &App) -> Vec<PaletteCommand> {
                 CommandAction::Refresh => {
                     matches!(app.tab, Tab::Dashboard | Tab::Inbox)
                 }
+                CommandAction::DriftReport | CommandAction::MessageHistory => true,
                 CommandAction::ToggleSelection => {
                     matches!(app.tab, Tab::Inbox | Tab::Library | Tab::Snoozed)
                 }
@@ -2520,8 +3563,11 @@ fn execute_command(vault: &FsVault, app: &mut App, action: CommandAction) -> Res
         CommandAction::TabLibrary => app.tab = Tab::Library,
         CommandAction::TabSettings => app.tab = Tab::Settings,
         CommandAction::Refresh => handle_refresh(vault, app)?,
+        CommandAction::DriftReport => open_drift_report(vault, app)?,
+        CommandAction::MessageHistory => app.show_messages = true,
         CommandAction::Accept => handle_accept(app),
         CommandAction::Snooze => handle_snooze(vault, app)?,
+        CommandAction::SnoozeSource => handle_snooze_source(vault, app)?,
         CommandAction::Ignore => handle_ignore(vault, app)?,
         CommandAction::EditRationale => handle_edit_rationale(app),
         CommandAction::EditVaultPath => {
@@ -2544,6 +3590,10 @@ fn execute_command(vault: &FsVault, app: &mut App, action: CommandAction) -> Res
         CommandAction::ToggleHelp => app.show_help = !app.show_help,
         CommandAction::Quit => app.status = Some("Use q to quit".into()),
         CommandAction::Remove => handle_remove(vault, app)?,
+        CommandAction::TogglePin => handle_toggle_pin(vault, app)?,
+        CommandAction::RepairEntry => handle_repair(vault, app)?,
+        CommandAction::CheckInstalled => handle_check_installed(app)?,
+        CommandAction::OpenEntry => handle_open_entry(vault, app)?,
         CommandAction::Filter => {
              if matches!(app.tab, Tab::Inbox | Tab::Library | Tab::Snoozed) {
                  app.input_mode = InputMode::Filter;
@@ -2559,6 +3609,11 @@ fn execute_command(vault: &FsVault, app: &mut App, action: CommandAction) -> Res
                   app.input.reset();
              }
         }
+        CommandAction::GotoId => {
+            app.input_mode = InputMode::GotoId;
+            app.input.reset();
+        }
+        CommandAction::ClearInbox => confirm_clear_inbox(app),
         CommandAction::Unsnooze => handle_unsnooze(vault, app)?,
         CommandAction::ClearFilter => {
             app.active_filter = None;
@@ -2597,120 +3652,351 @@ fn execute_command(vault: &FsVault, app: &mut App, action: CommandAction) -> Res
     Ok(())
 }
 
-fn handle_remove(vault: &FsVault, app: &mut App) -> Result<()> {
-    if app.tab == Tab::Library {
-        let ids_to_remove: Vec<uuid::Uuid> = if !app.selected_library.is_empty() {
-            app.selected_library.iter().cloned().collect()
-        } else {
-            current_library_id(app).into_iter().collect()
-        };
-
-        if ids_to_remove.is_empty() {
-            return Ok(());
-        }
-
-        for id in &ids_to_remove {
-            vault.restore_to_inbox(*id)?;
-            if let Some(real_index) = app.library.iter().position(|e| e.id == *id) {
-                app.library.remove(real_index);
-            }
-        }
-
-        app.inbox = vault.load_inbox().unwrap_or_default();
-        app.selected_library.clear();
-        app.status = Some(format!("Removed {} item(s) and restored to inbox", ids_to_remove.len()));
+fn toggle_selected_only(app: &mut App) {
+    let Some(count) = app.current_selection().map(HashSet::len) else {
+        return;
+    };
+    if count == 0 {
+        app.status = Some("No items selected; press space to select".into());
+        return;
+    }
+    app.show_selected_only = !app.show_selected_only;
+    app.status = Some(if app.show_selected_only {
+        format!("Showing {count} selected item(s)")
+    } else {
+        "Showing all items".into()
+    });
+    match app.tab {
+        Tab::Inbox => app.inbox_state.select(Some(0)),
+        Tab::Library => app.library_state.select(Some(0)),
+        Tab::Snoozed => app.snoozed_state.select(Some(0)),
+        Tab::Dashboard | Tab::Settings => {}
+    }
+}
 
-        // Adjust selection
-        let filtered_len = app.filtered_library().len();
-        if let Some(selected) = app.library_state.selected() {
-             if selected >= filtered_len && filtered_len > 0 {
-                app.library_state.select(Some(filtered_len - 1));
-            } else if filtered_len == 0 {
-                app.library_state.select(None);
-            }
-        }
-    } else if app.tab == Tab::Snoozed {
-        let ids_to_remove: Vec<uuid::Uuid> = if !app.selected_snoozed.is_empty() {
-            app.selected_snoozed.iter().cloned().collect()
-        } else {
-            current_snoozed_id(app).into_iter().collect()
-        };
+fn toggle_library_sort(app: &mut App) {
+    let selected = current_library_id(app);
+    app.library_sort = match app.library_sort {
+        LibrarySort::Stored => LibrarySort::RecentlyUsed,
+        LibrarySort::RecentlyUsed => LibrarySort::Stored,
+    };
+    app.status = Some(match app.library_sort {
+        LibrarySort::Stored => "Library in stored order".into(),
+        LibrarySort::RecentlyUsed => "Library sorted by recent use".into(),
+    });
+    if let Some(index) = selected
+        .and_then(|id| app.filtered_library().iter().position(|entry| entry.id == id))
+    {
+        app.library_state.select(Some(index));
+    }
+}
 
-        if ids_to_remove.is_empty() {
-            return Ok(());
-        }
+/// Log that the selected library entry was opened in the detail pane.
+///
+/// Usage tracking is best-effort, so a failed write is not surfaced.
+fn record_library_access(vault: &FsVault, app: &mut App) {
+    let Some(id) = current_library_id(app) else {
+        return;
+    };
+    if vault.record_access(id).is_ok() {
+        app.access_times.insert(id, chrono::Utc::now());
+    }
+}
 
-        for id in &ids_to_remove {
-            vault.remove_snoozed_item(*id)?;
-            if let Some(pos) = app.snoozed.iter().position(|item| item.id == *id) {
-                app.snoozed.remove(pos);
-            }
+fn toggle_inbox_sort(app: &mut App) {
+    if app.tab != Tab::Inbox {
+        return;
+    }
+    let selected = current_inbox_id(app);
+    app.inbox_sort = match app.inbox_sort {
+        InboxSort::Detected => InboxSort::OldestFirst,
+        InboxSort::OldestFirst => InboxSort::Detected,
+    };
+    app.status = Some(match app.inbox_sort {
+        InboxSort::Detected if app.inbox_order == InboxOrder::Insertion => {
+            "Inbox in detection order".into()
         }
+        InboxSort::Detected => format!("Inbox in configured {} order", app.inbox_order.as_str()),
+        InboxSort::OldestFirst => "Inbox sorted oldest first".into(),
+    });
 
-        app.selected_snoozed.clear();
-        app.status = Some(format!("Removed {} snoozed item(s)", ids_to_remove.len()));
-
-        let len = app.filtered_snoozed().len();
-        if let Some(selected) = app.snoozed_state.selected() {
-            if selected >= len && len > 0 {
-                app.snoozed_state.select(Some(len - 1));
-            } else if len == 0 {
-                app.snoozed_state.select(None);
-            }
-        }
+    // Keep the cursor on the same item in the re-sorted view.
+    if let Some(index) = selected
+        .and_then(|id| app.filtered_inbox().iter().position(|item| item.id == id))
+    {
+        app.inbox_state.select(Some(index));
     }
-    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use ratatui::backend::TestBackend;
-    use sv_core::{EntryType, SystemInfo, Tag};
+/// Icon and friendly label for known sources; unknown sources are shown verbatim.
+const SOURCE_LABELS: &[(&str, &str, &str)] = &[
+    ("homebrew", "🍺", "Homebrew"),
+    ("brew_services", "🍺", "Homebrew Services"),
+    ("npm", "📦", "npm"),
+    ("pnpm", "📦", "pnpm"),
+    ("yarn", "🧶", "Yarn"),
+    ("cargo", "🦀", "Cargo"),
+    ("pip", "🐍", "pip"),
+    ("docker", "🐳", "Docker"),
+    ("dotfiles", "📄", "Dotfiles"),
+    ("env", "🔧", "Environment"),
+    ("tmux", "🪟", "tmux"),
+    ("mac_defaults", "🍎", "macOS Defaults"),
+    ("applications", "💻", "Applications"),
+    ("apt", "📦", "APT"),
+    ("dnf", "📦", "DNF"),
+    ("yum", "📦", "YUM"),
+    ("pacman", "📦", "pacman"),
+    ("flatpak", "📦", "Flatpak"),
+    ("snap", "📦", "Snap"),
+    ("winget", "📦", "winget"),
+    ("msstore", "🛍", "Microsoft Store"),
+    ("uwp", "🛍", "Store Apps"),
+    ("chocolatey", "🍫", "Chocolatey"),
+    ("scoop", "🍨", "Scoop"),
+    ("startup", "🚀", "Startup Programs"),
+    ("manual", "📝", "Manual"),
+];
+
+/// Display label for a source, with its icon unless `plain` is set.
+fn source_display(source: &str, plain: bool) -> String {
+    match SOURCE_LABELS.iter().find(|(name, _, _)| *name == source) {
+        Some((_, _, label)) if plain => (*label).to_string(),
+        Some((_, icon, label)) => format!("{icon} {label}"),
+        None => source.to_string(),
+    }
+}
 
-    #[test]
-    fn render_snapshot() {
-        let backend = TestBackend::new(60, 20);
-        let mut terminal = Terminal::new(backend).expect("terminal");
-        let mut app = App::new();
-        app.tab = Tab::Inbox;
-        app.inbox = vec![DetectedChange {
-            id: uuid::Uuid::new_v4(),
-            path: None,
-            title: "jq".into(),
-            entry_type: EntryType::Package,
-            source: "homebrew".into(),
-            cmd: "brew install jq".into(),
-            system: SystemInfo {
-                os: "macos".into(),
-                arch: "arm64".into(),
-            },
-            detected_at: chrono::Utc::now(),
-            tags: vec![Tag::new("cli").unwrap()],
-        }];
-        app.inbox_state.select(Some(0));
+/// Prefix a list row title with its source icon, or leave it bare when `plain` is set.
+fn with_source_icon(title: &str, source: &str, plain: bool) -> String {
+    match SOURCE_LABELS.iter().find(|(name, _, _)| *name == source) {
+        Some((_, icon, _)) if !plain => format!("{icon} {title}"),
+        _ => title.to_string(),
+    }
+}
 
-        terminal
-            .draw(|frame| render_app(frame, &app))
-            .expect("render");
+/// Move the selection to the next item whose title starts with `letter`, wrapping around.
+fn jump_to_letter(app: &mut App, letter: char) {
+    let (titles, state): (Vec<&str>, _) = match app.tab {
+        Tab::Inbox => (
+            app.filtered_inbox().iter().map(|item| item.title.as_str()).collect(),
+            app.inbox_state.clone(),
+        ),
+        Tab::Library => (
+            app.filtered_library().iter().map(|entry| entry.title.as_str()).collect(),
+            app.library_state.clone(),
+        ),
+        Tab::Snoozed => (
+            app.filtered_snoozed().iter().map(|item| item.title.as_str()).collect(),
+            app.snoozed_state.clone(),
+        ),
+        Tab::Dashboard | Tab::Settings => return,
+    };
+    let Some(index) = next_title_match(&titles, state.selected(), letter) else {
+        app.status = Some(format!("No title starts with '{letter}'"));
+        return;
+    };
+    match app.tab {
+        Tab::Inbox => app.inbox_state.select(Some(index)),
+        Tab::Library => app.library_state.select(Some(index)),
+        Tab::Snoozed => app.snoozed_state.select(Some(index)),
+        Tab::Dashboard | Tab::Settings => {}
+    }
+}
 
-        let buffer = terminal.backend().buffer();
-        let snapshot = buffer_to_string(buffer);
-        insta::assert_snapshot!(snapshot);
+/// Index of the first title after `current` starting with `letter` (case-insensitive), wrapping.
+fn next_title_match(titles: &[&str], current: Option<usize>, letter: char) -> Option<usize> {
+    let start = current.map_or(0, |index| index + 1);
+    (0..titles.len())
+        .map(|offset| (start + offset) % titles.len())
+        .find(|&index| {
+            titles[index]
+                .chars()
+                .next()
+                .is_some_and(|first| first.to_lowercase().eq(letter.to_lowercase()))
+        })
+}
+
+/// Render how long ago a change was detected, e.g. `3d ago`.
+fn humanize_age(
+    detected_at: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let age = now.signed_duration_since(detected_at);
+    if age.num_days() > 0 {
+        format!("{}d ago", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h ago", age.num_hours())
+    } else if age.num_minutes() > 0 {
+        format!("{}m ago", age.num_minutes())
+    } else {
+        "just now".into()
     }
+}
 
-    fn buffer_to_string(buffer: &ratatui::buffer::Buffer) -> String {
-        let mut lines = Vec::new();
-        for y in 0..buffer.area.height {
-            let mut line = String::new();
-            for x in 0..buffer.area.width {
-                let cell = buffer.get(x, y);
-                line.push_str(cell.symbol());
+fn handle_toggle_pin(vault: &FsVault, app: &mut App) -> Result<()> {
+    if app.tab != Tab::Library {
+        return Ok(());
+    }
+    let Some(id) = current_library_id(app) else {
+        return Ok(());
+    };
+    let Some(entry) = app.library.iter_mut().find(|entry| entry.id == id) else {
+        return Ok(());
+    };
+    entry.pinned = !entry.pinned;
+    vault.update(entry).context("failed to update entry")?;
+    app.status = Some(if entry.pinned {
+        format!("Pinned {}", entry.title)
+    } else {
+        format!("Unpinned {}", entry.title)
+    });
+
+    // Keep the cursor on the entry after it moves between the pinned and unpinned groups.
+    if let Some(index) = app.filtered_library().iter().position(|entry| entry.id == id) {
+        app.library_state.select(Some(index));
+    }
+    Ok(())
+}
+
+fn handle_repair(vault: &FsVault, app: &mut App) -> Result<()> {
+    if app.tab != Tab::Library {
+        return Ok(());
+    }
+    let Some(id) = current_library_id(app) else {
+        return Ok(());
+    };
+    app.status = Some(match vault.repair_entry(id).context("failed to repair entry")? {
+        Some(true) => "Rewrote entry file in canonical form".into(),
+        Some(false) => "Entry file already canonical".into(),
+        None => "Entry file not found".into(),
+    });
+    Ok(())
+}
+
+fn handle_check_installed(app: &mut App) -> Result<()> {
+    if app.tab != Tab::Library {
+        return Ok(());
+    }
+    let Some(entry) = current_library_id(app)
+        .and_then(|id| app.library.iter().find(|entry| entry.id == id))
+    else {
+        return Ok(());
+    };
+    if app.no_detectors {
+        app.status = Some(DETECTORS_OFF_STATUS.into());
+        return Ok(());
+    }
+    let config = load_config().context("failed to load config")?;
+    let detectors = configured_detectors(&config.detector_config());
+    let check = check_presence(&detectors, &entry.source, &entry.title)
+        .context("failed to run detector")?;
+    app.status = Some(presence_status(&entry.title, &entry.source, check));
+    Ok(())
+}
+
+/// Open the selected library entry's file with the system's default application.
+fn handle_open_entry(vault: &FsVault, app: &mut App) -> Result<()> {
+    let Some(id) = current_library_id(app) else {
+        return Ok(());
+    };
+    let Some(path) = vault.entry_file(id)? else {
+        app.report_error("Entry file not found; it may have been removed".into());
+        return Ok(());
+    };
+    match sv_utils::open_path(&path.to_string_lossy()) {
+        Ok(()) => app.status = Some(format!("Opened {}", path.display())),
+        Err(err) => app.report_error(format!("Failed to open {}: {err}", path.display())),
+    }
+    Ok(())
+}
+
+fn presence_status(title: &str, source: &str, check: PresenceCheck) -> String {
+    match check {
+        PresenceCheck::StillInstalled(detector) => format!("{title}: still installed ({detector})"),
+        PresenceCheck::NoLongerDetected(detector) => {
+            format!("{title}: no longer detected ({detector})")
+        }
+        PresenceCheck::Unavailable => format!("Detector for {source} is not available here"),
+        PresenceCheck::NoDetector => format!("No detector reports source {source}"),
+    }
+}
+
+fn handle_remove(vault: &FsVault, app: &mut App) -> Result<()> {
+    if app.tab == Tab::Library {
+        let ids_to_remove: Vec<uuid::Uuid> = if !app.selected_library.is_empty() {
+            app.selected_library.iter().cloned().collect()
+        } else {
+            current_library_id(app).into_iter().collect()
+        };
+
+        if ids_to_remove.is_empty() {
+            return Ok(());
+        }
+
+        let mut restored = 0;
+        for id in &ids_to_remove {
+            if !vault.restore_to_inbox(*id)? {
+                continue;
+            }
+            restored += 1;
+            if let Some(real_index) = app.library.iter().position(|e| e.id == *id) {
+                app.library.remove(real_index);
+            }
+        }
+        app.refresh_weak_rationales();
+
+        app.inbox = vault.load_inbox().unwrap_or_default();
+        app.selected_library.clear();
+        let skipped = ids_to_remove.len() - restored;
+        app.status = Some(if skipped == 0 {
+            format!("Removed {restored} item(s) and restored to inbox")
+        } else {
+            format!(
+                "Removed {restored} item(s) and restored to inbox; kept {skipped} already queued"
+            )
+        });
+
+        // Adjust selection
+        let filtered_len = app.filtered_library().len();
+        if let Some(selected) = app.library_state.selected() {
+             if selected >= filtered_len && filtered_len > 0 {
+                app.library_state.select(Some(filtered_len - 1));
+            } else if filtered_len == 0 {
+                app.library_state.select(None);
+            }
+        }
+    } else if app.tab == Tab::Snoozed {
+        let ids_to_remove: Vec<uuid::Uuid> = if !app.selected_snoozed.is_empty() {
+            app.selected_snoozed.iter().cloned().collect()
+        } else {
+            current_snoozed_id(app).into_iter().collect()
+        };
+
+        if ids_to_remove.is_empty() {
+            return Ok(());
+        }
+
+        for id in &ids_to_remove {
+            vault.remove_snoozed_item(*id)?;
+            if let Some(pos) = app.snoozed.iter().position(|item| item.id == *id) {
+                app.snoozed.remove(pos);
+            }
+        }
+
+        app.selected_snoozed.clear();
+        app.status = Some(format!("Removed {} snoozed item(s)", ids_to_remove.len()));
+
+        let len = app.filtered_snoozed().len();
+        if let Some(selected) = app.snoozed_state.selected() {
+            if selected >= len && len > 0 {
+                app.snoozed_state.select(Some(len - 1));
+            } else if len == 0 {
+                app.snoozed_state.select(None);
             }
-            lines.push(line.trim_end().to_string());
         }
-        lines.join("\n")
     }
+    Ok(())
 }
 
 fn handle_snooze_query(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Result<bool> {
@@ -2723,8 +4009,7 @@ fn handle_snooze_query(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Res
             let query = app.input.content.to_lowercase();
             if !query.is_empty() {
                 let to_snooze: Vec<_> = app.inbox.iter()
-                    .filter(|item| item.title.to_lowercase().contains(&query) 
-                                || item.source.to_lowercase().contains(&query))
+                    .filter(|item| item.matches(&query))
                     .map(|item| item.id)
                     .collect();
 
@@ -2733,6 +4018,7 @@ fn handle_snooze_query(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Res
                     vault.snooze_inbox_item(id)?;
                     app.inbox.retain(|item| item.id != id);
                 }
+                app.snoozed = vault.load_snoozed().unwrap_or_default();
                 app.status = Some(format!("Snoozed {} items matching '{}'", count, query));
             }
             app.input_mode = InputMode::None;
@@ -2749,6 +4035,83 @@ fn handle_snooze_query(vault: &mut FsVault, app: &mut App, key: KeyEvent) -> Res
     Ok(false)
 }
 
+fn handle_goto_id_input(app: &mut App, key: KeyEvent) -> Result<bool> {
+    match key.code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::None;
+            app.input.reset();
+        }
+        KeyCode::Enter => {
+            let prefix = app.input.content.trim().to_string();
+            app.input_mode = InputMode::None;
+            app.input.reset();
+            if !prefix.is_empty() {
+                goto_entry(app, &prefix);
+            }
+        }
+        KeyCode::Char(c) => app.input.insert(c),
+        KeyCode::Backspace => app.input.delete_back(),
+        KeyCode::Left => app.input.move_left(),
+        KeyCode::Right => app.input.move_right(),
+        KeyCode::Home => app.input.move_home(),
+        KeyCode::End => app.input.move_end(),
+        _ => {}
+    }
+    Ok(false)
+}
+
+/// Most candidates listed in the status when an id prefix is ambiguous.
+const GOTO_CANDIDATE_LIMIT: usize = 5;
+
+/// Select the library entry whose id starts with `prefix`, switching to the Library tab and
+/// widening its filters so the entry is visible; ambiguous prefixes list candidates instead.
+fn goto_entry(app: &mut App, prefix: &str) {
+    let candidates = entries_with_id_prefix(&app.library, prefix);
+    let id = match candidates.as_slice() {
+        [] => {
+            app.status = Some(format!("No entry id starts with '{prefix}'"));
+            return;
+        }
+        [entry] => entry.id,
+        _ => {
+            let shown: Vec<String> = candidates
+                .iter()
+                .take(GOTO_CANDIDATE_LIMIT)
+                .map(|entry| format!("{} {}", &entry.id.to_string()[..8], entry.title))
+                .collect();
+            let more = candidates.len().saturating_sub(GOTO_CANDIDATE_LIMIT);
+            let suffix = if more > 0 { format!(", {more} more") } else { String::new() };
+            app.status = Some(format!(
+                "'{prefix}' matches {} entries: {}{suffix}",
+                candidates.len(),
+                shown.join(", ")
+            ));
+            return;
+        }
+    };
+
+    app.tab = Tab::Library;
+    app.focus = Focus::List;
+    app.library_source_index = 0;
+    if !app.filtered_library().iter().any(|entry| entry.id == id) {
+        app.library_type_filter = None;
+        app.active_filter = None;
+        app.show_selected_only = false;
+    }
+    let index = app.filtered_library().iter().position(|entry| entry.id == id);
+    app.library_state.select(index);
+}
+
+/// Entries whose id starts with `prefix`, compared case-insensitively against the hyphenated
+/// form so both a full id and a leading fragment match.
+fn entries_with_id_prefix<'a>(library: &'a [Entry], prefix: &str) -> Vec<&'a Entry> {
+    let prefix = prefix.to_lowercase();
+    library
+        .iter()
+        .filter(|entry| entry.id.to_string().starts_with(&prefix))
+        .collect()
+}
+
 fn render_snooze_popup(frame: &mut ratatui::Frame, area: Rect, input_data: &TextInput) {
     let popup_area = centered_rect(60, 20, area);
     frame.render_widget(Clear, popup_area);
@@ -2763,3 +4126,765 @@ fn render_snooze_popup(frame: &mut ratatui::Frame, area: Rect, input_data: &Text
     let cy = popup_area.y + 1;
     frame.set_cursor(cx, cy);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use sv_core::{DetectedChangeBuilder, EntryBuilder, EntryType, SystemInfo, Tag};
+    use tempfile::TempDir;
+
+    /// An initialized vault in a temporary directory, removed when the returned guard drops.
+    fn temp_vault() -> (TempDir, FsVault) {
+        let temp = TempDir::new().expect("temp dir");
+        let vault = FsVault::new(temp.path().to_path_buf());
+        vault.init().expect("init vault");
+        (temp, vault)
+    }
+
+    /// A homebrew package entry with a `daily tool` rationale; tests override what they vary.
+    fn sample_entry(title: &str) -> EntryBuilder {
+        Entry::builder(uuid::Uuid::new_v4(), title)
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd(format!("brew install {title}"))
+            .system(SystemInfo {
+                os: "macos".into(),
+                arch: "arm64".into(),
+                hostname: None,
+            })
+            .rationale(Rationale::new("daily tool").unwrap())
+    }
+
+    /// A homebrew package detection; tests override what they vary.
+    fn sample_change(title: &str) -> DetectedChangeBuilder {
+        DetectedChange::builder(uuid::Uuid::new_v4(), title)
+            .entry_type(EntryType::Package)
+            .source("homebrew")
+            .cmd(format!("brew install {title}"))
+            .system(SystemInfo {
+                os: "macos".into(),
+                arch: "arm64".into(),
+                hostname: None,
+            })
+    }
+
+    #[test]
+    fn render_snapshot() {
+        let backend = TestBackend::new(60, 20);
+        let mut terminal = Terminal::new(backend).expect("terminal");
+        let mut app = App::new();
+        app.tab = Tab::Inbox;
+        app.inbox = vec![DetectedChange {
+            id: uuid::Uuid::new_v4(),
+            path: None,
+            title: "jq".into(),
+            entry_type: EntryType::Package,
+            source: "homebrew".into(),
+            cmd: "brew install jq".into(),
+            system: SystemInfo {
+                os: "macos".into(),
+                arch: "arm64".into(),
+                hostname: None,
+            },
+            detected_at: chrono::Utc::now(),
+            tags: vec![Tag::new("cli").unwrap()],
+            detector: None,
+        }];
+        app.inbox_state.select(Some(0));
+
+        terminal
+            .draw(|frame| render_app(frame, &app))
+            .expect("render");
+
+        let buffer = terminal.backend().buffer();
+        let snapshot = buffer_to_string(buffer);
+        insta::assert_snapshot!(snapshot);
+    }
+
+    #[test]
+    fn inbox_sort_surfaces_oldest_first_within_source_filter() {
+        let now = chrono::Utc::now();
+        let change = |title: &str, source: &str, days: i64| {
+            sample_change(title)
+                .source(source)
+                .detected_at(now - chrono::Duration::days(days))
+                .build()
+                .unwrap()
+        };
+        let mut app = App::new();
+        app.tab = Tab::Inbox;
+        app.inbox = vec![
+            change("jq", "homebrew", 1),
+            change("serde", "cargo", 30),
+            change("ripgrep", "homebrew", 9),
+        ];
+        app.inbox_source_index = app
+            .available_sources()
+            .iter()
+            .position(|source| source == "homebrew")
+            .expect("homebrew source");
+        app.inbox_state.select(Some(0));
+
+        toggle_inbox_sort(&mut app);
+        let titles: Vec<_> = app.filtered_inbox().iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, ["ripgrep", "jq"]);
+        assert_eq!(current_inbox_id(&app), Some(app.inbox[0].id));
+
+        assert_eq!(humanize_age(now - chrono::Duration::days(9), now), "9d ago");
+        assert_eq!(humanize_age(now - chrono::Duration::minutes(5), now), "5m ago");
+        assert_eq!(humanize_age(now, now), "just now");
+    }
+
+    #[test]
+    fn tag_rendering_truncates_rows_and_skips_empty_lists() {
+        let tags: Vec<Tag> = ["cli", "json", "daily"]
+            .into_iter()
+            .map(|tag| Tag::new(tag).unwrap())
+            .collect();
+        let row = with_tag_suffix("jq".into(), &tags);
+        let text: String = row.spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(text, "jq #cli #json +1");
+
+        assert!(tags_line(&[]).is_none());
+        assert_eq!(with_tag_suffix("jq".into(), &[]).spans.len(), 1);
+        let line = tags_line(&tags).expect("tags line");
+        assert_eq!(line.spans[0].content, "Tags: cli, json, daily");
+    }
+
+    #[test]
+    fn library_type_filter_cycles_and_composes_with_source() {
+        let entry = |title: &str, source: &str, entry_type: EntryType| {
+            sample_entry(title).source(source).entry_type(entry_type).build().unwrap()
+        };
+        let mut app = App::new();
+        app.tab = Tab::Library;
+        app.library = vec![
+            entry("jq", "homebrew", EntryType::Package),
+            entry("iterm2", "homebrew", EntryType::Application),
+            entry("serde", "cargo", EntryType::Package),
+        ];
+        let titles = |app: &App| -> Vec<String> {
+            app.filtered_library().iter().map(|entry| entry.title.clone()).collect()
+        };
+
+        app.cycle_library_type();
+        assert_eq!(app.library_type_filter, Some(EntryType::Package));
+        assert_eq!(titles(&app), ["jq", "serde"]);
+
+        app.library_source_index = app
+            .available_library_sources()
+            .iter()
+            .position(|source| source == "homebrew")
+            .expect("homebrew source");
+        assert_eq!(titles(&app), ["jq"]);
+
+        app.cycle_library_type();
+        app.cycle_library_type();
+        assert_eq!(app.library_type_filter, Some(EntryType::Application));
+        assert_eq!(titles(&app), ["iterm2"]);
+
+        for _ in 0..3 {
+            app.cycle_library_type();
+        }
+        assert_eq!(app.library_type_filter, None);
+        assert_eq!(titles(&app), ["jq", "iterm2"]);
+    }
+
+    #[test]
+    fn long_lists_render_only_the_visible_window() {
+        let area = Rect::new(0, 0, 40, 12);
+        let mut state = ListState::default();
+        state.select(Some(4_000));
+        let (window, window_state) = visible_window(&state, 5_000, area);
+        assert_eq!(window, 3_991..4_001);
+        assert_eq!(window_state.selected(), Some(9));
+
+        state.select(Some(3));
+        let (window, window_state) = visible_window(&state, 5_000, area);
+        assert_eq!(window, 0..10);
+        assert_eq!(window_state.selected(), Some(3));
+
+        // A stale selection past the end clamps to the last item.
+        state.select(Some(50));
+        let (window, window_state) = visible_window(&state, 4, area);
+        assert_eq!(window, 0..4);
+        assert_eq!(window_state.selected(), Some(3));
+
+        let (window, window_state) = visible_window(&state, 0, area);
+        assert!(window.is_empty());
+        assert_eq!(window_state.selected(), None);
+
+        let backend = TestBackend::new(80, 20);
+        let mut terminal = Terminal::new(backend).expect("terminal");
+        let mut app = App::new();
+        app.tab = Tab::Inbox;
+        app.inbox = (0..5_000)
+            .map(|index| sample_change(&format!("pkg-{index:04}")).build().unwrap())
+            .collect();
+        app.inbox_state.select(Some(4_321));
+        terminal
+            .draw(|frame| render_app(frame, &app))
+            .expect("render");
+        let screen = buffer_to_string(terminal.backend().buffer());
+        assert!(screen.contains("pkg-4321"));
+        assert!(!screen.contains("pkg-0000"));
+        assert_eq!(current_inbox_id(&app), Some(app.inbox[4_321].id));
+    }
+
+    #[test]
+    fn library_recent_sort_puts_never_used_entries_last() {
+        let entry = |title: &str| sample_entry(title).build().unwrap();
+        let now = chrono::Utc::now();
+        let mut app = App::new();
+        app.tab = Tab::Library;
+        app.library = vec![entry("jq"), entry("fd"), entry("ripgrep"), entry("bat")];
+        app.library[3].pinned = true;
+        app.access_times.insert(app.library[1].id, now - chrono::Duration::days(2));
+        app.access_times.insert(app.library[2].id, now);
+        app.library_state.select(Some(1));
+
+        toggle_library_sort(&mut app);
+        let titles: Vec<_> = app.filtered_library().iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["bat", "ripgrep", "fd", "jq"]);
+        assert_eq!(current_library_id(&app), Some(app.library[0].id));
+
+        toggle_library_sort(&mut app);
+        let titles: Vec<_> = app.filtered_library().iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["bat", "jq", "fd", "ripgrep"]);
+    }
+
+    #[test]
+    fn zoom_shows_only_the_focused_pane() {
+        let mut app = App::new();
+        app.tab = Tab::Inbox;
+        app.inbox = vec![sample_change("jq").build().unwrap()];
+        app.inbox_state.select(Some(0));
+        let render = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(80, 20)).expect("terminal");
+            terminal.draw(|frame| render_app(frame, app)).expect("render");
+            buffer_to_string(terminal.backend().buffer())
+        };
+
+        let split = render(&app);
+        assert!(split.contains("[ ] ") && split.contains("Cmd: brew"));
+
+        app.zoomed = true;
+        assert!(get_key_hints(&app).contains(&("z", "Unzoom")));
+        let list_only = render(&app);
+        assert!(list_only.contains("[ ] ") && !list_only.contains("Cmd: brew"));
+
+        app.toggle_focus();
+        let detail_only = render(&app);
+        assert!(!detail_only.contains("[ ] ") && detail_only.contains("Cmd: brew"));
+
+        app.zoomed = false;
+        assert!(render(&app).contains("Cmd: brew"));
+        assert!(get_key_hints(&app).contains(&("z", "Zoom")));
+    }
+
+    #[test]
+    fn show_selected_only_narrows_the_list_until_selection_clears() {
+        let (_temp, mut vault) = temp_vault();
+        let mut app = App::new();
+        app.tab = Tab::Inbox;
+        app.inbox = ["jq", "ripgrep", "fd"]
+            .into_iter()
+            .map(|title| sample_change(title).build().unwrap())
+            .collect();
+        app.inbox_state.select(Some(0));
+        let press = |vault: &mut FsVault, app: &mut App, code| {
+            handle_key(vault, app, KeyEvent::new(code, KeyModifiers::NONE)).expect("key");
+        };
+
+        press(&mut vault, &mut app, KeyCode::Char('S'));
+        assert!(!app.show_selected_only);
+        assert_eq!(app.filtered_inbox().len(), 3);
+
+        app.selected_inbox.insert(app.inbox[0].id);
+        app.selected_inbox.insert(app.inbox[2].id);
+        press(&mut vault, &mut app, KeyCode::Char('S'));
+        let titles: Vec<&str> = app.filtered_inbox().iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, ["jq", "fd"]);
+
+        app.active_filter = Some("f".into());
+        assert_eq!(app.filtered_inbox().len(), 1);
+        app.active_filter = None;
+
+        app.selected_inbox.clear();
+        press(&mut vault, &mut app, KeyCode::Char('j'));
+        assert!(!app.show_selected_only);
+        assert_eq!(app.filtered_inbox().len(), 3);
+    }
+
+    #[test]
+    fn drift_report_splits_untracked_missing_and_tracked() {
+        let entry = |title: &str, source: &str| sample_entry(title).source(source).build().unwrap();
+        let change = |title: &str, source: &str| {
+            sample_change(title).source(source).build().unwrap()
+        };
+        let library = [
+            entry("jq", "homebrew"),
+            entry("wget", "homebrew"),
+            entry("notes", "manual"),
+            entry("jq", "npm"),
+        ];
+        let detected = [change("jq", "homebrew"), change("fd", "homebrew"), change("fd", "homebrew")];
+
+        let report = drift_report(&library, &detected);
+        assert_eq!(
+            report,
+            DriftReport {
+                untracked: vec![("homebrew".into(), "fd".into())],
+                missing: vec![("homebrew".into(), "wget".into())],
+                tracked: 1,
+            }
+        );
+
+        let mut app = App::new();
+        app.drift = Some(report);
+        app.show_drift = true;
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal");
+        terminal.draw(|frame| render_app(frame, &app)).expect("render");
+        let screen = buffer_to_string(terminal.backend().buffer());
+        assert!(screen.contains("Drift: 1 tracked"), "{screen}");
+        assert!(screen.contains("fd  [homebrew]") && screen.contains("wget  [homebrew]"));
+    }
+
+    #[test]
+    fn presence_status_names_the_detector() {
+        assert_eq!(
+            presence_status("jq", "homebrew", PresenceCheck::NoLongerDetected("homebrew")),
+            "jq: no longer detected (homebrew)"
+        );
+        assert_eq!(
+            presence_status("notes", "manual", PresenceCheck::NoDetector),
+            "No detector reports source manual"
+        );
+    }
+
+    #[test]
+    fn check_vault_path_reports_before_confirming() {
+        let temp = TempDir::new().expect("temp dir");
+        let root = temp.path().to_path_buf();
+        let file = root.join("notes.txt");
+        std::fs::write(&file, "x").expect("write file");
+
+        assert_eq!(check_vault_path(&root), PathCheck::Exists);
+        let nested = root.join("a").join("b");
+        assert_eq!(check_vault_path(&nested), PathCheck::WillCreate);
+        assert_eq!(
+            PathCheck::WillCreate.message(&nested),
+            Some(format!("Will create {}", nested.display()))
+        );
+        assert_eq!(check_vault_path(&file.join("vault")), PathCheck::NotDirectory(file.clone()));
+        assert_eq!(
+            PathCheck::NotWritable(root.clone()).message(&nested),
+            Some(format!("Parent not writable: {}", root.display()))
+        );
+        assert_eq!(std::fs::read_dir(&root).expect("read root").count(), 1, "probe removed");
+
+        let mut app = App::new();
+        app.settings_path = file.join("vault").display().to_string();
+        confirm_settings_change(&mut app, ConfirmAction::SwitchVault);
+        assert!(app.pending_confirm.is_none());
+        assert!(app.status.as_deref().unwrap_or_default().starts_with("Not a directory"));
+    }
+
+    #[test]
+    fn recent_entries_sort_by_detection_time_without_duplicates() {
+        let now = chrono::Utc::now();
+        let entry = |title: &str, days_ago: i64| {
+            sample_entry(title)
+                .detected_at(now - chrono::Duration::days(days_ago))
+                .build()
+                .unwrap()
+        };
+        let fd = entry("fd", 0);
+        // An imported old entry lands last in storage order but is not recent.
+        let library = vec![entry("jq", 2), fd.clone(), entry("bat", 1), fd, entry("imported", 90)];
+
+        let titles = |limit| {
+            recent_entries(&library, limit)
+                .iter()
+                .map(|entry| entry.title.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles(RECENT_ACTIVITY_LIMIT), ["fd", "bat", "jq", "imported"]);
+        assert_eq!(titles(2), ["fd", "bat"]);
+    }
+
+    #[test]
+    fn status_history_keeps_messages_and_errors_until_dismissed() {
+        let (_temp, mut vault) = temp_vault();
+        let mut app = App::new();
+        app.tab = Tab::Inbox;
+        let mut press = |app: &mut App, code: KeyCode| {
+            handle_key(&mut vault, app, KeyEvent::new(code, KeyModifiers::NONE)).expect("key")
+        };
+
+        press(&mut app, KeyCode::Char('S'));
+        assert_eq!(app.status.as_deref(), Some("No items selected; press space to select"));
+        app.report_error("Warning: potential secret detected".into());
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.status, None, "statuses last one key press");
+        assert!(app.error.is_some(), "errors stay until dismissed");
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.error, None);
+        let levels: Vec<_> = app.status_history.iter().map(|message| message.level).collect();
+        assert_eq!(levels, [StatusLevel::Info, StatusLevel::Error]);
+
+        press(&mut app, KeyCode::Char('M'));
+        assert!(app.show_messages);
+        press(&mut app, KeyCode::Char('q'));
+        assert!(!app.show_messages, "q closes the popup instead of quitting");
+
+        for index in 0..STATUS_HISTORY_LIMIT {
+            app.record_status(format!("message {index}"), StatusLevel::Info);
+        }
+        assert_eq!(app.status_history.len(), STATUS_HISTORY_LIMIT);
+        assert_eq!(app.status_history[0].text, "message 0");
+    }
+
+    #[test]
+    fn dashboard_lists_pinned_entries_beside_the_chart() {
+        let entry = |title: &str| sample_entry(title).build().unwrap();
+        let mut app = App::new();
+        app.library = vec![entry("jq"), entry("ripgrep")];
+        let render = |app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal");
+            terminal
+                .draw(|frame| render_dashboard(frame, frame.size(), app))
+                .expect("draw");
+            buffer_to_string(terminal.backend().buffer())
+        };
+
+        let screen = render(&app);
+        assert!(screen.contains("Top Sources"));
+        assert!(!screen.contains("Pinned"));
+
+        app.library[1].pinned = true;
+        let screen = render(&app);
+        assert!(screen.contains("Top Sources"));
+        assert!(screen.contains("Pinned (1)"));
+        assert!(screen.contains("★ ripgrep brew install ripgrep"));
+        assert!(!screen.contains("★ jq"));
+    }
+
+    #[test]
+    fn snooze_all_in_source_clears_that_source_and_badges_the_tab() {
+        let (_temp, mut vault) = temp_vault();
+        let change = |title: &str, source: &str| {
+            sample_change(title).source(source).build().unwrap()
+        };
+        vault
+            .save_inbox(&[change("jq", "homebrew"), change("eslint", "npm"), change("fd", "homebrew")])
+            .expect("save inbox");
+        let mut app = App::new();
+        load_data(&vault, &mut app).expect("load");
+        app.tab = Tab::Inbox;
+        let press = |vault: &mut FsVault, app: &mut App| {
+            handle_key(vault, app, KeyEvent::new(KeyCode::Char('Z'), KeyModifiers::NONE)).expect("key");
+        };
+
+        press(&mut vault, &mut app);
+        assert_eq!(app.inbox.len(), 3, "the All tab is never snoozed wholesale");
+
+        app.inbox_source_index = 1;
+        press(&mut vault, &mut app);
+        let titles: Vec<&str> = app.inbox.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, ["eslint"]);
+        assert_eq!(vault.load_snoozed().expect("snoozed").len(), 2);
+        assert_eq!(app.status.as_deref(), Some("Snoozed 2 item(s) from homebrew"));
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal");
+        terminal.draw(|frame| render_app(frame, &app)).expect("draw");
+        assert!(buffer_to_string(terminal.backend().buffer()).contains("Snoozed (2)"));
+    }
+
+    #[test]
+    fn goto_id_selects_entry_by_prefix_and_lists_ambiguous_candidates() {
+        let entry = |id: &str, title: &str, source: &str| Entry {
+            id: uuid::Uuid::parse_str(id).unwrap(),
+            ..sample_entry(title).source(source).build().unwrap()
+        };
+        let mut app = App::new();
+        app.library = vec![
+            entry("abcd1234-0000-4000-8000-000000000001", "jq", "homebrew"),
+            entry("abcd9999-0000-4000-8000-000000000002", "fd", "homebrew"),
+            entry("12345678-0000-4000-8000-000000000003", "eslint", "npm"),
+        ];
+        app.library_source_index = 1;
+        app.tab = Tab::Dashboard;
+
+        goto_entry(&mut app, "ABCD");
+        assert_eq!(app.tab, Tab::Dashboard);
+        assert_eq!(
+            app.status.as_deref(),
+            Some("'ABCD' matches 2 entries: abcd1234 jq, abcd9999 fd")
+        );
+
+        goto_entry(&mut app, "1234");
+        assert_eq!(app.tab, Tab::Library);
+        assert_eq!(app.library_source_index, 0);
+        assert_eq!(current_library_id(&app), Some(app.library[2].id));
+
+        app.active_filter = Some("eslint".into());
+        goto_entry(&mut app, "abcd9999-0000-4000-8000-000000000002");
+        assert_eq!(app.active_filter, None);
+        assert_eq!(current_library_id(&app), Some(app.library[1].id));
+
+        app.status = None;
+        goto_entry(&mut app, "ffff");
+        assert_eq!(app.status.as_deref(), Some("No entry id starts with 'ffff'"));
+    }
+
+    #[test]
+    fn clear_inbox_only_runs_after_confirmation() {
+        let (_temp, mut vault) = temp_vault();
+        let change = |title: &str| sample_change(title).build().unwrap();
+        vault.save_inbox(&[change("jq"), change("fd")]).expect("save inbox");
+        let mut app = App::new();
+        load_data(&vault, &mut app).expect("load");
+        app.tab = Tab::Inbox;
+        let press = |vault: &mut FsVault, app: &mut App, c: char| {
+            handle_key(vault, app, KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).expect("key");
+        };
+
+        press(&mut vault, &mut app, 'X');
+        assert!(matches!(app.input_mode, InputMode::Confirm));
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal");
+        terminal.draw(|frame| render_app(frame, &app)).expect("draw");
+        assert!(buffer_to_string(terminal.backend().buffer()).contains("Remove all 2 inbox item(s)"));
+        press(&mut vault, &mut app, 'n');
+        assert_eq!(vault.load_inbox().expect("inbox").len(), 2);
+        assert_eq!(app.status.as_deref(), Some("Inbox left unchanged"));
+
+        press(&mut vault, &mut app, 'X');
+        press(&mut vault, &mut app, 'y');
+        assert!(vault.load_inbox().expect("inbox").is_empty());
+        assert!(app.inbox.is_empty());
+        assert_eq!(app.status.as_deref(), Some("Cleared 2 inbox item(s)"));
+
+        press(&mut vault, &mut app, 'X');
+        assert!(matches!(app.input_mode, InputMode::None));
+        assert_eq!(app.status.as_deref(), Some("Inbox is already empty"));
+    }
+
+    #[test]
+    fn editing_a_rationale_keeps_the_previous_one_in_history() {
+        let (_temp, vault) = temp_vault();
+        let entry = sample_entry("jq")
+            .rationale(Rationale::new("Parse API responses").unwrap())
+            .build()
+            .unwrap();
+        vault.create(&entry).expect("create entry");
+        let mut app = App::new();
+        load_data(&vault, &mut app).expect("load");
+        app.tab = Tab::Library;
+
+        app.input.content = "Parse API responses and logs".into();
+        submit_rationale(&vault, &mut app).expect("submit");
+        let stored = vault.get(entry.id).expect("get").expect("entry");
+        assert_eq!(stored.rationale.as_str(), "Parse API responses and logs");
+        assert_eq!(stored.rationale_history.len(), 1);
+        assert_eq!(stored.rationale_history[0].1, "Parse API responses");
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal");
+        terminal.draw(|frame| render_app(frame, &app)).expect("draw");
+        assert!(buffer_to_string(terminal.backend().buffer()).contains("Rationale (1 prior revision(s)):"));
+    }
+
+    #[test]
+    fn external_changes_reload_only_what_differs() {
+        let (_temp, vault) = temp_vault();
+        let (jq, fd) = (sample_entry("jq").build().unwrap(), sample_entry("fd").build().unwrap());
+        vault.create(&jq).expect("create jq");
+        vault.create(&fd).expect("create fd");
+        let mut app = App::new();
+        load_data(&vault, &mut app).expect("load");
+        let index = app.library.iter().position(|entry| entry.id == fd.id).unwrap();
+        app.library_state.select(Some(index));
+        let entries = BTreeSet::from([VaultChange::Entries, VaultChange::Inbox]);
+
+        reload_external_changes(&vault, &mut app, &entries);
+        assert_eq!(app.status, None);
+
+        let mut edited = fd.clone();
+        edited.rationale = Rationale::new("faster than find").unwrap();
+        vault.update(&edited).expect("update fd");
+        vault.delete(jq.id).expect("delete jq");
+        reload_external_changes(&vault, &mut app, &entries);
+        assert_eq!(app.status.as_deref(), Some("Reloaded library after an external change"));
+        assert_eq!(app.library.len(), 1);
+        assert_eq!(app.library[0].rationale.as_str(), "faster than find");
+        assert_eq!(current_library_id(&app), Some(fd.id));
+    }
+
+    #[test]
+    fn external_reload_keeps_the_library_when_an_entry_fails_to_parse() {
+        let (temp, vault) = temp_vault();
+        let jq = sample_entry("jq").build().unwrap();
+        vault.create(&jq).expect("create jq");
+        let mut app = App::new();
+        load_data(&vault, &mut app).expect("load");
+
+        std::fs::write(temp.path().join("entries/half-written.md"), "---\nid: ").expect("write");
+        reload_external_changes(&vault, &mut app, &BTreeSet::from([VaultChange::Entries]));
+        assert_eq!(app.library.len(), 1);
+        assert_eq!(app.library[0].id, jq.id);
+        assert!(
+            app.error.as_deref().is_some_and(|error| error.starts_with("Failed to reload library")),
+            "{:?}",
+            app.error
+        );
+        assert_eq!(app.status, None);
+    }
+
+    #[test]
+    fn bulk_approving_one_terse_rationale_warns_and_marks_the_entries() {
+        let (_temp, vault) = temp_vault();
+        let inbox: Vec<_> = ["jq", "fd", "bat"]
+            .into_iter()
+            .map(|title| sample_change(title).build().unwrap())
+            .collect();
+        vault.save_inbox(&inbox).expect("save inbox");
+        let mut app = App::new();
+        load_data(&vault, &mut app).expect("load");
+        app.tab = Tab::Inbox;
+        app.selected_inbox = inbox.iter().map(|change| change.id).collect();
+        app.input.content = "cli".into();
+        submit_rationale(&vault, &mut app).expect("submit");
+        assert_eq!(app.library.len(), 3);
+        let error = app.error.clone().unwrap_or_default();
+        assert!(error.contains("the same rationale is used by 3 entries"), "{error}");
+
+        app.tab = Tab::Library;
+        app.library_state.select(Some(0));
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal");
+        terminal.draw(|frame| render_app(frame, &app)).expect("draw");
+        assert!(buffer_to_string(terminal.backend().buffer()).contains("⚠"));
+
+        app.rationale_quality_warnings = false;
+        app.refresh_weak_rationales();
+        assert!(app.weak_rationales.is_empty());
+        terminal.draw(|frame| render_app(frame, &app)).expect("draw");
+        assert!(!buffer_to_string(terminal.backend().buffer()).contains("⚠"));
+    }
+
+    #[test]
+    fn manual_capture_prefills_steps_from_the_entry_template() {
+        let (_temp, mut vault) = temp_vault();
+        let mut app = App::new();
+        let mut submit = |app: &mut App, text: &str| {
+            app.input = TextInput::from(text.to_string());
+            handle_key(&mut vault, app, KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                .expect("key");
+        };
+
+        open_manual_capture(&mut app);
+        submit(&mut app, "starship");
+        assert_eq!(app.manual_capture.as_ref().map(|capture| capture.step), Some(CaptureStep::EntryType));
+        app.config.entry_templates.insert(
+            "config".into(),
+            EntryTemplate {
+                tags: vec!["shell".into(), "prompt".into()],
+                verification: Some("Open a new shell".into()),
+                rationale: Some("Keeps {title} consistent across machines".into()),
+            },
+        );
+        submit(&mut app, "config");
+        let capture = app.manual_capture.as_ref().expect("capture");
+        assert_eq!(capture.step, CaptureStep::Rationale);
+        assert_eq!(
+            app.input.content,
+            "Keeps starship consistent across machines"
+        );
+
+        let rationale = app.input.content.clone();
+        submit(&mut app, &rationale);
+        assert_eq!(app.input.content, "");
+        submit(&mut app, "brew install starship");
+        assert_eq!(app.input.content, "shell, prompt");
+        app.config.tag_aliases.insert("sh".into(), "shell".into());
+        submit(&mut app, "sh");
+        assert_eq!(app.input.content, "Open a new shell");
+        submit(&mut app, "Open a new shell");
+
+        let entry = app.library.last().expect("captured entry");
+        assert_eq!(entry.entry_type, EntryType::Config);
+        assert_eq!(entry.rationale.as_str(), "Keeps starship consistent across machines");
+        assert_eq!(entry.tags, vec![Tag::new("shell").unwrap()]);
+        assert_eq!(entry.verification.as_deref(), Some("Open a new shell"));
+    }
+
+    #[test]
+    fn guide_bar_shows_queue_counts_when_wide_enough() {
+        let mut app = App::new();
+        app.tab = Tab::Library;
+        let draw = |width: u16, app: &App| {
+            let mut terminal = Terminal::new(TestBackend::new(width, 3)).expect("terminal");
+            terminal
+                .draw(|frame| render_guide_bar(frame, frame.size(), app))
+                .expect("render");
+            buffer_to_string(terminal.backend().buffer())
+        };
+
+        assert!(draw(120, &app).contains("Inbox: 0 | Snoozed: 0"));
+        assert!(!draw(40, &app).contains("Inbox:"));
+    }
+
+    fn buffer_to_string(buffer: &ratatui::buffer::Buffer) -> String {
+        let mut lines = Vec::new();
+        for y in 0..buffer.area.height {
+            let mut line = String::new();
+            for x in 0..buffer.area.width {
+                let cell = buffer.get(x, y);
+                line.push_str(cell.symbol());
+            }
+            lines.push(line.trim_end().to_string());
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn jump_finds_next_title_with_letter_and_wraps() {
+        let titles = ["git", "jq", "Gradle", "ripgrep"];
+        assert_eq!(next_title_match(&titles, None, 'g'), Some(0));
+        assert_eq!(next_title_match(&titles, Some(0), 'g'), Some(2));
+        assert_eq!(next_title_match(&titles, Some(2), 'G'), Some(0));
+        assert_eq!(next_title_match(&titles, Some(1), 'j'), Some(1));
+        assert_eq!(next_title_match(&titles, Some(0), 'z'), None);
+        assert_eq!(next_title_match(&[], None, 'a'), None);
+    }
+
+    #[test]
+    fn rationale_cursor_follows_newlines_and_word_wrap() {
+        let mut input = TextInput::default();
+        for c in "first line\nsecond".chars() {
+            input.insert(c);
+        }
+        assert_eq!(input.wrapped_cursor(40), (6, 1));
+
+        input = TextInput::from("alpha beta gamma".into());
+        assert_eq!(wrap_row_starts(&input.content, 12), vec![0, 11]);
+        assert_eq!(input.wrapped_cursor(12), (5, 1));
+        assert_eq!(wrap_row_starts("abcdefghij", 4), vec![0, 4, 8]);
+
+        input = TextInput::from("né\n".into());
+        input.delete_back();
+        input.delete_back();
+        assert_eq!(input.content, "n");
+    }
+
+    #[test]
+    fn source_labels_map_known_sources_and_keep_unknown_ones() {
+        assert_eq!(source_display("homebrew", false), "🍺 Homebrew");
+        assert_eq!(source_display("mac_defaults", true), "macOS Defaults");
+        assert_eq!(source_display("custom-tool", false), "custom-tool");
+        assert_eq!(source_display("All", false), "All");
+        assert_eq!(with_source_icon("jq", "homebrew", true), "jq");
+        assert_eq!(with_source_icon("jq", "homebrew", false), "🍺 jq");
+    }
+}
@@ -1,5 +1,15 @@
 use anyhow::Result;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(name = "sv-tui", version, about = "SetupVault TUI")]
+struct Args {
+    /// Run no detectors this session (refresh and the drift report scan nothing).
+    #[arg(long)]
+    no_detectors: bool,
+}
 
 fn main() -> Result<()> {
-    sv_tui::run()
+    let args = Args::parse();
+    sv_tui::run(args.no_detectors)
 }
@@ -1,5 +1,8 @@
 //! Shared helpers and error types for SetupVault.
 
+use std::path::Path;
+use std::process::{Command, Stdio};
+
 use thiserror::Error;
 
 /// Result type for shared helpers.
@@ -19,6 +22,47 @@ pub enum UtilsError {
     Parse(String),
 }
 
+/// Resolve the current machine's hostname, if it is valid UTF-8 and non-empty.
+pub fn hostname() -> Option<String> {
+    let name = gethostname::gethostname().into_string().ok()?;
+    let name = name.trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Warning raised when inspecting a detected change's path before approval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathWarning {
+    /// The path was removed after the change was detected.
+    Missing,
+    /// The file content looks like it contains a secret.
+    PotentialSecret,
+}
+
+impl std::fmt::Display for PathWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing => write!(f, "source path no longer exists"),
+            Self::PotentialSecret => write!(f, "potential secret detected"),
+        }
+    }
+}
+
+/// Check a detected change's path before approval.
+///
+/// Missing paths skip the secret scan; unreadable files are not flagged.
+pub fn inspect_change_path(path: &str) -> Option<PathWarning> {
+    let path = Path::new(path);
+    if !path.exists() {
+        return Some(PathWarning::Missing);
+    }
+    let contents = std::fs::read_to_string(path).ok()?;
+    contains_potential_secret(&contents).then_some(PathWarning::PotentialSecret)
+}
+
 /// Basic heuristic for detecting secrets in content.
 pub fn contains_potential_secret(contents: &str) -> bool {
     let lowered = contents.to_lowercase();
@@ -36,3 +80,283 @@ pub fn contains_potential_secret(contents: &str) -> bool {
     ];
     signals.iter().any(|signal| lowered.contains(signal))
 }
+
+/// A command that runs `script` through the current OS's shell: `cmd /C` on Windows and
+/// `sh -c` elsewhere.
+pub fn shell_command(script: &str) -> Command {
+    let (shell, flag) = shell_for(std::env::consts::OS);
+    let mut command = Command::new(shell);
+    command.args([flag, script]);
+    command
+}
+
+fn shell_for(os: &str) -> (&'static str, &'static str) {
+    match os {
+        "windows" => ("cmd", "/C"),
+        _ => ("sh", "-c"),
+    }
+}
+
+/// Program that opens a file or URL with its default application on the current OS:
+/// `open` on macOS, `start` on Windows, and `xdg-open` elsewhere.
+pub fn open_command() -> &'static str {
+    opener_for(std::env::consts::OS)
+}
+
+fn opener_for(os: &str) -> &'static str {
+    match os {
+        "macos" => "open",
+        "windows" => "start",
+        _ => "xdg-open",
+    }
+}
+
+/// Shell command that opens `path` with its default application, as [`open_path`] does.
+///
+/// On Windows this is `start ""` with `cmd.exe` quoting; elsewhere the opener with POSIX
+/// quoting.
+pub fn open_path_command(path: &str) -> String {
+    open_path_command_for(std::env::consts::OS, path)
+}
+
+fn open_path_command_for(os: &str, path: &str) -> String {
+    match os {
+        "windows" => format!("start \"\" {}", shell_quote_windows(path)),
+        _ => format!("{} {}", opener_for(os), shell_quote(path)),
+    }
+}
+
+/// Open `path` with its default application, without waiting for it to exit.
+///
+/// Fails when the path does not exist or the opener cannot be started. On Windows `start` is
+/// a `cmd.exe` builtin, so it runs through `cmd /C start ""`.
+pub fn open_path(path: &str) -> UtilsResult<()> {
+    if !Path::new(path).exists() {
+        return Err(UtilsError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("{path} does not exist"),
+        )));
+    }
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", open_command(), ""]);
+        command
+    } else {
+        Command::new(open_command())
+    };
+    let mut child = command
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| {
+            std::io::Error::new(
+                err.kind(),
+                format!("failed to run {}: {err}", open_command()),
+            )
+        })?;
+    // Reap the opener in the background so callers such as the TUI never block on it.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Rationales shorter than this many characters, ignoring surrounding space, look weak.
+pub const MIN_RATIONALE_CHARS: usize = 10;
+
+/// A rationale shared by at least this many entries looks like bulk boilerplate.
+pub const MAX_RATIONALE_REUSE: usize = 3;
+
+/// Why a rationale looks too thin to document the change it belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RationaleWarning {
+    /// The rationale is shorter than [`MIN_RATIONALE_CHARS`].
+    TooShort,
+    /// The same rationale is used by `count` entries.
+    Reused { count: usize },
+}
+
+impl std::fmt::Display for RationaleWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooShort => write!(f, "rationale is under {MIN_RATIONALE_CHARS} characters"),
+            Self::Reused { count } => write!(f, "the same rationale is used by {count} entries"),
+        }
+    }
+}
+
+/// Judge a rationale shared by `uses` entries; `None` when it looks fine.
+///
+/// Reuse is reported before length, since a repeated short rationale is the bulk-approval case.
+pub fn rationale_warning(text: &str, uses: usize) -> Option<RationaleWarning> {
+    if uses >= MAX_RATIONALE_REUSE {
+        return Some(RationaleWarning::Reused { count: uses });
+    }
+    (text.trim().chars().count() < MIN_RATIONALE_CHARS).then_some(RationaleWarning::TooShort)
+}
+
+/// Judge each rationale in a batch, counting identical ones regardless of case and spacing.
+pub fn rationale_warnings<S: AsRef<str>>(texts: &[S]) -> Vec<Option<RationaleWarning>> {
+    let key = |text: &str| {
+        text.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    };
+    let mut uses = std::collections::HashMap::new();
+    for text in texts {
+        *uses.entry(key(text.as_ref())).or_insert(0) += 1;
+    }
+    texts
+        .iter()
+        .map(|text| rationale_warning(text.as_ref(), uses[&key(text.as_ref())]))
+        .collect()
+}
+
+/// Quote `arg` as one word for a POSIX shell.
+///
+/// Arguments made only of characters no shell treats specially are returned unchanged;
+/// anything else is single-quoted, with embedded single quotes written as `'\''`.
+pub fn shell_quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./:@%+=,".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Quote `arg` as one word for `cmd.exe`, the Windows counterpart of [`shell_quote`].
+///
+/// Arguments without spaces or metacharacters are returned unchanged; anything else is
+/// wrapped in double quotes, with embedded double quotes doubled.
+pub fn shell_quote_windows(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-.:\\/+,=@".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        return arg.to_string();
+    }
+    format!("\"{}\"", arg.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inspect_change_path_flags_missing_paths() {
+        let missing = concat!(env!("CARGO_MANIFEST_DIR"), "/does-not-exist.conf");
+        assert_eq!(inspect_change_path(missing), Some(PathWarning::Missing));
+
+        let present = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        assert_eq!(inspect_change_path(present), None);
+    }
+
+    #[test]
+    fn shell_command_uses_the_platform_shell() {
+        assert_eq!(shell_for("windows"), ("cmd", "/C"));
+        assert_eq!(shell_for("macos"), ("sh", "-c"));
+        assert_eq!(shell_for("linux"), ("sh", "-c"));
+        if cfg!(unix) {
+            let status = shell_command("exit 3").status().expect("run shell");
+            assert_eq!(status.code(), Some(3));
+        }
+    }
+
+    #[test]
+    fn open_command_matches_the_target_os() {
+        assert_eq!(opener_for("macos"), "open");
+        assert_eq!(opener_for("windows"), "start");
+        assert_eq!(opener_for("linux"), "xdg-open");
+        assert_eq!(opener_for("freebsd"), "xdg-open");
+
+        let expected = if cfg!(target_os = "macos") {
+            "open"
+        } else if cfg!(target_os = "windows") {
+            "start"
+        } else {
+            "xdg-open"
+        };
+        assert_eq!(open_command(), expected);
+
+        assert_eq!(
+            open_path_command_for("linux", "/home/me/.zshrc"),
+            "xdg-open /home/me/.zshrc"
+        );
+        assert_eq!(
+            open_path_command_for("macos", "/Users/me/it's.conf"),
+            r"open '/Users/me/it'\''s.conf'"
+        );
+        assert_eq!(
+            open_path_command_for("windows", r"C:\Users\me\My Config\.gitconfig"),
+            r#"start "" "C:\Users\me\My Config\.gitconfig""#
+        );
+
+        let missing = concat!(env!("CARGO_MANIFEST_DIR"), "/does-not-exist.conf");
+        assert!(open_path(missing).is_err());
+    }
+
+    #[test]
+    fn rationale_warnings_flag_short_and_reused_rationales() {
+        assert_eq!(rationale_warning("ok", 1), Some(RationaleWarning::TooShort));
+        assert_eq!(
+            rationale_warning("  needed   ", 1),
+            Some(RationaleWarning::TooShort)
+        );
+        assert_eq!(rationale_warning("Parses JSON in API scripts", 1), None);
+        assert_eq!(
+            rationale_warning("ok", 3),
+            Some(RationaleWarning::Reused { count: 3 })
+        );
+
+        let warnings = rationale_warnings(&[
+            "Needed for work",
+            "needed  for WORK",
+            "Parses JSON in API scripts",
+            "Needed for work ",
+            "tmp",
+        ]);
+        let reused = Some(RationaleWarning::Reused { count: 3 });
+        assert_eq!(
+            warnings,
+            [
+                reused,
+                reused,
+                None,
+                reused,
+                Some(RationaleWarning::TooShort)
+            ]
+        );
+    }
+
+    #[test]
+    fn shell_quote_keeps_names_with_spaces_quotes_and_dollars_as_one_word() {
+        assert_eq!(shell_quote("/Users/me/.zshrc"), "/Users/me/.zshrc");
+        assert_eq!(
+            shell_quote("org.gnome.Terminal.desktop"),
+            "org.gnome.Terminal.desktop"
+        );
+        assert_eq!(
+            shell_quote("/Applications/Visual Studio Code.app"),
+            "'/Applications/Visual Studio Code.app'"
+        );
+        assert_eq!(
+            shell_quote("/Applications/Bob's \"Tools\".app"),
+            r#"'/Applications/Bob'\''s "Tools".app'"#
+        );
+        assert_eq!(shell_quote("$HOME/`x`\nnext"), "'$HOME/`x`\nnext'");
+        assert_eq!(shell_quote(""), "''");
+
+        assert_eq!(
+            shell_quote_windows(r"C:\Tools\app.exe"),
+            r"C:\Tools\app.exe"
+        );
+        assert_eq!(
+            shell_quote_windows(r"C:\Program Files\Git"),
+            r#""C:\Program Files\Git""#
+        );
+        assert_eq!(
+            shell_quote_windows(r#"say "hi" & $x"#),
+            r#""say ""hi"" & $x""#
+        );
+        assert_eq!(shell_quote_windows(""), r#""""#);
+    }
+}
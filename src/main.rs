@@ -1,5 +1,9 @@
 use anyhow::Result;
 
 fn main() -> Result<()> {
-    sv_cli::run()
+    let outcome = sv_cli::run()?;
+    if outcome != sv_cli::Outcome::Success {
+        std::process::exit(outcome.code());
+    }
+    Ok(())
 }